@@ -0,0 +1,62 @@
+//! 顶层导航审计日志，写入数据目录并按大小轮转
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const AUDIT_FILE: &str = "pake-navigation-audit.log";
+/// 默认在日志超过 1 MB 时轮转
+pub const DEFAULT_AUDIT_MAX_KB: u64 = 1024;
+
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    /// 当前页面地址，作为下一次导航的来源
+    current_url: RefCell<String>,
+}
+
+impl AuditLog {
+    pub fn new(data_dir: &Path, max_kb: u64) -> Self {
+        AuditLog {
+            path: data_dir.join(AUDIT_FILE),
+            max_bytes: max_kb.max(1) * 1024,
+            current_url: RefCell::new(String::new()),
+        }
+    }
+
+    /// 记录一次顶层导航，`outcome` 为 allowed / blocked / rewritten
+    pub fn record(&self, to: &str, outcome: &str) {
+        let from = self.current_url.borrow().clone();
+        if outcome == "allowed" {
+            *self.current_url.borrow_mut() = to.to_string();
+        }
+        self.rotate_if_needed();
+        let line = format!(
+            "{}\t{}\t{}\t{}\n",
+            chrono::Local::now().to_rfc3339(),
+            outcome,
+            from,
+            to
+        );
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        if let Err(e) = result {
+            eprintln!("警告: 无法写入导航审计日志: {:?}", e);
+        }
+    }
+
+    /// 超过大小上限时将当前日志改名为 `.1`，只保留一个历史文件
+    fn rotate_if_needed(&self) {
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size < self.max_bytes {
+            return;
+        }
+        let rotated = self.path.with_extension("log.1");
+        if let Err(e) = std::fs::rename(&self.path, &rotated) {
+            eprintln!("警告: 无法轮转导航审计日志: {:?}", e);
+        }
+    }
+}
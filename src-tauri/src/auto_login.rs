@@ -0,0 +1,94 @@
+//! `auto_login`：在登录页自动填写并提交固定账号
+//!
+//! 是否为登录页以 WebView 的真实地址判断，不使用页面通过 `url_changed:` 上报的地址，
+//! 否则任何页面都可以冒充登录页拿到密码
+use crate::config::AutoLogin;
+use crate::navigation::{js_string, UrlPatterns};
+use url::Url;
+
+pub struct AutoLoginPage {
+    page: UrlPatterns,
+    login: String,
+}
+
+impl AutoLoginPage {
+    /// 空的地址规则会匹配所有页面，视为未配置
+    pub fn new(login: &AutoLogin) -> Option<Self> {
+        if login.url_pattern.is_empty() {
+            return None;
+        }
+        let page = UrlPatterns::new(std::slice::from_ref(&login.url_pattern));
+        if page.is_empty() {
+            return None;
+        }
+        Some(AutoLoginPage {
+            page,
+            login: serde_json::to_string(login).ok()?,
+        })
+    }
+
+    /// 地址为匹配的 http(s) 登录页时返回填写脚本；脚本执行时再核对一次页面的 origin，防止期间页面已经跳走
+    pub fn script_for(&self, url: &Url) -> Option<String> {
+        if !matches!(url.scheme(), "http" | "https") || !self.page.is_match(url.as_str()) {
+            return None;
+        }
+        let origin = url.origin().ascii_serialization();
+        Some(format!(
+            "if (window.location.origin === {}) {{ window.pakeAutoLogin({}); }}",
+            js_string(&origin),
+            self.login
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(url_pattern: &str) -> AutoLogin {
+        AutoLogin {
+            url_pattern: url_pattern.to_string(),
+            username_selector: "#user".to_string(),
+            password_selector: "#pass".to_string(),
+            submit_selector: "#submit".to_string(),
+            username: "kiosk".to_string(),
+            password: "secret".to_string(),
+        }
+    }
+
+    fn script(page: &AutoLoginPage, url: &str) -> Option<String> {
+        page.script_for(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn fills_only_on_the_login_page() {
+        let page = AutoLoginPage::new(&login(r"https://example\.com/login.*")).unwrap();
+        let script = script(&page, "https://example.com/login?next=/").unwrap();
+        assert!(script.starts_with(r#"if (window.location.origin === "https://example.com")"#));
+        assert!(script.contains(r#""password":"secret""#));
+        assert!(script_for_none(&page, "https://example.com/home"));
+    }
+
+    #[test]
+    fn rejects_pages_that_only_mention_the_login_url() {
+        let page = AutoLoginPage::new(&login(r"https://example\.com/login.*")).unwrap();
+        assert!(script_for_none(
+            &page,
+            "https://evil.test/?x=https://example.com/login"
+        ));
+        assert!(script_for_none(
+            &page,
+            "https://example.com.evil.test/login"
+        ));
+    }
+
+    #[test]
+    fn empty_or_invalid_pattern_disables_auto_login() {
+        assert!(AutoLoginPage::new(&login("")).is_none());
+        assert!(AutoLoginPage::new(&login("(")).is_none());
+    }
+
+    fn script_for_none(page: &AutoLoginPage, url: &str) -> bool {
+        script(page, url).is_none()
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+/// 各平台 WebView 的 HTTP 缓存目录
+#[cfg(target_os = "windows")]
+pub fn webview_cache_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    let profile = data_dir.join("EBWebView").join("Default");
+    vec![profile.join("Cache"), profile.join("Code Cache")]
+}
+
+#[cfg(target_os = "linux")]
+pub fn webview_cache_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    vec![data_dir.join("WebKitCache"), data_dir.join("CacheStorage")]
+}
+
+#[cfg(target_os = "macos")]
+pub fn webview_cache_dirs(_data_dir: &Path) -> Vec<PathBuf> {
+    // WKWebView 使用默认数据存储，缓存位于 ~/Library/Caches/<bundle id>/WebKit
+    let conf: serde_json::Value =
+        serde_json::from_str(include_str!("../tauri.macos.conf.json")).unwrap_or_default();
+    match (
+        dirs::cache_dir(),
+        conf["tauri"]["bundle"]["identifier"].as_str(),
+    ) {
+        (Some(cache_dir), Some(identifier)) => vec![cache_dir.join(identifier).join("WebKit")],
+        _ => vec![],
+    }
+}
+
+/// 缓存总大小超过 `max_cache_mb` 时清空缓存目录，需要在创建 WebView 之前调用
+pub fn enforce_limit(cache_dirs: &[PathBuf], max_cache_mb: u64) {
+    let limit = max_cache_mb.saturating_mul(1024 * 1024);
+    let total: u64 = cache_dirs.iter().map(|dir| dir_size(dir)).sum();
+    if total <= limit {
+        return;
+    }
+    println!(
+        "缓存大小 {} MB 超过上限 {} MB，开始清理",
+        total / 1024 / 1024,
+        limit / 1024 / 1024
+    );
+    for dir in cache_dirs {
+        clear_dir(dir);
+    }
+    let remaining: u64 = cache_dirs.iter().map(|dir| dir_size(dir)).sum();
+    println!("缓存清理完成，剩余 {} MB", remaining / 1024 / 1024);
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn clear_dir(path: &Path) {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let result = if entry_path.is_dir() {
+            std::fs::remove_dir_all(&entry_path)
+        } else {
+            std::fs::remove_file(&entry_path)
+        };
+        // 其他实例正在使用的文件可能被占用，跳过即可，下次启动时再清理
+        if let Err(e) = result {
+            eprintln!("警告: 无法删除缓存 {}: {:?}", entry_path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_dir(name: &str, size: usize) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pake-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested").join("entry"), vec![0u8; size]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn keeps_caches_under_the_limit() {
+        let dir = cache_dir("under", 1024);
+        enforce_limit(&[dir.clone()], 1);
+        assert_eq!(dir_size(&dir), 1024);
+    }
+
+    #[test]
+    fn clears_caches_over_the_limit_but_keeps_the_directories() {
+        let dir = cache_dir("over", 2 * 1024 * 1024);
+        enforce_limit(&[dir.clone()], 1);
+        assert!(dir.is_dir());
+        assert_eq!(dir_size(&dir), 0);
+    }
+}
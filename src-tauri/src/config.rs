@@ -0,0 +1,666 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// JS 原生对话框（alert/confirm/prompt）的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogMode {
+    /// 交给平台 WebView 处理（保持原有行为）
+    #[default]
+    Default,
+    /// 自动确认，适用于 kiosk 模式：confirm 返回 true，prompt 返回默认值
+    Accept,
+    /// 自动取消：confirm 返回 false，prompt 返回 null
+    Dismiss,
+    /// 使用系统对话框显示，prompt 返回输入的内容；macOS 上 WKWebView 默认不显示这些对话框
+    Native,
+}
+
+/// URL 重写规则的匹配方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewriteKind {
+    /// `from_pattern` 作为前缀匹配，命中部分替换为 `to`
+    #[default]
+    Prefix,
+    /// `from_pattern` 作为正则匹配，`to` 中可使用 `$1` 等捕获组
+    Regex,
+}
+
+/// 一条导航重写规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlRewrite {
+    pub from_pattern: String,
+    pub to: String,
+    #[serde(default)]
+    pub kind: RewriteKind,
+}
+
+/// 时间表中的一个时间段，时间为本地时间 `HH:MM`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub from: String,
+    pub to: String,
+    pub url: String,
+}
+
+/// 窗口层级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowLevel {
+    #[default]
+    Normal,
+    AlwaysOnTop,
+    /// 固定在普通窗口之下，类似桌面小组件
+    AlwaysBelow,
+}
+
+/// 页面通过 beforeunload 阻止关闭时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeforeUnloadMode {
+    /// 弹出原生确认框，由用户决定是否关闭
+    Confirm,
+    /// 忽略页面的警告直接关闭，与之前的行为一致，适用于 kiosk 模式
+    #[default]
+    Ignore,
+}
+
+/// 强制使用的主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForceTheme {
+    Light,
+    Dark,
+}
+
+/// 启动图片的缩放方式，对应 CSS `object-fit`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplashFit {
+    #[default]
+    Contain,
+    Cover,
+}
+
+/// 鼠标中键点击链接时的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MiddleClickBehavior {
+    /// 交给 WebView 处理（保持原有行为）
+    #[default]
+    Default,
+    /// 在系统浏览器中打开
+    External,
+    /// 在新的 Pake 窗口中打开
+    NewWindow,
+    /// 忽略中键点击
+    Ignore,
+}
+
+/// 点击窗口关闭按钮时的行为，不影响 Cmd+Q 等退出操作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseButtonBehavior {
+    /// 退出应用（保持原有行为）
+    #[default]
+    Quit,
+    /// 隐藏：macOS 隐藏应用，其他平台需要托盘才能恢复，没有托盘时改为最小化
+    Hide,
+    Minimize,
+}
+
+/// 页面调用 `window.print()` 时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrintMode {
+    /// 弹出系统打印对话框
+    #[default]
+    Dialog,
+    /// 直接打印为 PDF 保存到下载目录
+    Pdf,
+}
+
+/// 窗口失去焦点时的处理方式，适合悬浮小组件
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlurBehavior {
+    #[default]
+    None,
+    /// 降低窗口不透明度，重新获得焦点时恢复
+    Dim,
+    /// 隐藏窗口，规则与 `close_button_behavior` 的 `hide` 相同
+    Hide,
+}
+
+/// 内存占用超过 `max_memory_mb` 时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HighMemoryAction {
+    /// 只输出日志
+    #[default]
+    None,
+    /// 向页面发送 `pake:high_memory` 事件，detail 为当前占用（MB）
+    Notify,
+    /// 重新加载当前页面以释放内存
+    Reload,
+}
+
+/// 在登录页自动填写并提交的固定账号，适用于共享的自助终端
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoLogin {
+    /// 登录页完整地址的正则表达式（首尾自动锚定），只在匹配的 http(s) 页面填写
+    pub url_pattern: String,
+    pub username_selector: String,
+    pub password_selector: String,
+    /// 为空时提交密码框所在的表单
+    pub submit_selector: String,
+    pub username: String,
+    pub password: String,
+}
+
+// 手动实现以免密码出现在日志中
+impl std::fmt::Debug for AutoLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoLogin")
+            .field("url_pattern", &self.url_pattern)
+            .field("username_selector", &self.username_selector)
+            .field("password_selector", &self.password_selector)
+            .field("submit_selector", &self.submit_selector)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// 窗口尺寸，可以是像素值或显示器工作区（去掉任务栏、Dock 后的区域）尺寸的百分比（如 `"80%"`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WindowDimension {
+    Pixels(f64),
+    Text(String),
+}
+
+impl WindowDimension {
+    pub fn is_percent(&self) -> bool {
+        matches!(self, WindowDimension::Text(s) if s.trim().ends_with('%'))
+    }
+
+    /// 按屏幕长度（逻辑像素）换算为逻辑像素，格式无效时返回 None
+    pub fn resolve(&self, screen: f64) -> Option<f64> {
+        let value = match self {
+            WindowDimension::Pixels(pixels) => *pixels,
+            WindowDimension::Text(s) => match s.trim().strip_suffix('%') {
+                Some(percent) => screen * percent.trim().parse::<f64>().ok()? / 100.0,
+                None => s.trim().parse().ok()?,
+            },
+        };
+        (value > 0.0).then_some(value)
+    }
+}
+
+/// 启动时显示窗口的显示器，可以是 `available_monitors()` 中的序号或显示器名称
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl MonitorSelector {
+    /// 在按 `available_monitors()` 顺序排列的显示器名称中查找，名称忽略大小写
+    pub fn find(&self, names: &[Option<String>]) -> Option<usize> {
+        match self {
+            MonitorSelector::Index(index) => (*index < names.len()).then_some(*index),
+            MonitorSelector::Name(name) => names.iter().position(|n| {
+                n.as_deref()
+                    .map_or(false, |n| n.trim().eq_ignore_ascii_case(name.trim()))
+            }),
+        }
+    }
+}
+
+/// Pake 自身的扩展配置，读取自 `pake.json`，所有字段都有默认值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PakeConfig {
+    /// 页面 JS 对话框的处理方式
+    pub dialog_mode: DialogMode,
+    /// 导航重写表，命中时取消原导航并跳转到重写后的地址
+    pub url_rewrites: Vec<UrlRewrite>,
+    /// WebView 缓存上限（MB），启动时超过上限则清空缓存，默认不限制
+    pub max_cache_mb: Option<u64>,
+    /// 允许导航的域名（包含子域名），为空时不限制
+    pub navigation_allowlist: Vec<String>,
+    /// 关闭 GPU 加速，改用软件渲染，解决部分虚拟机/旧显卡白屏或崩溃
+    pub disable_gpu: bool,
+    /// 额外的 WebView2 (Chromium) 启动参数，仅 Windows 生效
+    pub webview2_additional_args: Option<String>,
+    /// 随应用分发的固定版本 WebView2 运行时目录，仅 Windows 生效
+    pub webview2_fixed_runtime_path: Option<PathBuf>,
+    /// WebKitGTK 设置透传，仅 Linux 生效，支持的键见 `engine::apply_webkit_settings`
+    pub webkit_settings: BTreeMap<String, Value>,
+    /// WKPreferences 设置透传，仅 macOS 生效，支持的键见 `engine::apply_wkwebview_prefs`
+    pub wkwebview_prefs: BTreeMap<String, Value>,
+    /// Safari 内容拦截规则（JSON）文件路径，仅 macOS 生效
+    pub content_blocker_rules: Option<PathBuf>,
+    /// 是否平滑滚动，不设置时使用平台默认行为
+    pub smooth_scrolling: Option<bool>,
+    /// 鼠标滚轮滚动距离的倍数，不设置时使用平台默认行为
+    pub scroll_multiplier: Option<f64>,
+    /// 记住窗口位置、大小以及最大化/全屏状态，下次启动时恢复
+    pub remember_window_state: bool,
+    /// 自定义快捷键，键为 IPC 命令，值为快捷键（如 `CmdOrCtrl+Shift+P`）
+    pub shortcuts: BTreeMap<String, String>,
+    /// 点击关闭按钮时隐藏到托盘而不是退出，通过托盘菜单的 Quit 退出
+    pub close_to_tray: bool,
+    /// 最小化时隐藏到托盘而不是任务栏
+    pub minimize_to_tray: bool,
+    /// 将每次顶层导航记录到数据目录下的审计日志
+    pub audit_navigation: bool,
+    /// 审计日志轮转大小（KB），默认 1024
+    pub audit_max_kb: Option<u64>,
+    /// 按时间段显示不同地址，无匹配时显示默认 url
+    pub schedule: Vec<ScheduleEntry>,
+    /// 窗口层级
+    pub window_level: WindowLevel,
+    /// 关闭窗口时页面有未保存内容（beforeunload）的处理方式
+    pub beforeunload: BeforeUnloadMode,
+    /// 默认开启高对比度模式，运行时可通过 `toggle_high_contrast` 切换
+    pub high_contrast: bool,
+    /// 强制窗口使用指定主题，设置后不再跟随系统主题变化
+    pub force_theme: Option<ForceTheme>,
+    /// 调整窗口大小时的宽度步进（逻辑像素），与最小/最大尺寸同时生效
+    pub resize_increment_width: Option<f64>,
+    /// 调整窗口大小时的高度步进（逻辑像素）
+    pub resize_increment_height: Option<f64>,
+    /// 页面加载后自动聚焦第一个可见的输入框，可能抢走其他应用的焦点，默认关闭
+    pub auto_focus_input: bool,
+    /// 拖动窗口时的不透明度（0.0–1.0），松开鼠标后恢复，便于对齐下方内容
+    pub drag_opacity: Option<f64>,
+    /// 不显示内置的下载完成提示，改为向页面派发 `pake:download_complete` 事件
+    pub suppress_download_toast: bool,
+    /// 页面加载完成前居中显示的启动图片路径
+    pub splash_image: Option<PathBuf>,
+    /// 启动图片的缩放方式
+    pub splash_fit: SplashFit,
+    /// 启动时窗口淡入的时长（毫秒），页面首帧绘制后开始，0 表示直接显示
+    pub fade_in_ms: u64,
+    /// 窗口标题模板，支持 `{title}`、`{url}`、`{host}`，如 `{title} — MyApp`
+    pub title_template: Option<String>,
+    /// 鼠标中键点击链接时的行为
+    pub middle_click_behavior: MiddleClickBehavior,
+    /// 主地址加载失败时依次尝试的备用地址
+    pub fallback_urls: Vec<String>,
+    /// 页面加载超时（毫秒），超时后切换到下一个备用地址，默认 15000
+    pub load_timeout_ms: Option<u64>,
+    /// 切换到备用地址后多久（秒）再尝试回到主地址，默认 300
+    pub fallback_cooldown_secs: Option<u64>,
+    /// 关闭密码保存提示和表单自动填充，适用于共享设备
+    pub disable_autofill: bool,
+    /// 在系统浏览器中打开链接前弹窗确认
+    pub confirm_external_open: bool,
+    /// 导航到白名单外的地址时弹窗确认，而不是直接拦截
+    pub confirm_offsite_navigation: bool,
+    /// 自定义数据目录（WebView 数据、窗口状态、日志等），环境变量 `PAKE_DATA_DIR` 优先；
+    /// macOS 上 WKWebView 没有公开接口把网站数据存储放到指定目录，Cookie、缓存等仍在系统默认位置，
+    /// 不在本配置的支持范围内
+    pub data_dir: Option<PathBuf>,
+    /// 启动后超过该时长（毫秒）页面仍未就绪时，提示加载较慢并提供重试/取消
+    pub slow_load_ms: Option<u64>,
+    /// 点击窗口关闭按钮时的行为
+    pub close_button_behavior: CloseButtonBehavior,
+    /// 页面出现该 CSS 选择器对应的元素后才算就绪并关闭启动画面，适用于 SPA
+    pub ready_selector: Option<String>,
+    /// 等待 `ready_selector` 的超时（毫秒），默认 10000
+    pub ready_timeout_ms: Option<u64>,
+    /// 定时截取页面内容保存到数据目录下的 `pake-monitor.png`（只保留最近一张），用于远程确认
+    /// 标牌屏幕显示是否正常；截图包含页面上的全部内容，可能含有个人信息，仅在受控设备上开启
+    pub monitor_screenshot_interval_secs: Option<u64>,
+    /// 用 PageDown/PageUp/Home/End 触发 `scroll_*` 命令滚动页面，适用于翻页器和无障碍场景
+    pub keyboard_scroll: bool,
+    /// `scroll_down`/`scroll_up` 的滚动距离（像素），默认为可视高度的 90%
+    pub scroll_amount: Option<f64>,
+    /// 初始缩放比例（1.0 为 100%）
+    pub initial_zoom: Option<f64>,
+    /// 按域名记住缩放比例并保存到数据目录，未记录的域名使用 `initial_zoom`
+    pub per_host_zoom: bool,
+    /// 页面调用 `window.print()` 时的处理方式
+    pub print_mode: PrintMode,
+    /// 初始窗口宽度，覆盖 tauri.conf.json 中的 `width`，支持 `"80%"` 这样的屏幕百分比
+    pub window_width: Option<WindowDimension>,
+    /// 初始窗口高度，覆盖 tauri.conf.json 中的 `height`
+    pub window_height: Option<WindowDimension>,
+    /// 在 `crash_loop_window_secs` 内启动这么多次都没能正常运行时，弹窗提示并停止启动，防止被反复拉起
+    pub crash_loop_max_launches: Option<u32>,
+    /// 崩溃循环检测的时间窗口（秒），默认 60
+    pub crash_loop_window_secs: Option<u64>,
+    /// 系统从睡眠中唤醒后重新加载当前页面，适合聊天、看板等实时数据页面
+    pub reload_on_wake: bool,
+    /// 窗口模式下启动时显示在指定显示器上并居中，找不到时使用主显示器
+    pub start_monitor: Option<MonitorSelector>,
+    /// 锁定缩放为 100%，拦截 Ctrl+滚轮、捏合手势和缩放快捷键，开启时忽略 `initial_zoom` 和 `per_host_zoom`
+    pub lock_zoom: bool,
+    /// 页面加载完成后在后台依次请求这些地址，预先放入 WebView 缓存，加快之后的跳转
+    pub preload_urls: Vec<String>,
+    /// 窗口和托盘图标跟随页面的 favicon，加载失败时使用打包的图标
+    pub sync_favicon: bool,
+    /// 窗口圆角半径（逻辑像素），仅 Linux 生效，用于无边框窗口与 GTK 应用保持一致；默认直角
+    pub rounded_corners: Option<f64>,
+    /// 把拖入窗口的本地文件交给页面的文件输入框或上传区域，适合上传类页面
+    pub forward_file_drops: bool,
+    /// 页面加载失败时显示的错误页标题，配置了任一错误页字段时才使用 Pake 的错误页，否则保留引擎默认的错误页
+    pub error_page_title: Option<String>,
+    pub error_page_message: Option<String>,
+    /// 错误页上显示的支持联系方式，如 IT 部门的邮箱或电话
+    pub support_contact: Option<String>,
+    /// 完整的自定义错误页 HTML 文件，页面中可以通过 `window.ipc.postMessage("retry_load")` 重试
+    pub error_page_path: Option<PathBuf>,
+    /// 窗口失去焦点时变暗或隐藏，默认不处理
+    pub blur_behavior: BlurBehavior,
+    /// 启动时检查下载目录和数据目录的剩余空间（MB），低于该值时弹窗提醒；默认不检查
+    pub min_free_space_mb: Option<u64>,
+    /// 剩余空间低于 `min_free_space_mb` 时拒绝新的下载，直到空间释放
+    pub refuse_downloads_on_low_space: bool,
+    /// 显示由 pake.js 绘制的悬浮工具栏，平时隐藏，鼠标移到窗口底部时出现
+    pub show_toolbar: bool,
+    /// 工具栏按钮及顺序，可选 back、forward、home、set_home、reload、zoom_in、zoom_out、zoom_reset、print；
+    /// 为空时使用 back、forward、home、reload、zoom_out、zoom_in
+    pub toolbar_buttons: Vec<String>,
+    /// 捕获页面未处理的 JS 错误和 Promise 拒绝并输出到日志
+    pub capture_js_errors: bool,
+    /// 同时把捕获的 JS 错误保存到数据目录的 `pake-js-errors.log`
+    pub save_js_errors: bool,
+    /// 把当前地址和页面加载状态（loading/ready/error）写入数据目录的 `pake-health.json`，供数字标牌监控；
+    /// 需要通过 HTTP 读取时使用 `health_port`
+    pub report_page_state: bool,
+    /// 页面播放视频期间阻止系统和显示器休眠，页面也可以通过 `keep_awake:true/false` 自行控制
+    pub prevent_sleep: bool,
+    /// 页面看到的时区（IANA 名称，如 `Asia/Tokyo`），用于测试。Linux 上通过 `TZ` 环境变量原生生效；
+    /// 所有平台上 pake.js 都会替换 `Intl.DateTimeFormat` 的默认时区、`getTimezoneOffset` 和 `toLocale*String`，
+    /// 但 `getHours()` 等本地时间方法只在 Linux 上跟随
+    pub override_timezone: Option<String>,
+    /// 页面看到的语言（如 `ja-JP`），原生设置 Accept-Language 和 `navigator.language`：
+    /// Windows 使用 WebView2 的 `--lang`，Linux 使用 `LANGUAGE` 环境变量；macOS 上 WKWebView 没有对应接口，
+    /// Accept-Language 保持系统语言。所有平台上 pake.js 都会替换 `navigator.language(s)` 和 `Intl` 的默认语言
+    pub override_locale: Option<String>,
+    /// 从托盘或快捷键显示窗口时，把窗口移到光标所在显示器的中央；光标不在任何显示器上时使用主显示器
+    pub show_on_cursor_monitor: bool,
+    /// 显示或隐藏窗口的系统级快捷键，如 `CmdOrCtrl+Shift+Space`；窗口可见且在前台时隐藏，否则显示并聚焦
+    pub global_hotkey: Option<String>,
+    /// 启动地址为 localhost 或回环地址时，把它当作安全上下文，以便在没有 HTTPS 的本地开发服务器上使用
+    /// Service Worker 等 API；Windows 上同时忽略该地址的证书错误，WebKit 默认已如此处理
+    pub localhost_secure_context: bool,
+    /// 同时跟踪的进行中下载数上限，默认 100，超出时丢弃最早的记录
+    pub max_tracked_downloads: Option<usize>,
+    /// 下载开始后超过这么久（秒）仍未报告完成就不再跟踪，默认 24 小时
+    pub download_max_age_secs: Option<u64>,
+    /// 注入的提示、工具栏等界面从右到左排列并镜像位置，未配置时按 `override_locale` 判断，默认从左到右
+    pub rtl: Option<bool>,
+    /// 主窗口中点击链接、提交表单跳转时不发送 Referer，页面内的子资源请求不受影响
+    pub strip_referrer: bool,
+    /// 忽略页面发来的所有 IPC 消息，适合包装不完全信任的网站，防止其调用拖动、全屏、打开外部链接等原生功能。
+    /// 开启后 pake.js 中依赖 IPC 的功能都不会生效，包括工具栏、快捷键、拖动窗口、标题/网址/图标同步、
+    /// 控制台转发、错误上报、页面状态报告、淡入和 ready_selector 通知等
+    pub disable_ipc: bool,
+    /// 介于完全开放和 `disable_ipc` 之间：读写文件、导入导出设置、打开外部链接或新窗口等会改变状态的命令
+    /// 需要附带每次启动随机生成的令牌，只有 pake.js 在用户真实点击或按键后才会发出，页面自己的脚本调用这些命令会被拒绝
+    pub secure_ipc: bool,
+    /// 悬浮层模式：窗口置顶、背景透明、无边框无阴影，只有页面中可交互的区域接收鼠标，其余部分点击穿透。
+    /// 可交互区域为带 `data-pake-interactive` 的元素，没有标记时为 body 下的所有直接子元素；
+    /// 依赖 IPC 上报区域，不能与 `disable_ipc` 同时使用
+    pub overlay_mode: bool,
+    /// 退出时保存所有打开窗口的地址、位置、大小和缩放，下次启动时全部重新打开；
+    /// 没有保存过窗口时按启动地址打开主窗口，配置了 `schedule` 时主窗口仍按时间表选择地址
+    pub restore_windows: bool,
+    /// 把域名固定解析到指定 IP（域名 → IP），用于 DNS 不稳定的网络；请求仍使用原域名作为 Host 和 SNI。
+    /// 只在 Windows（WebView2）上生效，WebKitGTK 和 WKWebView 没有相应接口
+    pub hosts_overrides: BTreeMap<String, String>,
+    /// 应用改名前使用过的包名，新数据目录为空时从第一个存在的旧目录复制登录状态和 Pake 状态，只迁移一次；
+    /// macOS 上 Cookie 和本地存储不在数据目录中，只能迁移 Pake 自己的状态
+    pub migrate_from: Vec<String>,
+    /// 在这些地址上关闭触控板前进后退手势，其他地址照常开启，适合某个路径下是画布编辑器的应用；
+    /// 每项为匹配完整地址的正则表达式，仅在 macOS 上生效
+    pub disable_gestures_on: Vec<String>,
+    /// 开发用户脚本的目录，devtools 构建中页面可以发送 `run_script_file:<路径>` 重新执行其中的脚本，
+    /// 相对路径以该目录为基准，目录以外的文件一律拒绝
+    pub dev_scripts_dir: Option<PathBuf>,
+    /// 应用及其 WebView 子进程的内存上限（MB），每 30 秒采样一次；默认不检查
+    pub max_memory_mb: Option<u64>,
+    /// 内存占用超过 `max_memory_mb` 时的处理方式
+    pub on_high_memory: HighMemoryAction,
+    /// 触发一次后再次检查前的等待时间（秒），默认 600
+    pub high_memory_cooldown_secs: Option<u64>,
+    /// 允许交给系统打开的外部协议，如 `["mailto", "zoommtg", "slack"]`；页面导航到这些协议时取消导航，
+    /// 改由系统默认的处理程序打开，其他非网页协议的导航会被拦截。为空时不处理，保持 WebView 的默认行为
+    pub external_schemes: Vec<String>,
+    /// 退出前弹窗确认，覆盖 Cmd+Q、托盘的 Quit、设为 quit 的关闭按钮、页面调用 window.close()，
+    /// 以及 macOS 上 Dock 菜单的退出和注销/关机时的退出请求
+    pub confirm_quit: bool,
+    /// 在启动画面底部显示应用名称、版本和构建时 `PAKE_BUILD_INFO` 环境变量的内容；未配置启动图片时显示在加载动画下方
+    pub splash_show_version: bool,
+    /// 在匹配 `url_pattern` 的登录页自动填写账号密码并提交；账号密码只在登录页通过脚本传给页面，
+    /// 不会写入 __PAKE_CONFIG__
+    #[serde(skip_serializing)]
+    pub auto_login: Option<AutoLogin>,
+    /// 去掉导航地址中的跟踪参数（utm_*、fbclid、gclid 等）后重新导航；重新导航总是 GET 请求，
+    /// 提交地址带跟踪参数的 POST 表单会丢失表单内容
+    pub strip_tracking_params: bool,
+    /// 替换默认的跟踪参数列表，参数名支持 `*` 通配符，如 `["utm_*", "ref"]`；非空时同时开启 `strip_tracking_params`
+    pub strip_query_params: Vec<String>,
+    /// 启动时先显示等待页，确认能连上启动地址的主机后再加载，适合开机时网络尚未就绪的场景
+    pub wait_for_network: bool,
+    /// 等待网络的最长时间（秒），超时后仍然加载启动地址，默认 60
+    pub network_wait_timeout_secs: Option<u64>,
+    /// Windows 11 标题栏颜色，如 `"#1e1e2e"`，未配置时使用系统颜色；旧版 Windows 和其他平台忽略
+    pub titlebar_color: Option<String>,
+    /// 让窗口跟随页面的 `<meta name="theme-color">`：Windows 上设置标题栏颜色，Linux 上设置窗口背景色；
+    /// 页面没有设置时使用 `titlebar_color`
+    pub follow_theme_color: bool,
+    /// 在 127.0.0.1 的该端口上提供 `GET /health`，返回当前地址、页面加载状态和进行中的下载数，
+    /// 内容与 `pake-health.json` 相同，页面还没上报过状态时返回 503
+    pub health_port: Option<u16>,
+}
+
+/// `window.__PAKE_CONFIG__` 中的字段，即 pake.js 和内置错误页读取的配置，pake.js 用到新字段时在这里添加
+const PAGE_FIELDS: &[&str] = &[
+    "auto_focus_input",
+    "capture_js_errors",
+    "dialog_mode",
+    "disable_autofill",
+    "disable_ipc",
+    "drag_opacity",
+    "error_page_message",
+    "error_page_title",
+    "external_schemes",
+    "fade_in_ms",
+    "follow_theme_color",
+    "forward_file_drops",
+    "high_contrast",
+    "initial_zoom",
+    "keyboard_scroll",
+    "lock_zoom",
+    "middle_click_behavior",
+    "overlay_mode",
+    "override_locale",
+    "override_timezone",
+    "per_host_zoom",
+    "preload_urls",
+    "prevent_sleep",
+    "print_mode",
+    "ready_selector",
+    "ready_timeout_ms",
+    "report_page_state",
+    "rtl",
+    "scroll_multiplier",
+    "shortcuts",
+    "show_toolbar",
+    "smooth_scrolling",
+    "strip_referrer",
+    "support_contact",
+    "sync_favicon",
+    "title_template",
+    "toolbar_buttons",
+];
+
+impl PakeConfig {
+    /// 是否配置了自定义错误页
+    pub fn custom_error_page(&self) -> bool {
+        self.error_page_title.is_some()
+            || self.error_page_message.is_some()
+            || self.support_contact.is_some()
+            || self.error_page_path.is_some()
+    }
+
+    /// 生成注入页面的初始化脚本，供 pake.js 读取配置；只包含 `PAGE_FIELDS`，
+    /// 其他字段（域名解析、本地路径、端口、安全开关等）页面脚本用不到，不暴露给页面
+    pub fn init_script(&self) -> String {
+        let json = serde_json::to_string(&self.page_config()).unwrap_or_else(|_| "{}".to_string());
+        format!(
+            "window.__PAKE_CONFIG__ = {}; window.__PAKE_DEVTOOLS__ = {};",
+            json,
+            cfg!(feature = "devtools")
+        )
+    }
+
+    fn page_config(&self) -> serde_json::Map<String, Value> {
+        let mut fields = match serde_json::to_value(self) {
+            Ok(Value::Object(object)) => object,
+            _ => return serde_json::Map::new(),
+        };
+        fields.retain(|key, _| PAGE_FIELDS.contains(&key.as_str()));
+        // health_port 也需要页面上报状态，但端口本身不告诉页面
+        fields.insert(
+            "report_page_state".to_string(),
+            Value::Bool(self.report_page_state || self.health_port.is_some()),
+        );
+        fields
+    }
+}
+
+pub fn parse_pake_config() -> serde_json::Result<PakeConfig> {
+    serde_json::from_str(include_str!("../pake.json"))
+}
+
+pub fn get_pake_config() -> PakeConfig {
+    match parse_pake_config() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("警告: 无法解析 pake.json，使用默认配置: {:?}", e);
+            PakeConfig::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_config(config: &PakeConfig) -> Value {
+        let script = config.init_script();
+        let json = script
+            .strip_prefix("window.__PAKE_CONFIG__ = ")
+            .and_then(|rest| rest.split_once("; window.__PAKE_DEVTOOLS__"))
+            .unwrap()
+            .0;
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn page_config_hides_fields_pake_js_does_not_use() {
+        let config: PakeConfig = serde_json::from_value(serde_json::json!({
+            "show_toolbar": true,
+            "hosts_overrides": { "api.example.com": "10.0.0.5" },
+            "dev_scripts_dir": "/home/dev/scripts",
+            "health_port": 9000,
+            "secure_ipc": true,
+            "webview2_additional_args": "--proxy-server=10.0.0.1",
+            "navigation_allowlist": ["example.com"],
+        }))
+        .unwrap();
+        let page = page_config(&config);
+        assert_eq!(page["show_toolbar"], true);
+        for field in [
+            "hosts_overrides",
+            "dev_scripts_dir",
+            "health_port",
+            "secure_ipc",
+            "webview2_additional_args",
+            "navigation_allowlist",
+            "data_dir",
+            "auto_login",
+        ] {
+            assert!(page.get(field).is_none(), "{}", field);
+        }
+        // 只配置了 health_port 时页面仍需上报状态
+        assert_eq!(page["report_page_state"], true);
+        assert_eq!(
+            page_config(&PakeConfig::default())["report_page_state"],
+            false
+        );
+    }
+
+    #[test]
+    fn page_fields_exist_in_the_config() {
+        let fields = match serde_json::to_value(PakeConfig::default()).unwrap() {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        };
+        for field in PAGE_FIELDS {
+            assert!(fields.contains_key(*field), "{}", field);
+        }
+    }
+
+    #[test]
+    fn window_dimensions_parse_numbers_and_percentages() {
+        let config: PakeConfig =
+            serde_json::from_str(r#"{ "window_width": 1024, "window_height": "60%" }"#).unwrap();
+        assert_eq!(config.window_width, Some(WindowDimension::Pixels(1024.0)));
+        assert!(config.window_height.as_ref().unwrap().is_percent());
+        assert_eq!(config.window_height.unwrap().resolve(1000.0), Some(600.0));
+    }
+
+    #[test]
+    fn invalid_window_dimensions_do_not_resolve() {
+        for text in ["abc", "%", "-10%", "0"] {
+            assert_eq!(
+                WindowDimension::Text(text.to_string()).resolve(1000.0),
+                None
+            );
+        }
+        assert_eq!(
+            WindowDimension::Text(" 720 ".to_string()).resolve(1000.0),
+            Some(720.0)
+        );
+    }
+
+    #[test]
+    fn blur_behavior_parses_snake_case_and_defaults_to_none() {
+        let config: PakeConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.blur_behavior, BlurBehavior::None);
+        for (text, behavior) in [("dim", BlurBehavior::Dim), ("hide", BlurBehavior::Hide)] {
+            let config: PakeConfig =
+                serde_json::from_str(&format!(r#"{{ "blur_behavior": "{}" }}"#, text)).unwrap();
+            assert_eq!(config.blur_behavior, behavior);
+        }
+        assert!(serde_json::from_str::<PakeConfig>(r#"{ "blur_behavior": "fade" }"#).is_err());
+    }
+
+    #[test]
+    fn start_monitor_matches_index_or_name() {
+        let names = vec![
+            Some("Built-in Retina Display".to_string()),
+            None,
+            Some("DELL U2720Q".to_string()),
+        ];
+        let config: PakeConfig = serde_json::from_str(r#"{ "start_monitor": 1 }"#).unwrap();
+        assert_eq!(config.start_monitor.unwrap().find(&names), Some(1));
+        let by_name = MonitorSelector::Name(" dell u2720q ".to_string());
+        assert_eq!(by_name.find(&names), Some(2));
+        assert_eq!(MonitorSelector::Index(3).find(&names), None);
+        assert_eq!(MonitorSelector::Name("HP".to_string()).find(&names), None);
+    }
+}
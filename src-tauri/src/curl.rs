@@ -0,0 +1,33 @@
+//! 开发者命令：将当前页面请求复制为 curl 命令
+use serde::Deserialize;
+
+/// pake.js 通过 `curl_info:` 上报的请求信息
+#[derive(Debug, Deserialize)]
+pub struct CurlRequest {
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+}
+
+pub fn format_curl(request: &CurlRequest) -> String {
+    let mut command = format!("curl {}", shell_quote(&request.url));
+    for (name, value) in &request.headers {
+        // 头部名称和值中不应包含换行，去掉以免生成多行命令
+        let header = format!("{}: {}", name, value).replace(['\r', '\n'], "");
+        command.push_str(" \\\n  -H ");
+        command.push_str(&shell_quote(&header));
+    }
+    command.push_str(" \\\n  --compressed");
+    command
+}
+
+/// 用单引号包裹，内部的单引号转义为 `'\''`
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text.to_string()))
+        .map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,52 @@
+//! 独立的调试控制台窗口，显示 pake.js 转发的 console 日志（仅 devtools 构建可用）
+use crate::navigation::js_string;
+use crate::protocol;
+use std::collections::VecDeque;
+use wry::application::{
+    dpi::LogicalSize, event_loop::EventLoopWindowTarget, window::WindowBuilder,
+};
+use wry::webview::{WebView, WebViewBuilder};
+
+/// 最多缓存的日志行数，避免内存无限增长
+const MAX_LINES: usize = 1000;
+
+#[derive(Default)]
+pub struct ConsoleBuffer {
+    lines: VecDeque<String>,
+}
+
+impl ConsoleBuffer {
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() >= MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter()
+    }
+}
+
+pub fn open<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    buffer: &ConsoleBuffer,
+) -> wry::Result<WebView> {
+    let window = WindowBuilder::new()
+        .with_title("Pake Debug Console")
+        .with_inner_size(LogicalSize::new(640.0, 400.0))
+        .build(event_loop)?;
+    let logs = serde_json::to_string(&buffer.lines).unwrap_or_else(|_| "[]".to_string());
+    let assets_dir = protocol::assets_dir();
+    WebViewBuilder::new(window)?
+        .with_initialization_script(&format!("window.__PAKE_LOGS__ = {};", logs))
+        .with_custom_protocol(protocol::PROTOCOL_NAME.into(), move |request| {
+            protocol::handle(&assets_dir, request)
+        })
+        .with_url(&protocol::builtin_url("debug-console.html"))?
+        .build()
+}
+
+pub fn append(console: &WebView, line: &str) {
+    let _ = console.evaluate_script(&format!("window.pakeAppendLog({})", js_string(line)));
+}
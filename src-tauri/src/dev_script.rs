@@ -0,0 +1,35 @@
+//! 开发用户脚本时从本地目录读取并执行，修改后无需重启应用（仅 devtools 构建可用）
+use std::path::{Path, PathBuf};
+
+/// 单个脚本文件的大小上限
+const MAX_SCRIPT_BYTES: u64 = 1024 * 1024;
+
+/// 读取 `dev_scripts_dir` 下的脚本，相对路径以该目录为基准；目录外的文件一律拒绝
+pub fn read_script(dev_dir: Option<&Path>, requested: &str) -> Result<(PathBuf, String), String> {
+    let dev_dir = dev_dir.ok_or_else(|| "未配置 dev_scripts_dir".to_string())?;
+    let dev_dir = dev_dir
+        .canonicalize()
+        .map_err(|e| format!("{}: {:?}", dev_dir.display(), e))?;
+    let requested = Path::new(requested.trim());
+    let path = dev_dir
+        .join(requested)
+        .canonicalize()
+        .map_err(|e| format!("{}: {:?}", requested.display(), e))?;
+    // 规范化后再比较，防止通过 `..` 或符号链接跳出目录
+    if !path.starts_with(&dev_dir) {
+        return Err(format!("{} 不在 dev_scripts_dir 中", path.display()));
+    }
+    let size = std::fs::metadata(&path)
+        .map_err(|e| format!("{}: {:?}", path.display(), e))?
+        .len();
+    if size > MAX_SCRIPT_BYTES {
+        return Err(format!(
+            "{} 超过 {} 字节上限",
+            path.display(),
+            MAX_SCRIPT_BYTES
+        ));
+    }
+    let script =
+        std::fs::read_to_string(&path).map_err(|e| format!("{}: {:?}", path.display(), e))?;
+    Ok((path, script))
+}
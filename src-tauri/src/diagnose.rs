@@ -0,0 +1,87 @@
+//! `--diagnose`：不启动界面，输出排查用户环境所需的诊断报告
+use crate::config::parse_pake_config;
+use crate::{check_writable, engine, get_data_dir, get_windows_config};
+use std::time::Duration;
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 运行所有检查并打印报告，返回进程退出码：全部通过为 0，否则为 1
+pub fn run() -> i32 {
+    println!("=== Pake 诊断报告 ===");
+    let mut failures = 0;
+    let mut report = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("[通过] {}: {}", name, detail),
+        Err(detail) => {
+            failures += 1;
+            println!("[失败] {}: {}", name, detail);
+        }
+    };
+
+    let (product_name, window_config) = get_windows_config();
+    report(
+        "tauri.conf.json",
+        match (&product_name, &window_config) {
+            (Some(name), Some(_)) => Ok(format!("product_name={}", name)),
+            _ => Err("缺少 productName 或窗口配置".to_string()),
+        },
+    );
+    let pake_config = parse_pake_config();
+    report(
+        "pake.json",
+        pake_config
+            .as_ref()
+            .map(|_| "解析成功".to_string())
+            .map_err(|e| format!("{:?}", e)),
+    );
+
+    let package_name = product_name.unwrap_or_default().to_lowercase();
+    report("图标", icon_path(&package_name));
+
+    let configured_data_dir = pake_config.ok().and_then(|c| c.data_dir);
+    let data_dir = get_data_dir(&package_name, configured_data_dir.as_deref());
+    report(
+        "数据目录",
+        check_writable(&data_dir).map(|_| data_dir.display().to_string()),
+    );
+
+    report("WebView 引擎", Ok(engine::webview_version()));
+
+    match window_config {
+        Some(config) => report("网络", check_reachable(&config.url.to_string())),
+        None => report("网络", Err("没有启动地址".to_string())),
+    }
+
+    report(
+        "下载目录",
+        match dirs::download_dir() {
+            Some(dir) => check_writable(&dir).map(|_| dir.display().to_string()),
+            None => Err("无法找到下载目录".to_string()),
+        },
+    );
+
+    println!("=== 诊断完成，{} 项失败 ===", failures);
+    if failures == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn icon_path(package_name: &str) -> Result<String, String> {
+    crate::find_icon_path(package_name).ok_or_else(|| "未找到任何图标文件".to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn icon_path(_package_name: &str) -> Result<String, String> {
+    Ok("仅 Windows 使用窗口图标，跳过".to_string())
+}
+
+/// 对启动地址发送 HEAD 请求，只要服务器有响应（包括 4xx/5xx）就视为可达
+fn check_reachable(url: &str) -> Result<String, String> {
+    match ureq::head(url).timeout(NETWORK_TIMEOUT).call() {
+        Ok(response) => Ok(format!("{} -> {}", url, response.status())),
+        Err(ureq::Error::Status(code, _)) => Ok(format!("{} -> {}", url, code)),
+        Err(e) => Err(format!("{} 不可达: {}", url, e)),
+    }
+}
@@ -0,0 +1,41 @@
+//! 原生对话框
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageLevel};
+use std::path::PathBuf;
+
+/// 显示确认对话框，用户选择“是”时返回 true
+pub fn confirm(title: &str, message: &str) -> bool {
+    MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(MessageButtons::YesNo)
+        .show()
+}
+
+/// 显示错误提示框
+pub fn error(title: &str, message: &str) {
+    MessageDialog::new()
+        .set_level(MessageLevel::Error)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+/// 显示警告提示框
+pub fn warning(title: &str, message: &str) {
+    MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+/// 选择一个 JSON 文件，用户取消时返回 None
+pub fn pick_json_file(title: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_title(title)
+        .add_filter("JSON", &["json"])
+        .pick_file()
+}
@@ -0,0 +1,26 @@
+//! 磁盘剩余空间检查，避免磁盘写满后下载静默失败
+use std::path::Path;
+
+/// 目录所在磁盘的可用空间（MB），查询失败时返回 None
+pub fn free_space_mb(path: &Path) -> Option<u64> {
+    match fs2::available_space(path) {
+        Ok(bytes) => Some(bytes / 1024 / 1024),
+        Err(e) => {
+            eprintln!("警告: 无法查询 {} 的剩余空间: {:?}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// 剩余空间低于 `min_free_mb` 时返回提示信息
+pub fn check_free_space(path: &Path, min_free_mb: u64) -> Result<(), String> {
+    match free_space_mb(path) {
+        Some(free) if free < min_free_mb => Err(format!(
+            "Only {} MB free on the disk holding {} (at least {} MB recommended).",
+            free,
+            path.display(),
+            min_free_mb
+        )),
+        _ => Ok(()),
+    }
+}
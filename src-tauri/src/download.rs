@@ -0,0 +1,209 @@
+//! 下载文件的保存位置，以及页面通过 `save_file:` 直接写入下载目录
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dirs::download_dir;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 单次 `save_file:` 允许写入的最大字节数（解码后）
+pub const MAX_SAVE_FILE_BYTES: usize = 20 * 1024 * 1024;
+/// 同时记录的进行中下载数上限
+pub const DEFAULT_MAX_TRACKED: usize = 100;
+/// 开始后超过这么久仍未报告完成的下载视为已失效
+pub const DEFAULT_MAX_AGE_SECS: u64 = 24 * 60 * 60;
+
+/// pake.js 或页面通过 `save_file:` 传入的文件内容
+#[derive(Debug, Deserialize)]
+pub struct SaveFileRequest {
+    pub filename: String,
+    pub base64_content: String,
+    #[serde(default)]
+    pub mime: Option<String>,
+}
+
+/// 进行中的下载，按 URI 记录，`DownloadComplete` 时移除
+///
+/// WebView 不保证每个下载都会回调完成，超过上限或过期的条目会被淘汰，避免长时间运行后越积越多。
+/// 同一地址同时下载多次时只记一条
+pub struct DownloadTracker {
+    active: HashMap<String, Instant>,
+    max_entries: usize,
+    max_age: Duration,
+}
+
+impl DownloadTracker {
+    pub fn new(max_entries: usize, max_age: Duration) -> Self {
+        DownloadTracker {
+            active: HashMap::new(),
+            max_entries: max_entries.max(1),
+            max_age,
+        }
+    }
+
+    pub fn start(&mut self, uri: String) {
+        self.evict_expired();
+        if self.active.len() >= self.max_entries && !self.active.contains_key(&uri) {
+            let oldest = self
+                .active
+                .iter()
+                .min_by_key(|(_, started)| **started)
+                .map(|(uri, _)| uri.clone());
+            if let Some(oldest) = oldest {
+                eprintln!(
+                    "警告: 进行中的下载超过 {} 个，不再跟踪: {}",
+                    self.max_entries, oldest
+                );
+                self.active.remove(&oldest);
+            }
+        }
+        self.active.insert(uri, Instant::now());
+    }
+
+    /// 返回该下载是否仍在跟踪中
+    pub fn complete(&mut self, uri: &str) -> bool {
+        self.active.remove(uri).is_some()
+    }
+
+    pub fn active_count(&mut self) -> usize {
+        self.evict_expired();
+        self.active.len()
+    }
+
+    fn evict_expired(&mut self) {
+        let max_age = self.max_age;
+        self.active.retain(|uri, started| {
+            let expired = started.elapsed() > max_age;
+            if expired {
+                eprintln!(
+                    "警告: 下载超过 {} 秒未完成，不再跟踪: {}",
+                    max_age.as_secs(),
+                    uri
+                );
+            }
+            !expired
+        });
+    }
+}
+
+/// 下载目录，找不到时退回临时目录
+pub fn downloads_dir() -> PathBuf {
+    download_dir().unwrap_or_else(|| {
+        eprintln!("警告: 无法找到下载目录，使用临时目录");
+        std::env::temp_dir()
+    })
+}
+
+/// 下载文件的保存路径
+pub fn download_path(file_name: &str) -> PathBuf {
+    downloads_dir().join(file_name)
+}
+
+/// 解码并写入下载目录
+pub fn save_file(request: &SaveFileRequest) -> Result<PathBuf, String> {
+    let file_name = sanitize_file_name(&request.filename)?;
+    // base64 长度约为原始数据的 4/3，先按编码长度拒绝，避免解码超大数据
+    if request.base64_content.len() / 4 * 3 > MAX_SAVE_FILE_BYTES {
+        return Err(format!("文件超过 {} 字节上限", MAX_SAVE_FILE_BYTES));
+    }
+    let bytes = STANDARD
+        .decode(request.base64_content.trim())
+        .map_err(|e| format!("base64 解码失败: {:?}", e))?;
+    if bytes.len() > MAX_SAVE_FILE_BYTES {
+        return Err(format!("文件超过 {} 字节上限", MAX_SAVE_FILE_BYTES));
+    }
+    save_bytes(file_name, &bytes)
+}
+
+/// 将内容写入下载目录，已存在同名文件时自动追加序号，返回实际保存的路径
+pub fn save_bytes(file_name: &str, bytes: &[u8]) -> Result<PathBuf, String> {
+    let path = unique_path(download_path(file_name));
+    std::fs::write(&path, bytes).map_err(|e| format!("{:?}", e))?;
+    Ok(path)
+}
+
+/// 只接受纯文件名，拒绝包含目录分隔符或 `..` 的名称
+fn sanitize_file_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    let is_plain = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', '\0'])
+        && Path::new(name).file_name().map_or(false, |n| n == name);
+    if is_plain {
+        Ok(name)
+    } else {
+        Err(format!("非法文件名: {:?}", name))
+    }
+}
+
+/// 已存在同名文件时在文件名后追加 ` (1)`、` (2)` 等序号
+pub fn unique_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|i| path.with_file_name(format!("{} ({}){}", stem, i, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_started_downloads_until_complete() {
+        let mut tracker = DownloadTracker::new(10, Duration::from_secs(60));
+        tracker.start("https://example.com/a.zip".to_string());
+        tracker.start("https://example.com/b.zip".to_string());
+        assert_eq!(tracker.active_count(), 2);
+        assert!(tracker.complete("https://example.com/a.zip"));
+        assert!(!tracker.complete("https://example.com/a.zip"));
+        assert!(!tracker.complete("https://example.com/unknown.zip"));
+        assert_eq!(tracker.active_count(), 1);
+    }
+
+    #[test]
+    fn evicts_the_oldest_download_when_full() {
+        let mut tracker = DownloadTracker::new(2, Duration::from_secs(60));
+        tracker.start("a".to_string());
+        std::thread::sleep(Duration::from_millis(2));
+        tracker.start("b".to_string());
+        std::thread::sleep(Duration::from_millis(2));
+        tracker.start("c".to_string());
+        assert_eq!(tracker.active_count(), 2);
+        assert!(!tracker.complete("a"));
+        assert!(tracker.complete("b"));
+        assert!(tracker.complete("c"));
+    }
+
+    #[test]
+    fn restarting_a_tracked_download_does_not_evict_others() {
+        let mut tracker = DownloadTracker::new(2, Duration::from_secs(60));
+        tracker.start("a".to_string());
+        tracker.start("b".to_string());
+        tracker.start("a".to_string());
+        assert!(tracker.complete("a"));
+        assert!(tracker.complete("b"));
+    }
+
+    #[test]
+    fn forgets_downloads_older_than_max_age() {
+        let mut tracker = DownloadTracker::new(10, Duration::from_millis(20));
+        tracker.start("stale".to_string());
+        std::thread::sleep(Duration::from_millis(40));
+        tracker.start("fresh".to_string());
+        assert_eq!(tracker.active_count(), 1);
+        assert!(!tracker.complete("stale"));
+        assert!(tracker.complete("fresh"));
+    }
+}
@@ -0,0 +1,661 @@
+//! 各平台 WebView 引擎设置的透传
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use wry::webview::WebView;
+
+/// 将 `webkit_settings` 应用到 WebKitGTK 的 `WebKitSettings`
+///
+/// 支持的键：
+/// - `enable-developer-extras`: bool
+/// - `enable-webgl`: bool
+/// - `enable-smooth-scrolling`: bool
+/// - `enable-javascript`: bool
+/// - `hardware-acceleration-policy`: `"always"` | `"never"` | `"on-demand"`
+#[cfg(target_os = "linux")]
+pub fn apply_webkit_settings(webview: &WebView, settings: &BTreeMap<String, Value>) {
+    use webkit2gtk::{HardwareAccelerationPolicy, SettingsExt, WebViewExt};
+    use wry::webview::WebviewExtUnix;
+
+    if settings.is_empty() {
+        return;
+    }
+    let webkit_settings = match webview.webview().settings() {
+        Some(s) => s,
+        None => {
+            eprintln!("警告: 无法获取 WebKitSettings，忽略 webkit_settings");
+            return;
+        }
+    };
+    for (key, value) in settings {
+        let applied = match (key.as_str(), value) {
+            ("enable-developer-extras", Value::Bool(v)) => {
+                webkit_settings.set_enable_developer_extras(*v);
+                true
+            }
+            ("enable-webgl", Value::Bool(v)) => {
+                webkit_settings.set_enable_webgl(*v);
+                true
+            }
+            ("enable-smooth-scrolling", Value::Bool(v)) => {
+                webkit_settings.set_enable_smooth_scrolling(*v);
+                true
+            }
+            ("enable-javascript", Value::Bool(v)) => {
+                webkit_settings.set_enable_javascript(*v);
+                true
+            }
+            ("hardware-acceleration-policy", Value::String(v)) => {
+                let policy = match v.as_str() {
+                    "always" => Some(HardwareAccelerationPolicy::Always),
+                    "never" => Some(HardwareAccelerationPolicy::Never),
+                    "on-demand" => Some(HardwareAccelerationPolicy::OnDemand),
+                    _ => None,
+                };
+                if let Some(policy) = policy {
+                    webkit_settings.set_hardware_acceleration_policy(policy);
+                }
+                policy.is_some()
+            }
+            _ => false,
+        };
+        if applied {
+            println!("WebKitGTK 设置: {} = {}", key, value);
+        } else {
+            eprintln!("警告: 忽略不支持的 webkit_settings 项: {} = {}", key, value);
+        }
+    }
+}
+
+/// 设置 WebKitGTK 原生平滑滚动，其他平台由 pake.js 注入 `scroll-behavior` 实现
+#[cfg(target_os = "linux")]
+pub fn apply_smooth_scrolling(webview: &WebView, enabled: bool) {
+    use webkit2gtk::{SettingsExt, WebViewExt};
+    use wry::webview::WebviewExtUnix;
+
+    if let Some(settings) = webview.webview().settings() {
+        settings.set_enable_smooth_scrolling(enabled);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply_smooth_scrolling(_webview: &WebView, _enabled: bool) {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn apply_webkit_settings(_webview: &WebView, settings: &BTreeMap<String, Value>) {
+    if !settings.is_empty() {
+        println!("警告: webkit_settings 仅在 Linux 上生效，已忽略");
+    }
+}
+
+/// 可通过 `wkwebview_prefs` 设置的 WKPreferences 布尔项
+#[cfg(target_os = "macos")]
+const WK_BOOL_PREFS: &[&str] = &[
+    "developerExtrasEnabled",
+    "javaScriptCanOpenWindowsAutomatically",
+    "javaScriptEnabled",
+    "fraudulentWebsiteWarningEnabled",
+    "tabFocusesLinks",
+];
+
+/// 将 `wkwebview_prefs` 应用到 WKWebView 的 `WKPreferences`
+///
+/// 支持 `WK_BOOL_PREFS` 中的布尔项以及数值项 `minimumFontSize`
+#[cfg(target_os = "macos")]
+pub fn apply_wkwebview_prefs(webview: &WebView, prefs: &BTreeMap<String, Value>) {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+    use wry::webview::WebviewExtMacOS;
+
+    if prefs.is_empty() {
+        return;
+    }
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let configuration: id = msg_send![wk_webview, configuration];
+        let preferences: id = msg_send![configuration, preferences];
+        for (key, value) in prefs {
+            let number: id = match (key.as_str(), value) {
+                (k, Value::Bool(v)) if WK_BOOL_PREFS.contains(&k) => {
+                    msg_send![class!(NSNumber), numberWithBool: *v]
+                }
+                ("minimumFontSize", Value::Number(v)) => {
+                    msg_send![class!(NSNumber), numberWithDouble: v.as_f64().unwrap_or_default()]
+                }
+                _ => nil,
+            };
+            if number == nil {
+                eprintln!("警告: 忽略不支持的 wkwebview_prefs 项: {} = {}", key, value);
+                continue;
+            }
+            let ns_key = NSString::alloc(nil).init_str(key);
+            let _: () = msg_send![preferences, setValue: number forKey: ns_key];
+            println!("WKWebView 设置: {} = {}", key, value);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn apply_wkwebview_prefs(_webview: &WebView, prefs: &BTreeMap<String, Value>) {
+    if !prefs.is_empty() {
+        println!("警告: wkwebview_prefs 仅在 macOS 上生效，已忽略");
+    }
+}
+
+/// 在当前页面中加载地址，供推迟首次加载时使用
+pub fn load_url(webview: &WebView, url: &str) {
+    let _ = webview.evaluate_script(&format!(
+        "window.location.replace({});",
+        crate::navigation::js_string(url)
+    ));
+}
+
+/// 编译 Safari 内容拦截规则并添加到 WKWebView，完成后（无论成功与否）加载 `then_load`
+///
+/// 编译结果由 `WKContentRuleListStore` 按规则内容的哈希缓存，规则不变时不会重复编译；
+/// 规则读取或编译失败时只记录错误，不做拦截
+#[cfg(target_os = "macos")]
+pub fn install_content_blocker(webview: &WebView, rules_path: &Path, then_load: Option<String>) {
+    use block::ConcreteBlock;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+    use wry::webview::WebviewExtMacOS;
+
+    let load_now = |then_load: Option<String>| {
+        if let Some(url) = then_load {
+            load_url(webview, &url);
+        }
+    };
+    let rules = match std::fs::read_to_string(rules_path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!(
+                "错误: 无法读取内容拦截规则 {}: {:?}",
+                rules_path.display(),
+                e
+            );
+            return load_now(then_load);
+        }
+    };
+    if let Err(e) = serde_json::from_str::<Value>(&rules) {
+        eprintln!("错误: 内容拦截规则不是有效的 JSON: {:?}", e);
+        return load_now(then_load);
+    }
+    let identifier = format!("pake-{:x}", md5::compute(rules.as_bytes()));
+
+    unsafe fn describe_error(error: id) -> String {
+        if error == nil {
+            return String::new();
+        }
+        let description: id = msg_send![error, localizedDescription];
+        let utf8: *const std::os::raw::c_char = msg_send![description, UTF8String];
+        std::ffi::CStr::from_ptr(utf8)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    unsafe fn load_request(wk_webview: id, url: &Option<String>) {
+        if let Some(url) = url {
+            let ns_string = NSString::alloc(nil).init_str(url);
+            let ns_url: id = msg_send![class!(NSURL), URLWithString: ns_string];
+            let request: id = msg_send![class!(NSURLRequest), requestWithURL: ns_url];
+            let _: id = msg_send![wk_webview, loadRequest: request];
+            let _: () = msg_send![ns_string, release];
+        }
+    }
+
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let configuration: id = msg_send![wk_webview, configuration];
+        let controller: id = msg_send![configuration, userContentController];
+        let store: id = msg_send![class!(WKContentRuleListStore), defaultStore];
+        let ns_identifier = NSString::alloc(nil).init_str(&identifier);
+        let ns_rules = NSString::alloc(nil).init_str(&rules);
+
+        let lookup = ConcreteBlock::new(move |list: id, _error: id| {
+            if list != nil {
+                let _: () = msg_send![controller, addContentRuleList: list];
+                println!("已加载缓存的内容拦截规则: {}", identifier);
+                load_request(wk_webview, &then_load);
+                return;
+            }
+            let then_load = then_load.clone();
+            let compile = ConcreteBlock::new(move |list: id, error: id| {
+                if list == nil {
+                    eprintln!("错误: 内容拦截规则编译失败: {}", describe_error(error));
+                } else {
+                    let _: () = msg_send![controller, addContentRuleList: list];
+                    println!("内容拦截规则编译完成并已启用");
+                }
+                load_request(wk_webview, &then_load);
+            })
+            .copy();
+            let _: () = msg_send![store, compileContentRuleListForIdentifier: ns_identifier
+                                         encodedContentRuleList: ns_rules
+                                         completionHandler: &*compile];
+        })
+        .copy();
+        let _: () = msg_send![store, lookUpContentRuleListForIdentifier: ns_identifier
+                                     completionHandler: &*lookup];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn install_content_blocker(webview: &WebView, _rules_path: &Path, then_load: Option<String>) {
+    println!("警告: content_blocker_rules 仅在 macOS 上生效，已忽略");
+    if let Some(url) = then_load {
+        load_url(webview, &url);
+    }
+}
+
+#[cfg(target_os = "windows")]
+const ENGINE_NAME: &str = "WebView2";
+#[cfg(target_os = "linux")]
+const ENGINE_NAME: &str = "WebKitGTK";
+#[cfg(target_os = "macos")]
+const ENGINE_NAME: &str = "WKWebView";
+
+/// 当前系统的 WebView 引擎及版本，如 `WebView2 108.0.1462.54`，用于排查渲染差异
+///
+/// Windows 为 WebView2 运行时版本，Linux 为运行时加载的 WebKitGTK 版本，
+/// macOS 为系统 WebKit.framework 的版本
+pub fn webview_version() -> String {
+    match wry::webview::webview_version() {
+        Ok(version) => format!("{} {}", ENGINE_NAME, version),
+        Err(e) => {
+            eprintln!("警告: 无法获取 WebView 版本: {:?}", e);
+            format!("{} unknown", ENGINE_NAME)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const WEBVIEW2_DOWNLOAD_URL: &str = "https://developer.microsoft.com/microsoft-edge/webview2/";
+
+/// 使用随应用分发的固定版本 WebView2 运行时，相对路径以可执行文件所在目录为基准
+#[cfg(target_os = "windows")]
+pub fn use_webview2_fixed_runtime(path: &Path) {
+    let path = if path.is_relative() {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(path)))
+            .unwrap_or_else(|| path.to_path_buf())
+    } else {
+        path.to_path_buf()
+    };
+    if !path.exists() {
+        eprintln!(
+            "警告: 固定版本 WebView2 运行时目录不存在: {}",
+            path.display()
+        );
+    }
+    println!("使用固定版本 WebView2 运行时: {}", path.display());
+    std::env::set_var("WEBVIEW2_BROWSER_EXECUTABLE_FOLDER", path);
+}
+
+/// 启动前检查 WebView2 运行时是否可用，缺失时弹窗提示下载，返回 false 表示应退出
+///
+/// 不检查的话会在创建 WebView 时才失败，错误信息难以理解
+#[cfg(target_os = "windows")]
+pub fn ensure_webview2_runtime() -> bool {
+    if wry::webview::webview_version().is_ok() {
+        return true;
+    }
+    eprintln!("错误: 未检测到 WebView2 运行时");
+    if crate::dialog::confirm(
+        "WebView2 Runtime required",
+        "This app requires the Microsoft Edge WebView2 Runtime, which is not installed.\n\nOpen the download page now?",
+    ) {
+        if let Err(e) = webbrowser::open(WEBVIEW2_DOWNLOAD_URL) {
+            eprintln!("警告: 无法打开浏览器: {:?}", e);
+        }
+    }
+    false
+}
+
+/// 运行时修改 WebView 的 User-Agent，对之后的请求生效，返回是否设置成功
+#[cfg(target_os = "linux")]
+pub fn set_user_agent(webview: &WebView, user_agent: &str) -> bool {
+    use webkit2gtk::{SettingsExt, WebViewExt};
+    use wry::webview::WebviewExtUnix;
+
+    match webview.webview().settings() {
+        Some(settings) => {
+            settings.set_user_agent(Some(user_agent));
+            true
+        }
+        None => {
+            eprintln!("警告: 无法获取 WebKitSettings，无法修改 User-Agent");
+            false
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_user_agent(webview: &WebView, user_agent: &str) -> bool {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+    use wry::webview::WebviewExtMacOS;
+
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let ns_user_agent = NSString::alloc(nil).init_str(user_agent);
+        let _: () = msg_send![wk_webview, setCustomUserAgent: ns_user_agent];
+    }
+    true
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_user_agent(webview: &WebView, user_agent: &str) -> bool {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings2;
+    use windows::core::{Interface, HSTRING, PCWSTR};
+    use wry::webview::WebviewExtWindows;
+
+    let result = unsafe {
+        webview
+            .controller()
+            .CoreWebView2()
+            .and_then(|core| core.Settings())
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings2>())
+            .and_then(|settings| {
+                let user_agent = HSTRING::from(user_agent);
+                settings.SetUserAgent(PCWSTR(user_agent.as_ptr()))
+            })
+    };
+    match result {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("警告: 无法修改 WebView2 User-Agent: {:?}", e);
+            false
+        }
+    }
+}
+
+/// 关闭 WebView2 的密码保存和表单自动填充，避免共享设备上的数据在用户之间泄露
+///
+/// WebKitGTK 和 WKWebView 没有内置的密码管理器，只由 pake.js 为表单加上 `autocomplete="off"`
+#[cfg(target_os = "windows")]
+pub fn disable_autofill(webview: &WebView) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings4;
+    use windows::core::Interface;
+    use windows::Win32::Foundation::BOOL;
+    use wry::webview::WebviewExtWindows;
+
+    let result = unsafe {
+        webview
+            .controller()
+            .CoreWebView2()
+            .and_then(|core| core.Settings())
+            .and_then(|settings| settings.cast::<ICoreWebView2Settings4>())
+            .and_then(|settings| {
+                settings.SetIsPasswordAutosaveEnabled(BOOL::from(false))?;
+                settings.SetIsGeneralAutofillEnabled(BOOL::from(false))
+            })
+    };
+    match result {
+        Ok(_) => println!("已关闭 WebView2 密码保存和自动填充"),
+        Err(e) => eprintln!("警告: 无法关闭 WebView2 自动填充: {:?}", e),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn disable_autofill(_webview: &WebView) {}
+
+/// 关闭 WebView2 自带的 Ctrl+滚轮、捏合和快捷键缩放，供 `lock_zoom` 使用
+///
+/// WebKitGTK 和 WKWebView 的缩放由 pake.js 拦截，页面缩放比例变化时再通过 IPC 恢复为 100%
+#[cfg(target_os = "windows")]
+pub fn disable_zoom_control(webview: &WebView) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings5;
+    use windows::core::Interface;
+    use windows::Win32::Foundation::BOOL;
+    use wry::webview::WebviewExtWindows;
+
+    let result = unsafe {
+        webview
+            .controller()
+            .CoreWebView2()
+            .and_then(|core| core.Settings())
+            .and_then(|settings| {
+                settings.SetIsZoomControlEnabled(BOOL::from(false))?;
+                settings
+                    .cast::<ICoreWebView2Settings5>()?
+                    .SetIsPinchZoomEnabled(BOOL::from(false))
+            })
+    };
+    match result {
+        Ok(_) => println!("已锁定 WebView2 缩放"),
+        Err(e) => eprintln!("警告: 无法锁定 WebView2 缩放: {:?}", e),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn disable_zoom_control(_webview: &WebView) {}
+
+/// 设置页面使用的语言，影响 Accept-Language 和 `navigator.language`，必须在创建 WebView 之前调用
+#[cfg(target_os = "windows")]
+pub fn override_locale(locale: &str) {
+    if !is_bcp47_tag(locale) {
+        eprintln!(
+            "警告: override_locale 不是有效的语言标签，已忽略: {:?}",
+            locale
+        );
+        return;
+    }
+    crate::append_webview2_args(&format!("--lang={}", locale));
+    println!("页面语言: {} (WebView2 --lang)", locale);
+}
+
+/// 语言标签会拼进 WebView2 的启动参数，只接受 BCP-47 使用的字母、数字和连字符
+#[cfg(any(target_os = "windows", test))]
+fn is_bcp47_tag(locale: &str) -> bool {
+    !locale.is_empty()
+        && !locale.starts_with('-')
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(target_os = "linux")]
+pub fn override_locale(locale: &str) {
+    // WebKitGTK 按 GLib 的语言列表生成 Accept-Language，LANGUAGE 优先级最高
+    std::env::set_var("LANGUAGE", locale.replace('-', "_"));
+    println!("页面语言: {} (LANGUAGE)", locale);
+}
+
+/// WKWebView 的页面运行在独立的 WebContent 进程中，不读取本进程的 `AppleLanguages`，也没有设置
+/// Accept-Language 的接口，只能由 pake.js 替换 `navigator.language(s)` 和 `Intl` 的默认语言
+#[cfg(target_os = "macos")]
+pub fn override_locale(locale: &str) {
+    println!(
+        "页面语言: {} (由 pake.js 模拟，Accept-Language 不变)",
+        locale
+    );
+}
+
+/// 设置页面使用的时区，只有 WebKitGTK 会读取 `TZ`；其他平台完全依靠 pake.js 模拟
+#[cfg(target_os = "linux")]
+pub fn override_timezone(timezone: &str) {
+    std::env::set_var("TZ", timezone);
+    println!("页面时区: {} (TZ)", timezone);
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn override_timezone(timezone: &str) {
+    println!("页面时区: {} (由 pake.js 模拟)", timezone);
+}
+
+/// 把本机开发服务器当作安全上下文，并忽略其 HTTPS 证书错误，必须在创建 WebView 之前调用
+#[cfg(target_os = "windows")]
+pub fn treat_as_secure_origin(origin: &str) {
+    crate::append_webview2_args(&format!(
+        "--unsafely-treat-insecure-origin-as-secure={} --allow-insecure-localhost",
+        origin
+    ));
+    println!("视为安全上下文: {}", origin);
+}
+
+/// WebKit 本身已把 localhost 和回环地址视为安全上下文，没有按域名放宽证书校验的设置
+#[cfg(not(target_os = "windows"))]
+pub fn treat_as_secure_origin(origin: &str) {
+    println!("视为安全上下文: {} (WebKit 默认行为)", origin);
+}
+
+/// 把域名固定解析到指定 IP，必须在创建 WebView 之前调用
+///
+/// 只有 WebView2 支持（Chromium 的 `--host-resolver-rules`），请求仍带原来的 Host 和 SNI；
+/// WebKitGTK 和 WKWebView 没有按进程改写解析结果的接口
+#[cfg(target_os = "windows")]
+pub fn apply_hosts_overrides(overrides: &BTreeMap<String, String>) {
+    if let Some(arg) = host_resolver_rules(overrides) {
+        crate::append_webview2_args(&arg);
+        println!("域名解析覆盖: {}", arg);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn apply_hosts_overrides(overrides: &BTreeMap<String, String>) {
+    if valid_host_overrides(overrides).next().is_some() {
+        println!("警告: hosts_overrides 仅在 Windows 上生效，已忽略");
+    }
+}
+
+/// 生成追加到 WebView2 启动参数中的 `--host-resolver-rules`，没有有效条目时返回 None
+#[cfg(any(target_os = "windows", test))]
+fn host_resolver_rules(overrides: &BTreeMap<String, String>) -> Option<String> {
+    let rules: Vec<String> = valid_host_overrides(overrides)
+        .map(|(host, ip)| format!("MAP {} {}", host, ip))
+        .collect();
+    (!rules.is_empty()).then(|| format!("--host-resolver-rules=\"{}\"", rules.join(",")))
+}
+
+/// 跳过域名或 IP 无效的条目，IPv6 地址按 Chromium 的要求加上方括号
+///
+/// 规则拼在启动参数里，域名只接受普通的 DNS 名称（可带 `*.` 通配前缀），引号、空格、逗号等字符一律拒绝
+fn valid_host_overrides(
+    overrides: &BTreeMap<String, String>,
+) -> impl Iterator<Item = (String, String)> + '_ {
+    overrides.iter().filter_map(|(host, ip)| {
+        let host = match override_host(host) {
+            Some(host) => host,
+            None => {
+                eprintln!("警告: hosts_overrides 中的域名无效，已忽略: {:?}", host);
+                return None;
+            }
+        };
+        match ip.parse::<std::net::IpAddr>() {
+            Ok(std::net::IpAddr::V4(ip)) => Some((host, ip.to_string())),
+            Ok(std::net::IpAddr::V6(ip)) => Some((host, format!("[{}]", ip))),
+            Err(_) => {
+                eprintln!(
+                    "警告: hosts_overrides 中 {} 的 IP 无效，已忽略: {}",
+                    host, ip
+                );
+                None
+            }
+        }
+    })
+}
+
+/// 规范化为 ASCII 小写域名，IP 地址和含有 DNS 名称以外字符的返回 None
+fn override_host(host: &str) -> Option<String> {
+    let (wildcard, name) = match host.strip_prefix("*.") {
+        Some(name) => ("*.", name),
+        None => ("", host),
+    };
+    match url::Host::parse(name) {
+        Ok(url::Host::Domain(domain))
+            if domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_')) =>
+        {
+            Some(format!("{}{}", wildcard, domain))
+        }
+        _ => None,
+    }
+}
+
+/// 开关触控板左右轻扫前进后退的手势
+#[cfg(target_os = "macos")]
+pub fn set_navigation_gestures(webview: &WebView, enabled: bool) {
+    use cocoa::base::{id, BOOL, NO, YES};
+    use objc::{msg_send, sel, sel_impl};
+    use wry::webview::WebviewExtMacOS;
+
+    let enabled: BOOL = if enabled { YES } else { NO };
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let _: () = msg_send![wk_webview, setAllowsBackForwardNavigationGestures: enabled];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_navigation_gestures(_webview: &WebView, _enabled: bool) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_host_overrides_skips_invalid_ips_and_brackets_ipv6() {
+        let overrides: BTreeMap<String, String> = [
+            ("api.example.com", "10.0.0.5"),
+            ("bad.example.com", "10.0.0"),
+            ("name.example.com", "gateway.local"),
+            ("v6.example.com", "::1"),
+        ]
+        .iter()
+        .map(|(host, ip)| (host.to_string(), ip.to_string()))
+        .collect();
+        let valid: Vec<(String, String)> = valid_host_overrides(&overrides).collect();
+        assert_eq!(
+            valid,
+            vec![
+                ("api.example.com".to_string(), "10.0.0.5".to_string()),
+                ("v6.example.com".to_string(), "[::1]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn locale_argument_accepts_only_bcp47_characters() {
+        for locale in ["ja-JP", "zh-Hant-TW", "en", "es-419"] {
+            assert!(is_bcp47_tag(locale), "{}", locale);
+        }
+        for locale in [
+            "",
+            "-",
+            "en US",
+            "en-US --disable-web-security",
+            "ja_JP",
+            "en\"",
+            "de;",
+        ] {
+            assert!(!is_bcp47_tag(locale), "{:?}", locale);
+        }
+    }
+
+    #[test]
+    fn host_resolver_rules_skip_hosts_that_could_inject_arguments() {
+        let overrides: BTreeMap<String, String> = [
+            ("API.Example.com", "10.0.0.5"),
+            ("*.cdn.example.com", "10.0.0.6"),
+            ("evil.com\" --disable-web-security \"x", "10.0.0.7"),
+            ("a.com,MAP b.com", "10.0.0.8"),
+            ("with space.com", "10.0.0.9"),
+            ("10.1.1.1", "10.0.0.10"),
+            ("", "10.0.0.11"),
+        ]
+        .iter()
+        .map(|(host, ip)| (host.to_string(), ip.to_string()))
+        .collect();
+        assert_eq!(
+            host_resolver_rules(&overrides).as_deref(),
+            Some("--host-resolver-rules=\"MAP *.cdn.example.com 10.0.0.6,MAP api.example.com 10.0.0.5\"")
+        );
+        assert_eq!(host_resolver_rules(&BTreeMap::new()), None);
+    }
+}
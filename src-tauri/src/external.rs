@@ -0,0 +1,151 @@
+//! 把指向其他应用自定义协议的链接（如 `zoommtg://`、`slack://`）交给系统默认的处理程序打开
+//!
+//! 不使用 webbrowser，它会把地址当作网页交给浏览器，部分平台上无法正确处理自定义协议
+
+/// WebView 自身能够加载的协议，其余协议都视为外部协议
+const WEBVIEW_SCHEMES: [&str; 8] = [
+    "http",
+    "https",
+    "about",
+    "data",
+    "blob",
+    "file",
+    "javascript",
+    crate::protocol::PROTOCOL_NAME,
+];
+
+/// 地址使用 WebView 无法加载的协议时返回小写的协议名
+pub fn external_scheme(url: &str) -> Option<String> {
+    let scheme = url::Url::parse(url).ok()?.scheme().to_ascii_lowercase();
+    (!WEBVIEW_SCHEMES.contains(&scheme.as_str())).then(|| scheme)
+}
+
+/// 协议是否在 `external_schemes` 白名单中，配置项不区分大小写，可以带或不带结尾的冒号
+pub fn is_permitted(allowed: &[String], scheme: &str) -> bool {
+    allowed
+        .iter()
+        .any(|allowed| allowed.trim_end_matches(':').eq_ignore_ascii_case(scheme))
+}
+
+/// 导航处理器对外部协议地址的处理方式
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExternalLink {
+    /// 交给系统打开，取消 WebView 中的导航
+    Open,
+    /// 协议不在白名单中，直接拦截
+    Blocked(String),
+}
+
+/// 未配置 `external_schemes` 或地址使用 WebView 能加载的协议时返回 None，按普通导航处理
+pub fn classify(allowed: &[String], url: &str) -> Option<ExternalLink> {
+    if allowed.is_empty() {
+        return None;
+    }
+    let scheme = external_scheme(url)?;
+    Some(if is_permitted(allowed, &scheme) {
+        ExternalLink::Open
+    } else {
+        ExternalLink::Blocked(scheme)
+    })
+}
+
+#[cfg(target_os = "macos")]
+pub fn open_uri(uri: &str) -> Result<(), String> {
+    use cocoa::base::{id, nil, BOOL, NO};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let ns_uri = NSString::alloc(nil).init_str(uri);
+        let url: id = msg_send![class!(NSURL), URLWithString: ns_uri];
+        if url == nil {
+            return Err("无效的地址".to_string());
+        }
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let opened: BOOL = msg_send![workspace, openURL: url];
+        if opened == NO {
+            return Err("没有可以处理该协议的应用".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub fn open_uri(uri: &str) -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let operation = HSTRING::from("open");
+    let file = HSTRING::from(uri);
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            PCWSTR(operation.as_ptr()),
+            PCWSTR(file.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL.0 as i32,
+        )
+    };
+    // 返回值不大于 32 表示失败
+    if result.0 <= 32 {
+        return Err(format!("ShellExecuteW 返回 {}", result.0));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn open_uri(uri: &str) -> Result<(), String> {
+    gio::AppInfo::launch_default_for_uri(uri, None::<&gio::AppLaunchContext>)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schemes(schemes: &[&str]) -> Vec<String> {
+        schemes.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn external_scheme_ignores_webview_schemes() {
+        assert_eq!(external_scheme("https://example.com"), None);
+        assert_eq!(external_scheme("about:blank"), None);
+        assert_eq!(external_scheme("pake://localhost/error.html"), None);
+        assert_eq!(external_scheme("not a url"), None);
+        assert_eq!(
+            external_scheme("ZoomMtg://zoom.us/join?confno=1").as_deref(),
+            Some("zoommtg")
+        );
+        assert_eq!(
+            external_scheme("mailto:a@example.com").as_deref(),
+            Some("mailto")
+        );
+    }
+
+    #[test]
+    fn is_permitted_ignores_case_and_trailing_colon() {
+        let allowed = schemes(&["Slack:", "zoommtg"]);
+        assert!(is_permitted(&allowed, "slack"));
+        assert!(is_permitted(&allowed, "zoommtg"));
+        assert!(!is_permitted(&allowed, "mailto"));
+    }
+
+    #[test]
+    fn classify_opens_permitted_schemes_and_blocks_others() {
+        let allowed = schemes(&["zoommtg"]);
+        assert_eq!(
+            classify(&allowed, "zoommtg://zoom.us/join"),
+            Some(ExternalLink::Open)
+        );
+        assert_eq!(
+            classify(&allowed, "ms-settings:privacy"),
+            Some(ExternalLink::Blocked("ms-settings".to_string()))
+        );
+        assert_eq!(classify(&allowed, "https://zoom.us"), None);
+        assert_eq!(classify(&[], "zoommtg://zoom.us/join"), None);
+    }
+}
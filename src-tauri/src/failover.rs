@@ -0,0 +1,241 @@
+//! 主地址加载失败时依次切换到 `fallback_urls`，冷却一段时间后再尝试回到主地址
+use crate::UserEvent;
+use std::time::Duration;
+use wry::application::event_loop::EventLoopProxy;
+
+pub const DEFAULT_LOAD_TIMEOUT_MS: u64 = 15_000;
+pub const DEFAULT_COOLDOWN_SECS: u64 = 300;
+
+/// 以页面在超时前是否上报 `page_loaded` 判断加载成功，加载失败时引擎显示的错误页不会注入 pake.js；
+/// 引擎报告的导航失败（`load_failure`）会立即切换，不必等到超时
+pub struct Failover {
+    urls: Vec<String>,
+    active: usize,
+    /// 每次由 Failover 发起的加载都有新的编号，过期的超时事件直接忽略
+    generation: u64,
+    loaded: bool,
+    load_timeout: Duration,
+    cooldown: Duration,
+    /// 延迟一段时间后把事件发给事件循环
+    schedule: Box<dyn Fn(Duration, UserEvent)>,
+}
+
+impl Failover {
+    pub fn new(
+        primary: String,
+        fallbacks: &[String],
+        load_timeout: Duration,
+        cooldown: Duration,
+        proxy: EventLoopProxy<UserEvent>,
+    ) -> Self {
+        let schedule = move |delay: Duration, event: UserEvent| {
+            let proxy = proxy.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(delay);
+                let _ = proxy.send_event(event);
+            });
+        };
+        Self::with_schedule(
+            primary,
+            fallbacks,
+            load_timeout,
+            cooldown,
+            Box::new(schedule),
+        )
+    }
+
+    fn with_schedule(
+        primary: String,
+        fallbacks: &[String],
+        load_timeout: Duration,
+        cooldown: Duration,
+        schedule: Box<dyn Fn(Duration, UserEvent)>,
+    ) -> Self {
+        let mut urls = vec![primary];
+        urls.extend(fallbacks.iter().cloned());
+        Failover {
+            urls,
+            active: 0,
+            generation: 0,
+            loaded: false,
+            load_timeout,
+            cooldown,
+            schedule,
+        }
+    }
+
+    pub fn active_url(&self) -> &str {
+        &self.urls[self.active]
+    }
+
+    /// 开始监视一次加载，启动时以及每次切换地址后调用
+    pub fn watch(&mut self) {
+        self.generation += 1;
+        self.loaded = false;
+        (self.schedule)(self.load_timeout, UserEvent::LoadTimeout(self.generation));
+    }
+
+    /// 页面加载成功；当前为备用地址时，冷却后尝试回到主地址
+    pub fn loaded(&mut self) {
+        if self.loaded {
+            return;
+        }
+        self.loaded = true;
+        if self.active != 0 {
+            (self.schedule)(self.cooldown, UserEvent::RetryPrimary(self.generation));
+        }
+    }
+
+    /// 加载超时时切换到下一个地址，所有地址都失败后回到主地址继续轮询
+    pub fn timeout(&mut self, generation: u64) -> Option<String> {
+        if generation != self.generation || self.loaded {
+            return None;
+        }
+        eprintln!("警告: 页面加载超时");
+        Some(self.advance())
+    }
+
+    /// 当前地址导航失败时不等超时，直接切换到下一个地址；页面加载成功之后的导航失败不处理
+    pub fn failed(&mut self) -> Option<String> {
+        if self.loaded {
+            return None;
+        }
+        Some(self.advance())
+    }
+
+    fn advance(&mut self) -> String {
+        self.active = (self.active + 1) % self.urls.len();
+        eprintln!("警告: 切换到: {}", self.active_url());
+        self.watch();
+        self.active_url().to_string()
+    }
+
+    /// 主地址和所有备用地址都已超时，轮询回到了主地址
+    pub fn exhausted(&self) -> bool {
+        self.active == 0
+    }
+
+    /// 错误页上手动或自动重试，从主地址重新开始
+    pub fn restart(&mut self) -> String {
+        self.active = 0;
+        println!("重新加载: {}", self.active_url());
+        self.watch();
+        self.active_url().to_string()
+    }
+
+    /// 冷却结束后回到主地址，期间有过新的加载则忽略
+    pub fn retry_primary(&mut self, generation: u64) -> Option<String> {
+        if generation != self.generation || self.active == 0 {
+            return None;
+        }
+        self.active = 0;
+        println!("尝试切回主地址: {}", self.active_url());
+        self.watch();
+        Some(self.active_url().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const TIMEOUT: Duration = Duration::from_secs(15);
+    const COOLDOWN: Duration = Duration::from_secs(300);
+
+    /// 不启动线程，只记录安排的事件：("timeout" 或 "retry", 延迟, 编号)
+    fn failover(fallbacks: &[&str]) -> (Failover, Rc<RefCell<Vec<(&'static str, Duration, u64)>>>) {
+        let scheduled = Rc::new(RefCell::new(Vec::new()));
+        let record = scheduled.clone();
+        let schedule = move |delay: Duration, event: UserEvent| {
+            let entry = match event {
+                UserEvent::LoadTimeout(generation) => ("timeout", delay, generation),
+                UserEvent::RetryPrimary(generation) => ("retry", delay, generation),
+                _ => panic!("意外的事件"),
+            };
+            record.borrow_mut().push(entry);
+        };
+        let fallbacks: Vec<String> = fallbacks.iter().map(|url| url.to_string()).collect();
+        let failover = Failover::with_schedule(
+            "https://primary.example.com/".to_string(),
+            &fallbacks,
+            TIMEOUT,
+            COOLDOWN,
+            Box::new(schedule),
+        );
+        (failover, scheduled)
+    }
+
+    #[test]
+    fn timeouts_walk_through_the_fallbacks_and_wrap_around() {
+        let (mut failover, scheduled) = failover(&["https://backup.example.com/"]);
+        failover.watch();
+        assert_eq!(
+            failover.timeout(1).as_deref(),
+            Some("https://backup.example.com/")
+        );
+        assert!(!failover.exhausted());
+        assert_eq!(
+            failover.timeout(2).as_deref(),
+            Some("https://primary.example.com/")
+        );
+        assert!(failover.exhausted());
+        assert_eq!(
+            *scheduled.borrow(),
+            vec![
+                ("timeout", TIMEOUT, 1),
+                ("timeout", TIMEOUT, 2),
+                ("timeout", TIMEOUT, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_timeouts_and_loaded_pages_are_ignored() {
+        let (mut failover, _) = failover(&["https://backup.example.com/"]);
+        failover.watch();
+        failover.watch();
+        assert_eq!(failover.timeout(1), None);
+        failover.loaded();
+        assert_eq!(failover.timeout(2), None);
+        assert_eq!(failover.failed(), None);
+        assert_eq!(failover.active_url(), "https://primary.example.com/");
+    }
+
+    #[test]
+    fn navigation_failure_switches_without_waiting() {
+        let (mut failover, _) = failover(&["https://backup.example.com/"]);
+        failover.watch();
+        assert_eq!(
+            failover.failed().as_deref(),
+            Some("https://backup.example.com/")
+        );
+        // 切换后发起的新加载有新的编号，旧的超时不再生效
+        assert_eq!(failover.timeout(1), None);
+    }
+
+    #[test]
+    fn returns_to_the_primary_after_the_cooldown() {
+        let (mut failover, scheduled) = failover(&["https://backup.example.com/"]);
+        failover.watch();
+        failover.timeout(1);
+        failover.loaded();
+        assert_eq!(scheduled.borrow().last(), Some(&("retry", COOLDOWN, 2)));
+        assert_eq!(failover.retry_primary(1), None);
+        assert_eq!(
+            failover.retry_primary(2).as_deref(),
+            Some("https://primary.example.com/")
+        );
+        assert_eq!(failover.retry_primary(3), None);
+    }
+
+    #[test]
+    fn restart_goes_back_to_the_primary() {
+        let (mut failover, _) = failover(&["https://backup.example.com/"]);
+        failover.watch();
+        failover.timeout(1);
+        assert_eq!(failover.restart(), "https://primary.example.com/");
+        assert!(failover.exhausted());
+    }
+}
@@ -0,0 +1,91 @@
+//! 让窗口和托盘图标跟随页面的 favicon
+use crate::UserEvent;
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::Duration;
+use wry::application::{event_loop::EventLoopProxy, window::Icon};
+
+const MAX_FAVICON_BYTES: u64 = 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 解码后的 favicon，Icon 不能跨线程传递，先以 RGBA 数据交给事件循环
+#[derive(Debug)]
+pub struct FaviconImage {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl FaviconImage {
+    pub fn to_icon(&self) -> Result<Icon, String> {
+        Icon::from_rgba(self.rgba.clone(), self.width, self.height).map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// 按 favicon 地址缓存下载结果，失败的地址也会记录，避免重复下载
+#[derive(Default)]
+pub struct FaviconCache {
+    images: HashMap<String, Option<FaviconImage>>,
+}
+
+impl FaviconCache {
+    /// 已缓存时返回 Some，其中内层为 None 表示之前下载或解码失败
+    pub fn get(&self, url: &str) -> Option<Option<&FaviconImage>> {
+        self.images.get(url).map(Option::as_ref)
+    }
+
+    pub fn insert(&mut self, url: String, image: Option<FaviconImage>) {
+        self.images.insert(url, image);
+    }
+}
+
+/// 在后台线程下载并解码 favicon，完成后发送 `FaviconLoaded`
+pub fn spawn_fetch(url: String, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        let image = match fetch(&url) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                eprintln!("警告: 无法加载 favicon {}: {}，使用默认图标", url, e);
+                None
+            }
+        };
+        let _ = proxy.send_event(UserEvent::FaviconLoaded { url, image });
+    });
+}
+
+fn fetch(url: &str) -> Result<FaviconImage, String> {
+    // image 无法解码 SVG，直接跳过
+    if url
+        .split(&['?', '#'][..])
+        .next()
+        .unwrap_or(url)
+        .ends_with(".svg")
+    {
+        return Err("不支持 SVG 图标".to_string());
+    }
+    let response = ureq::get(url)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .call()
+        .map_err(|e| e.to_string())?;
+    if response.content_type() == "image/svg+xml" {
+        return Err("不支持 SVG 图标".to_string());
+    }
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_FAVICON_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("{:?}", e))?;
+    if bytes.len() as u64 > MAX_FAVICON_BYTES {
+        return Err(format!("图标超过 {} 字节上限", MAX_FAVICON_BYTES));
+    }
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("{:?}", e))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(FaviconImage {
+        rgba: image.into_raw(),
+        width,
+        height,
+    })
+}
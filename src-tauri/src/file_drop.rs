@@ -0,0 +1,141 @@
+//! 把拖入窗口的本地文件转交给页面，由 pake.js 填入文件输入框或模拟拖放到页面的上传区域
+use crate::UserEvent;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use std::path::PathBuf;
+use wry::application::dpi::PhysicalPosition;
+use wry::application::event_loop::EventLoopProxy;
+
+/// 一次拖放允许转交的文件总字节数，超出的文件会被跳过
+pub const MAX_DROPPED_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct DroppedFile {
+    pub name: String,
+    pub base64_content: String,
+}
+
+/// 松开拖放时光标在页面中的位置（CSS 像素），页面按这个位置查找放置目标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DropPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl DropPoint {
+    /// 由光标的屏幕位置和 WebView 所在窗口客户区的左上角换算，光标不在客户区内时返回 None
+    pub fn from_cursor(
+        cursor: PhysicalPosition<f64>,
+        inner_position: PhysicalPosition<i32>,
+        scale: f64,
+    ) -> Option<Self> {
+        let x = (cursor.x - inner_position.x as f64) / scale;
+        let y = (cursor.y - inner_position.y as f64) / scale;
+        (x >= 0.0 && y >= 0.0).then_some(DropPoint { x, y })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DroppedFiles {
+    files: Vec<DroppedFile>,
+    point: Option<DropPoint>,
+}
+
+/// 在后台线程读取文件并编码，完成后发送 `FilesDropped`，内容为文件列表和放置位置的 JSON
+pub fn spawn_read(paths: Vec<PathBuf>, point: Option<DropPoint>, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        let files = read_files(&paths);
+        if files.is_empty() {
+            return;
+        }
+        println!("拖入文件: {} 个", files.len());
+        match serde_json::to_string(&DroppedFiles { files, point }) {
+            Ok(json) => {
+                let _ = proxy.send_event(UserEvent::FilesDropped(json));
+            }
+            Err(e) => eprintln!("警告: 无法序列化拖入的文件: {:?}", e),
+        }
+    });
+}
+
+fn read_files(paths: &[PathBuf]) -> Vec<DroppedFile> {
+    let mut total = 0;
+    let mut files = Vec::new();
+    for path in paths {
+        let size = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            Ok(_) => {
+                println!("跳过非文件: {}", path.display());
+                continue;
+            }
+            Err(e) => {
+                eprintln!("警告: 无法读取 {}: {:?}", path.display(), e);
+                continue;
+            }
+        };
+        if total + size > MAX_DROPPED_BYTES {
+            eprintln!(
+                "警告: 拖入的文件超过 {} 字节上限，跳过: {}",
+                MAX_DROPPED_BYTES,
+                path.display()
+            );
+            continue;
+        }
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("警告: 无法读取 {}: {:?}", path.display(), e);
+                continue;
+            }
+        };
+        total += size;
+        files.push(DroppedFile {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            base64_content: STANDARD.encode(bytes),
+        });
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_point_is_relative_to_the_client_area() {
+        let point = DropPoint::from_cursor(
+            PhysicalPosition::new(700.0, 500.0),
+            PhysicalPosition::new(100, 80),
+            2.0,
+        );
+        assert_eq!(point, Some(DropPoint { x: 300.0, y: 210.0 }));
+    }
+
+    #[test]
+    fn drop_point_outside_the_client_area_is_ignored() {
+        let point = DropPoint::from_cursor(
+            PhysicalPosition::new(90.0, 500.0),
+            PhysicalPosition::new(100, 80),
+            1.0,
+        );
+        assert_eq!(point, None);
+    }
+
+    #[test]
+    fn files_over_the_size_limit_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("pake-file-drop-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let small = dir.join("small.txt");
+        let large = dir.join("large.bin");
+        std::fs::write(&small, b"hello").unwrap();
+        std::fs::write(&large, vec![0u8; MAX_DROPPED_BYTES as usize]).unwrap();
+        let files = read_files(&[small, dir.clone(), large]);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "small.txt");
+        assert_eq!(files[0].base64_content, "aGVsbG8=");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,280 @@
+//! 页面加载状态报告，供监控程序轮询，区分“正常显示”和“卡在加载中”，并附带进行中的下载数
+//!
+//! 报告可以写入数据目录的 `pake-health.json`，也可以通过本机的 HTTP 端点 `GET /health` 读取
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const HEALTH_FILE: &str = "pake-health.json";
+/// 读取请求的超时，避免不发送请求的连接一直占住端点线程
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageState {
+    Loading,
+    Ready,
+    Error,
+}
+
+impl PageState {
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "loading" => Some(PageState::Loading),
+            "ready" => Some(PageState::Ready),
+            "error" => Some(PageState::Error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HealthReport<'a> {
+    url: &'a str,
+    page_state: PageState,
+    /// 进入当前状态的时间
+    since: &'a str,
+    active_downloads: usize,
+}
+
+pub struct HealthReporter {
+    /// 为 None 时不写文件，只提供 HTTP 端点
+    path: Option<PathBuf>,
+    /// 最新的报告 JSON，页面还没上报过状态时为 None，由端点线程读取
+    latest: Arc<Mutex<Option<String>>>,
+    state: Option<PageState>,
+    url: String,
+    since: String,
+    active_downloads: usize,
+}
+
+impl HealthReporter {
+    /// `data_dir` 为 Some 时同时把报告写入其中的 `pake-health.json`
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        HealthReporter {
+            path: data_dir.map(|dir| dir.join(HEALTH_FILE)),
+            latest: Arc::new(Mutex::new(None)),
+            state: None,
+            url: String::new(),
+            since: String::new(),
+            active_downloads: 0,
+        }
+    }
+
+    /// 在 127.0.0.1 的端口上提供 `GET /health`，返回实际监听的端口（`port` 为 0 时由系统分配）
+    pub fn serve(&self, port: u16) -> std::io::Result<u16> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let port = listener.local_addr()?.port();
+        let latest = self.latest.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let report = latest.lock().ok().and_then(|latest| latest.clone());
+                if let Err(e) = handle_request(stream, report.as_deref()) {
+                    eprintln!("警告: 处理 /health 请求失败: {:?}", e);
+                }
+            }
+        });
+        println!("健康检查端点: http://127.0.0.1:{}/health", port);
+        Ok(port)
+    }
+
+    /// 记录状态变化并立即更新报告
+    pub fn update(&mut self, url: &str, state: PageState) {
+        if self.state != Some(state) {
+            println!("页面状态: {:?} {}", state, url);
+        }
+        self.state = Some(state);
+        self.url = url.to_string();
+        self.since = chrono::Local::now().to_rfc3339();
+        self.publish();
+    }
+
+    /// 更新进行中的下载数，页面还没上报过状态时只记录不发布
+    pub fn set_active_downloads(&mut self, count: usize) {
+        self.active_downloads = count;
+        self.publish();
+    }
+
+    fn publish(&self) {
+        let page_state = match self.state {
+            Some(state) => state,
+            None => return,
+        };
+        let report = HealthReport {
+            url: &self.url,
+            page_state,
+            since: &self.since,
+            active_downloads: self.active_downloads,
+        };
+        let json = match serde_json::to_string(&report) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("警告: 无法序列化页面状态: {:?}", e);
+                return;
+            }
+        };
+        if let Some(path) = &self.path {
+            if let Err(e) = std::fs::write(path, &json) {
+                eprintln!("警告: 无法写入页面状态: {:?}", e);
+            }
+        }
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some(json);
+        }
+    }
+}
+
+/// 只读取请求行和请求头，`GET /health` 返回报告，页面还没上报过状态时返回 503，其他路径返回 404
+fn handle_request(mut stream: TcpStream, report: Option<&str>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let port = stream.local_addr()?.port();
+    let mut buffer = [0u8; 1024];
+    let read = stream.read(&mut buffer)?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let (status, body) = respond(&request, port, report);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Host 不是本机地址加端口的请求返回 403：网页可以通过 DNS 重绑定让浏览器访问 127.0.0.1，
+/// 但这类请求的 Host 仍是网页自己的域名
+fn respond(request: &str, port: u16, report: Option<&str>) -> (&'static str, String) {
+    let mut lines = request.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let host = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.trim().to_ascii_lowercase());
+    let local = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    if !host.map_or(false, |host| local.contains(&host)) {
+        return ("403 Forbidden", r#"{"error":"forbidden"}"#.to_string());
+    }
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next().unwrap_or_default());
+    let path = target.split('?').next().unwrap_or_default();
+    match (method, path, report) {
+        (Some("GET"), "/health", Some(report)) => ("200 OK", report.to_string()),
+        (Some("GET"), "/health", None) => (
+            "503 Service Unavailable",
+            r#"{"page_state":null}"#.to_string(),
+        ),
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(port: u16, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost:{}\r\n\r\n",
+            path, port
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    fn page_state(body: &str) -> serde_json::Value {
+        serde_json::from_str::<serde_json::Value>(body).unwrap()["page_state"].clone()
+    }
+
+    #[test]
+    fn health_endpoint_follows_page_state_transitions() {
+        let mut reporter = HealthReporter::new(None);
+        let port = reporter.serve(0).unwrap();
+
+        let (status, body) = get(port, "/health");
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+        assert_eq!(page_state(&body), serde_json::Value::Null);
+
+        for (state, expected) in [
+            (PageState::Loading, "loading"),
+            (PageState::Ready, "ready"),
+            (PageState::Error, "error"),
+        ] {
+            reporter.update("https://example.com/", state);
+            let (status, body) = get(port, "/health?t=1");
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            assert_eq!(page_state(&body), expected);
+        }
+
+        reporter.set_active_downloads(2);
+        let (_, body) = get(port, "/health");
+        let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(report["url"], "https://example.com/");
+        assert_eq!(report["active_downloads"], 2);
+    }
+
+    fn request(request_line: &str, host: &str) -> String {
+        format!("{}\r\nHost: {}\r\n\r\n", request_line, host)
+    }
+
+    #[test]
+    fn health_endpoint_rejects_other_requests() {
+        let local = "127.0.0.1:9000";
+        assert_eq!(
+            respond(&request("GET / HTTP/1.1", local), 9000, Some("{}")).0,
+            "404 Not Found"
+        );
+        assert_eq!(
+            respond(&request("POST /health HTTP/1.1", local), 9000, Some("{}")).0,
+            "404 Not Found"
+        );
+        assert_eq!(respond(&request("", local), 9000, None).0, "404 Not Found");
+    }
+
+    #[test]
+    fn health_endpoint_rejects_foreign_host_headers() {
+        let get = "GET /health HTTP/1.1";
+        for host in ["127.0.0.1:9000", "LOCALHOST:9000"] {
+            assert_eq!(respond(&request(get, host), 9000, Some("{}")).0, "200 OK");
+        }
+        // DNS 重绑定后浏览器发出的请求带的是网页自己的域名
+        for host in [
+            "attacker.example:9000",
+            "localhost",
+            "127.0.0.1:9001",
+            "localhost.attacker.example:9000",
+        ] {
+            assert_eq!(
+                respond(&request(get, host), 9000, Some("{}")).0,
+                "403 Forbidden",
+                "{}",
+                host
+            );
+        }
+        assert_eq!(
+            respond(&format!("{}\r\n\r\n", get), 9000, Some("{}")).0,
+            "403 Forbidden"
+        );
+    }
+
+    #[test]
+    fn health_file_is_written_when_configured() {
+        let dir = std::env::temp_dir().join(format!("pake-health-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut reporter = HealthReporter::new(Some(&dir));
+        reporter.set_active_downloads(1);
+        assert!(!dir.join(HEALTH_FILE).exists());
+        reporter.update("https://example.com/", PageState::Ready);
+        let body = std::fs::read_to_string(dir.join(HEALTH_FILE)).unwrap();
+        assert_eq!(page_state(&body), "ready");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,43 @@
+//! 系统级快捷键，其他应用在前台时也能显示或隐藏窗口
+use std::str::FromStr;
+use wry::application::{
+    accelerator::{Accelerator, AcceleratorId},
+    event_loop::EventLoopWindowTarget,
+    global_shortcut::{GlobalShortcut, ShortcutManager},
+};
+
+pub struct GlobalHotkey {
+    // 快捷键管理器被释放后快捷键会失效，需要一直持有
+    _manager: ShortcutManager,
+    _shortcut: GlobalShortcut,
+    pub id: AcceleratorId,
+}
+
+/// 注册 `global_hotkey`，格式如 `CmdOrCtrl+Shift+Space`；无法解析或已被占用时返回 None
+pub fn register<T>(event_loop: &EventLoopWindowTarget<T>, hotkey: &str) -> Option<GlobalHotkey> {
+    let accelerator = match Accelerator::from_str(hotkey) {
+        Ok(accelerator) => accelerator,
+        Err(e) => {
+            eprintln!("警告: 无法解析全局快捷键 {}: {:?}", hotkey, e);
+            return None;
+        }
+    };
+    let mut manager = ShortcutManager::new(event_loop);
+    match manager.register(accelerator) {
+        Ok(shortcut) => {
+            println!("全局快捷键: {}", hotkey);
+            Some(GlobalHotkey {
+                id: shortcut.id(),
+                _manager: manager,
+                _shortcut: shortcut,
+            })
+        }
+        Err(e) => {
+            eprintln!(
+                "警告: 无法注册全局快捷键 {}: {:?}，可能已被其他应用占用",
+                hotkey, e
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! 页面通过 `window.ipc.postMessage` 发来的命令统一在这里解析为 `IpcCommand`，由 main.rs 中的处理器执行
+use crate::health::PageState;
+use crate::navigation::js_string;
+use crate::UserEvent;
+
+pub enum ZoomAction {
+    In,
+    Out,
+    Reset,
+}
+
+/// 解析后的 IPC 命令，需要窗口或处理器状态的命令各自对应一个变体，
+/// 其余命令直接转换为交给事件循环的 `UserEvent`
+pub enum IpcCommand<'a> {
+    Html5Fullscreen(bool),
+    UrlChanged(&'a str),
+    Zoom(ZoomAction),
+    ReloadWithQuery { key: &'a str, value: &'a str },
+    GoHome,
+    SetHome,
+    ResetHome,
+    CurlInfo(&'a str),
+    SaveFile(&'a str),
+    DragWindow,
+    DragEnd,
+    Fullscreen,
+    ResetPakeState,
+    ImportSettings,
+    Navigate(&'a str),
+    OpenWindow(&'a str),
+    OpenBrowser(&'a str),
+    Event(UserEvent),
+    /// 开启 `secure_ipc` 时缺少有效会话令牌的敏感命令
+    Rejected(&'a str),
+    Unknown,
+}
+
+/// 开启 `secure_ipc` 后需要会话令牌的命令：读写文件、导入导出设置、离开当前页面或打开新窗口、
+/// 退出应用、打印（`print_mode` 为 `pdf` 时会写入下载目录）、修改 Pake 保存的状态，以及 devtools 构建中执行脚本
+///
+/// 防范的是页面里的第三方脚本（广告、被注入的脚本）伪造消息；令牌只保存在 pake.js 的闭包中，
+/// 经页面脚本运行前绑定的原生发送函数发出，页面脚本读不到，且 pake.js 只在用户真实的点击和按键后附带令牌。
+/// 拖动、滚动、缩放等只影响窗口本身的命令不受限制
+const SENSITIVE_PREFIXES: &[&str] = &[
+    "close_window",
+    "print",
+    "save_file:",
+    "open_browser:",
+    "navigate:",
+    "open_window:",
+    "run_script_file:",
+    "set_user_agent:",
+    "import_settings",
+    "export_settings",
+    "export_logs",
+    "reset_pake_state",
+    "set_home",
+    "reset_home",
+];
+
+/// 用操作系统的随机数生成本次运行的会话令牌，注入 pake.js 后由它在敏感命令前加上 `secure:<令牌>:`
+pub fn session_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 校验并去掉令牌前缀，令牌错误或敏感命令缺少令牌时返回 Err
+fn verify_token<'a>(command: &'a str, token: &str) -> Result<&'a str, &'a str> {
+    if let Some(rest) = command.strip_prefix("secure:") {
+        return match rest.split_once(':') {
+            Some((given, command)) if given == token => Ok(command),
+            _ => Err("secure"),
+        };
+    }
+    match SENSITIVE_PREFIXES
+        .iter()
+        .find(|prefix| command.starts_with(**prefix))
+    {
+        Some(prefix) => Err(prefix.trim_end_matches(':')),
+        None => Ok(command),
+    }
+}
+
+/// `session_token` 为 None 时不校验令牌，与未开启 `secure_ipc` 时的行为一致
+pub fn parse_ipc_command<'a>(command: &'a str, session_token: Option<&str>) -> IpcCommand<'a> {
+    let command = match session_token.map(|token| verify_token(command, token)) {
+        Some(Ok(command)) => command,
+        Some(Err(name)) => return IpcCommand::Rejected(name),
+        None => command,
+    };
+    if let Some(state) = command.strip_prefix("html5_fullscreen:") {
+        return IpcCommand::Html5Fullscreen(state == "true");
+    }
+    if let Some(url) = command.strip_prefix("url_changed:") {
+        return IpcCommand::UrlChanged(url);
+    }
+    if let Some(param) = command.strip_prefix("reload_with_query:") {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        return IpcCommand::ReloadWithQuery { key, value };
+    }
+    if let Some(info) = command
+        .strip_prefix("curl_info:")
+        .filter(|_| cfg!(feature = "devtools"))
+    {
+        return IpcCommand::CurlInfo(info);
+    }
+    if let Some(payload) = command.strip_prefix("save_file:") {
+        return IpcCommand::SaveFile(payload);
+    }
+    if let Some(input) = command.strip_prefix("navigate:") {
+        return IpcCommand::Navigate(input);
+    }
+    if let Some(input) = command.strip_prefix("open_window:") {
+        return IpcCommand::OpenWindow(input);
+    }
+    if let Some(href) = command.strip_prefix("open_browser:") {
+        return IpcCommand::OpenBrowser(href);
+    }
+    match command {
+        "zoom_in" => IpcCommand::Zoom(ZoomAction::In),
+        "zoom_out" => IpcCommand::Zoom(ZoomAction::Out),
+        "zoom_reset" => IpcCommand::Zoom(ZoomAction::Reset),
+        "go_home" => IpcCommand::GoHome,
+        "set_home" => IpcCommand::SetHome,
+        "reset_home" => IpcCommand::ResetHome,
+        "drag_window" => IpcCommand::DragWindow,
+        "drag_end" => IpcCommand::DragEnd,
+        "fullscreen" => IpcCommand::Fullscreen,
+        "reset_pake_state" => IpcCommand::ResetPakeState,
+        "import_settings" => IpcCommand::ImportSettings,
+        _ => dispatch_ipc(command).map_or(IpcCommand::Unknown, IpcCommand::Event),
+    }
+}
+
+/// 插件处理函数，参数为 payload，返回的 JS 表达式作为 `pake:plugin:<name>` 事件的 detail 回传给页面
+pub type PluginHandler = fn(&str) -> Option<String>;
+
+/// 启动时注册的插件，页面发送 `plugin:<name>:<payload>` 调用，新增插件在这里添加一项
+const PLUGINS: &[(&str, PluginHandler)] = &[("echo", plugin_echo)];
+
+/// 原样返回 payload，用于验证插件调用链路
+fn plugin_echo(payload: &str) -> Option<String> {
+    Some(js_string(payload))
+}
+
+/// 将命令转换为交给事件循环处理的事件，无法识别的命令返回 None
+pub fn dispatch_ipc(command: &str) -> Option<UserEvent> {
+    let devtools = cfg!(feature = "devtools");
+    if let Some(rest) = command.strip_prefix("plugin:") {
+        return dispatch_plugin(rest);
+    }
+    if let Some(title) = command.strip_prefix("title_changed:") {
+        return Some(UserEvent::TitleChanged(title.to_string()));
+    }
+    if let Some(result) = command.strip_prefix("beforeunload_result:") {
+        return Some(UserEvent::BeforeUnloadResult(result == "true"));
+    }
+    if let Some(active) = command.strip_prefix("keep_awake:") {
+        return Some(UserEvent::KeepAwake(active == "true"));
+    }
+    if let Some(report) = command.strip_prefix("page_state:") {
+        let (state, url) = report.split_once(':').unwrap_or((report, ""));
+        return PageState::parse(state).map(|state| UserEvent::PageState {
+            state,
+            url: url.to_string(),
+        });
+    }
+    if let Some(regions) = command.strip_prefix("overlay_regions:") {
+        return Some(UserEvent::OverlayRegions(regions.to_string()));
+    }
+    if let Some(error) = command.strip_prefix("js_error:") {
+        return Some(UserEvent::JsError(error.to_string()));
+    }
+    if let Some(color) = command.strip_prefix("theme_color:") {
+        return Some(UserEvent::ThemeColor(color.to_string()));
+    }
+    if let Some(url) = command.strip_prefix("favicon:") {
+        return Some(UserEvent::FaviconChanged(url.to_string()));
+    }
+    if let Some(level) = command.strip_prefix("request_attention") {
+        let critical = match level {
+            "" | ":informational" => false,
+            ":urgent" => true,
+            _ => return None,
+        };
+        return Some(UserEvent::RequestAttention { critical });
+    }
+    if let Some(result) = command.strip_prefix("preloaded:") {
+        let (status, url) = result.split_once(':').unwrap_or(("failed", result));
+        return Some(UserEvent::Preloaded {
+            url: url.to_string(),
+            ok: status == "ok",
+        });
+    }
+    if let Some(direction) = command
+        .strip_prefix("scroll_")
+        .filter(|d| matches!(*d, "down" | "up" | "top" | "bottom"))
+    {
+        return Some(UserEvent::Scroll(direction.to_string()));
+    }
+    if let Some(log) = command.strip_prefix("console:").filter(|_| devtools) {
+        let (level, message) = log.split_once(':').unwrap_or(("log", log));
+        return Some(UserEvent::ConsoleLog(format!("[{}] {}", level, message)));
+    }
+    if let Some(path) = command.strip_prefix("run_script_file:").filter(|_| devtools) {
+        return Some(UserEvent::RunScriptFile(path.to_string()));
+    }
+    if let Some(user_agent) = command.strip_prefix("set_user_agent:").filter(|_| devtools) {
+        return Some(UserEvent::SetUserAgent(user_agent.trim().to_string()));
+    }
+    let event = match command {
+        "get_current_url" => UserEvent::ReportCurrentUrl,
+        "get_power_state" => UserEvent::ReportPowerState,
+        "page_ready" => UserEvent::PageReady,
+        "first_paint" => UserEvent::FadeIn,
+        "reload" => UserEvent::Reload,
+        "retry_load" => UserEvent::RetryLoad,
+        "close_window" => UserEvent::CloseWindow,
+        "export_logs" => UserEvent::ExportLogs,
+        "export_settings" => UserEvent::ExportSettings,
+        "print" => UserEvent::Print,
+        "toggle_decorations" => UserEvent::ToggleDecorations,
+        "toggle_high_contrast" => UserEvent::ToggleHighContrast,
+        "toggle_pip" => UserEvent::TogglePip,
+        "zoom_changed" => UserEvent::ResetZoom,
+        "copy_as_curl" if devtools => UserEvent::CopyAsCurl,
+        "show_debug_console" if devtools => UserEvent::ShowDebugConsole,
+        _ => return None,
+    };
+    Some(event)
+}
+
+fn dispatch_plugin(rest: &str) -> Option<UserEvent> {
+    let (name, payload) = rest.split_once(':').unwrap_or((rest, ""));
+    match PLUGINS.iter().find(|(plugin, _)| *plugin == name) {
+        Some((_, handler)) => handler(payload).map(|detail| UserEvent::PluginResponse {
+            name: name.to_string(),
+            detail,
+        }),
+        None => {
+            eprintln!("警告: 未注册的插件: {}", name);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    #[test]
+    fn parses_commands_with_payloads() {
+        assert!(matches!(
+            parse_ipc_command("html5_fullscreen:true", None),
+            IpcCommand::Html5Fullscreen(true)
+        ));
+        assert!(matches!(
+            parse_ipc_command("html5_fullscreen:yes", None),
+            IpcCommand::Html5Fullscreen(false)
+        ));
+        assert!(matches!(
+            parse_ipc_command("reload_with_query:lang=en", None),
+            IpcCommand::ReloadWithQuery {
+                key: "lang",
+                value: "en"
+            }
+        ));
+        assert!(matches!(
+            parse_ipc_command("reload_with_query:debug", None),
+            IpcCommand::ReloadWithQuery {
+                key: "debug",
+                value: ""
+            }
+        ));
+        assert!(matches!(
+            parse_ipc_command("zoom_out", None),
+            IpcCommand::Zoom(ZoomAction::Out)
+        ));
+        assert!(matches!(
+            parse_ipc_command("page_state:ready:https://example.com/a:b", None),
+            IpcCommand::Event(UserEvent::PageState { state: PageState::Ready, ref url })
+                if url == "https://example.com/a:b"
+        ));
+    }
+
+    #[test]
+    fn malformed_commands_are_unknown() {
+        for command in [
+            "",
+            "zoom",
+            "ZOOM_IN",
+            " zoom_in",
+            "page_state:bogus:https://example.com",
+            "request_attention:loud",
+            "scroll_sideways",
+            "plugin:missing:payload",
+        ] {
+            assert!(
+                matches!(parse_ipc_command(command, None), IpcCommand::Unknown),
+                "{:?}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn open_browser_keeps_the_whole_href() {
+        assert!(matches!(
+            parse_ipc_command("open_browser:https://example.com/a?b=c:d#e", None),
+            IpcCommand::OpenBrowser("https://example.com/a?b=c:d#e")
+        ));
+        assert!(matches!(
+            parse_ipc_command("open_browser:", None),
+            IpcCommand::OpenBrowser("")
+        ));
+        // 缺少冒号时不是 open_browser 命令
+        assert!(matches!(
+            parse_ipc_command("open_browser", None),
+            IpcCommand::Unknown
+        ));
+    }
+
+    #[test]
+    fn secure_ipc_rejects_sensitive_commands_without_the_token() {
+        assert!(matches!(
+            parse_ipc_command("open_browser:https://example.com", Some(TOKEN)),
+            IpcCommand::Rejected("open_browser")
+        ));
+        assert!(matches!(
+            parse_ipc_command("set_home", Some(TOKEN)),
+            IpcCommand::Rejected("set_home")
+        ));
+        assert!(matches!(
+            parse_ipc_command("secure:wrong:open_browser:https://example.com", Some(TOKEN)),
+            IpcCommand::Rejected("secure")
+        ));
+        assert!(matches!(
+            parse_ipc_command("secure:", Some(TOKEN)),
+            IpcCommand::Rejected("secure")
+        ));
+        for command in ["close_window", "print"] {
+            assert!(matches!(
+                parse_ipc_command(command, Some(TOKEN)),
+                IpcCommand::Rejected(name) if name == command
+            ));
+        }
+        let signed = format!("secure:{}:close_window", TOKEN);
+        assert!(matches!(
+            parse_ipc_command(&signed, Some(TOKEN)),
+            IpcCommand::Event(UserEvent::CloseWindow)
+        ));
+    }
+
+    #[test]
+    fn secure_ipc_accepts_signed_and_harmless_commands() {
+        let signed = format!("secure:{}:open_browser:https://example.com:8080/", TOKEN);
+        assert!(matches!(
+            parse_ipc_command(&signed, Some(TOKEN)),
+            IpcCommand::OpenBrowser("https://example.com:8080/")
+        ));
+        let signed = format!("secure:{}:zoom_in", TOKEN);
+        assert!(matches!(
+            parse_ipc_command(&signed, Some(TOKEN)),
+            IpcCommand::Zoom(ZoomAction::In)
+        ));
+        assert!(matches!(
+            parse_ipc_command("drag_window", Some(TOKEN)),
+            IpcCommand::DragWindow
+        ));
+    }
+
+    #[test]
+    fn session_tokens_are_random() {
+        let token = session_token().unwrap();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, session_token().unwrap());
+    }
+}
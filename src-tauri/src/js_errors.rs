@@ -0,0 +1,102 @@
+//! 页面 JavaScript 错误的记录，写入日志并可选保存到数据目录
+use serde::Deserialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const JS_ERRORS_FILE: &str = "pake-js-errors.log";
+/// 错误文件超过该大小时轮转
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// 每个时间窗口内最多记录的错误数，页面陷入错误循环时丢弃多余的错误
+const MAX_ERRORS_PER_WINDOW: u32 = 30;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// pake.js 通过 `js_error:` 上报的错误信息
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct JsError {
+    pub kind: String,
+    pub message: String,
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+    pub stack: String,
+}
+
+pub struct JsErrorLog {
+    /// 为 None 时只输出到日志，不写文件
+    path: Option<PathBuf>,
+    window_start: Instant,
+    count: u32,
+    dropped: u32,
+}
+
+impl JsErrorLog {
+    pub fn new(data_dir: &Path, save_to_file: bool) -> Self {
+        JsErrorLog {
+            path: save_to_file.then(|| data_dir.join(JS_ERRORS_FILE)),
+            window_start: Instant::now(),
+            count: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn record(&mut self, payload: &str, page_url: &str) {
+        if self.window_start.elapsed() >= RATE_WINDOW {
+            if self.dropped > 0 {
+                eprintln!("警告: 过去一分钟丢弃了 {} 条 JS 错误", self.dropped);
+            }
+            self.window_start = Instant::now();
+            self.count = 0;
+            self.dropped = 0;
+        }
+        if self.count >= MAX_ERRORS_PER_WINDOW {
+            self.dropped += 1;
+            return;
+        }
+        self.count += 1;
+
+        let error: JsError = match serde_json::from_str(payload) {
+            Ok(error) => error,
+            Err(e) => {
+                eprintln!("警告: 无效的 js_error: {:?}", e);
+                return;
+            }
+        };
+        eprintln!(
+            "JS 错误 [{}] {} ({}:{}:{}) 页面: {}",
+            error.kind, error.message, error.source, error.line, error.column, page_url
+        );
+        if let Some(path) = &self.path {
+            write_entry(path, &error, page_url);
+        }
+    }
+}
+
+fn write_entry(path: &Path, error: &JsError, page_url: &str) {
+    if std::fs::metadata(path).map_or(0, |m| m.len()) >= MAX_FILE_BYTES {
+        if let Err(e) = std::fs::rename(path, path.with_extension("log.1")) {
+            eprintln!("警告: 无法轮转 JS 错误日志: {:?}", e);
+        }
+    }
+    let entry = format!(
+        "{}\t{}\t{}\t{}:{}:{}\t{}\n{}\n",
+        chrono::Local::now().to_rfc3339(),
+        error.kind,
+        page_url,
+        error.source,
+        error.line,
+        error.column,
+        error.message,
+        error.stack
+    );
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("警告: 无法写入 JS 错误日志: {:?}", e);
+    }
+}
@@ -0,0 +1,98 @@
+//! 防止启动即崩溃时被守护进程反复拉起：记录最近的启动时间，短时间内启动次数过多则不再启动
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const LAUNCHES_FILE: &str = "pake-launches.json";
+pub const DEFAULT_WINDOW_SECS: u64 = 60;
+/// 运行超过这么久即视为启动成功，清空启动记录
+pub const DEFAULT_RESET_SECS: u64 = 30;
+
+pub struct LaunchGuard {
+    path: PathBuf,
+}
+
+impl LaunchGuard {
+    /// 记录本次启动；`window` 内未正常运行的启动次数达到 `max_launches` 时返回 Err，内容为最近的启动次数
+    pub fn check(data_dir: &Path, max_launches: u32, window: Duration) -> Result<Self, usize> {
+        let path = data_dir.join(LAUNCHES_FILE);
+        let now = unix_now();
+        let mut launches: Vec<u64> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        launches.retain(|&time| now.saturating_sub(time) <= window.as_secs());
+        if launches.len() >= max_launches as usize {
+            return Err(launches.len());
+        }
+        launches.push(now);
+        if let Err(e) = serde_json::to_string(&launches)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(&path, json))
+        {
+            eprintln!("警告: 无法写入启动记录: {:?}", e);
+        }
+        Ok(LaunchGuard { path })
+    }
+
+    /// 运行 `after` 之后清空启动记录
+    pub fn reset_after(self, after: Duration) {
+        std::thread::spawn(move || {
+            std::thread::sleep(after);
+            let _ = std::fs::remove_file(&self.path);
+        });
+    }
+}
+
+/// 正常退出时清空启动记录，很快主动退出的启动不算作崩溃
+pub fn clear(data_dir: &Path) {
+    let _ = std::fs::remove_file(data_dir.join(LAUNCHES_FILE));
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pake-launch-guard-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const WINDOW: Duration = Duration::from_secs(DEFAULT_WINDOW_SECS);
+
+    #[test]
+    fn stops_after_too_many_recent_launches() {
+        let dir = temp_dir("limit");
+        assert!(LaunchGuard::check(&dir, 2, WINDOW).is_ok());
+        assert!(LaunchGuard::check(&dir, 2, WINDOW).is_ok());
+        assert_eq!(LaunchGuard::check(&dir, 2, WINDOW).err(), Some(2));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn clean_exit_clears_the_launch_record() {
+        let dir = temp_dir("clear");
+        assert!(LaunchGuard::check(&dir, 1, WINDOW).is_ok());
+        clear(&dir);
+        assert!(LaunchGuard::check(&dir, 1, WINDOW).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn old_launches_fall_out_of_the_window() {
+        let dir = temp_dir("window");
+        let old = unix_now() - DEFAULT_WINDOW_SECS - 10;
+        std::fs::write(dir.join(LAUNCHES_FILE), format!("[{}, {}]", old, old)).unwrap();
+        assert!(LaunchGuard::check(&dir, 1, WINDOW).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,131 @@
+//! 监听 WebView 主框架的导航失败（断网、DNS 解析失败、连接被拒绝等），发送 `LoadFailed`
+//!
+//! 失败时立即切换地址或显示错误页，不必等到加载超时；被新导航取消或被拦截的导航不算失败
+use crate::UserEvent;
+use wry::application::event_loop::EventLoopProxy;
+use wry::webview::WebView;
+
+#[cfg(target_os = "linux")]
+pub fn watch(webview: &WebView, proxy: EventLoopProxy<UserEvent>) {
+    use webkit2gtk::{NetworkError, PolicyError, WebViewExt};
+    use wry::webview::WebviewExtUnix;
+
+    webview
+        .webview()
+        .connect_load_failed(move |_webview, _event, uri, error| {
+            let ignored = error.matches(NetworkError::Cancelled)
+                || error.matches(PolicyError::FrameLoadInterruptedByPolicyChange);
+            if !ignored {
+                let _ = proxy.send_event(UserEvent::LoadFailed(format!("{}: {}", uri, error)));
+            }
+            // 保留 WebKit 自带的错误页，由事件循环决定是否跳转
+            false
+        });
+}
+
+#[cfg(target_os = "windows")]
+pub fn watch(webview: &WebView, proxy: EventLoopProxy<UserEvent>) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_WEB_ERROR_STATUS, COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED,
+    };
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::System::WinRT::EventRegistrationToken;
+    use wry::webview::WebviewExtWindows;
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |_sender, args| {
+        let args = match args {
+            Some(args) => args,
+            None => return Ok(()),
+        };
+        let mut success = BOOL::default();
+        let mut status = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+        unsafe {
+            args.IsSuccess(&mut success)?;
+            args.WebErrorStatus(&mut status)?;
+        }
+        if !success.as_bool() && status != COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED {
+            let _ = proxy.send_event(UserEvent::LoadFailed(format!(
+                "WebErrorStatus {}",
+                status.0
+            )));
+        }
+        Ok(())
+    }));
+    let mut token = EventRegistrationToken::default();
+    let result = unsafe {
+        webview
+            .controller()
+            .CoreWebView2()
+            .and_then(|core| core.add_NavigationCompleted(&handler, &mut token))
+    };
+    if let Err(e) = result {
+        eprintln!("警告: 无法监听页面加载失败: {:?}", e);
+    }
+}
+
+/// 给 WKWebView 的导航代理补上 `webView:didFailProvisionalNavigation:withError:`
+#[cfg(target_os = "macos")]
+pub fn watch(webview: &WebView, proxy: EventLoopProxy<UserEvent>) {
+    use cocoa::base::{id, nil};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{class_addMethod, object_getClass, Class, Imp, Object, Sel, NO};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::sync::Mutex;
+    use wry::webview::WebviewExtMacOS;
+
+    static PROXY: Mutex<Option<EventLoopProxy<UserEvent>>> = Mutex::new(None);
+    /// NSURLErrorCancelled
+    const CANCELLED: isize = -999;
+    /// WebKitErrorFrameLoadInterruptedByPolicyChange
+    const INTERRUPTED_BY_POLICY: isize = 102;
+
+    extern "C" fn did_fail(_: &Object, _: Sel, _: id, _: id, error: id) {
+        unsafe {
+            let code: isize = msg_send![error, code];
+            if code == CANCELLED || code == INTERRUPTED_BY_POLICY {
+                return;
+            }
+            let description: id = msg_send![error, localizedDescription];
+            let utf8: *const c_char = msg_send![description, UTF8String];
+            let message = if utf8.is_null() {
+                format!("NSError {}", code)
+            } else {
+                CStr::from_ptr(utf8).to_string_lossy().into_owned()
+            };
+            if let Some(proxy) = PROXY.lock().ok().and_then(|proxy| proxy.clone()) {
+                let _ = proxy.send_event(UserEvent::LoadFailed(message));
+            }
+        }
+    }
+
+    if let Ok(mut slot) = PROXY.lock() {
+        *slot = Some(proxy);
+    }
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let mut delegate: id = msg_send![wk_webview, navigationDelegate];
+        if delegate == nil {
+            // WKWebView 只弱引用导航代理，创建的代理随应用一直存在，不释放
+            let class = match ClassDecl::new("PakeNavigationDelegate", class!(NSObject)) {
+                Some(decl) => decl.register(),
+                None => class!(PakeNavigationDelegate),
+            };
+            delegate = msg_send![class, new];
+            let _: () = msg_send![wk_webview, setNavigationDelegate: delegate];
+        }
+        let class = object_getClass(delegate as *const Object) as *mut Class;
+        let imp: Imp = std::mem::transmute(did_fail as extern "C" fn(&Object, Sel, id, id, id));
+        let added = class_addMethod(
+            class,
+            sel!(webView:didFailProvisionalNavigation:withError:),
+            imp,
+            "v@:@@@\0".as_ptr() as *const c_char,
+        );
+        if added == NO {
+            eprintln!("警告: 导航代理已实现 didFailProvisionalNavigation，无法监听页面加载失败");
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! `export_logs`：把最近的页面日志、导航审计日志和崩溃日志导出为一个文本文件，方便用户反馈问题
+use crate::audit::AUDIT_FILE;
+use crate::debug_console::ConsoleBuffer;
+use crate::download;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// 写入下载目录，返回导出文件的路径
+pub fn export(
+    console: &ConsoleBuffer,
+    data_dir: &Path,
+    webview_version: &str,
+) -> Result<PathBuf, String> {
+    let now = chrono::Local::now();
+    let mut report = String::new();
+    let _ = writeln!(report, "=== Pake 日志导出 ===");
+    let _ = writeln!(report, "时间: {}", now.to_rfc3339());
+    let _ = writeln!(
+        report,
+        "系统: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "WebView: {}", webview_version);
+
+    let _ = writeln!(report, "\n=== 页面日志 ===");
+    for line in console.lines() {
+        let _ = writeln!(report, "{}", line);
+    }
+
+    for (title, path) in [
+        ("导航审计日志", Some(data_dir.join(AUDIT_FILE))),
+        ("崩溃日志", crate::crash_log_path()),
+    ] {
+        if let Some(content) = path.and_then(|p| std::fs::read_to_string(p).ok()) {
+            let _ = writeln!(report, "\n=== {} ===\n{}", title, content);
+        }
+    }
+
+    let file_name = format!("pake-logs-{}.txt", now.format("%Y%m%d-%H%M%S"));
+    download::save_bytes(&file_name, report.as_bytes())
+}
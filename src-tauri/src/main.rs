@@ -2,15 +2,75 @@
 // 临时注释掉以显示错误信息，生产环境可以恢复
 // #![windows_subsystem = "windows"]
 extern crate image;
+mod audit;
+mod auto_login;
+mod cache;
+mod config;
+mod curl;
+mod debug_console;
+mod dev_script;
+mod diagnose;
+mod dialog;
+mod disk;
+mod download;
+mod engine;
+mod external;
+mod failover;
+mod favicon;
+mod file_drop;
+mod health;
+mod hotkey;
+mod ipc;
+mod js_errors;
+mod launch_guard;
+mod load_failure;
+mod logs;
+mod memory;
+mod migrate;
+mod navigation;
+mod network;
+mod overlay;
+mod popup;
+mod power;
+mod print;
+mod protocol;
+mod schedule;
+mod screenshot;
+mod script_dialog;
+mod settings;
+mod splash;
+mod state;
+mod title;
+mod tray;
+mod window_ext;
+
+use audit::AuditLog;
+use config::{
+    get_pake_config, BeforeUnloadMode, BlurBehavior, CloseButtonBehavior, DialogMode, ForceTheme,
+    HighMemoryAction, MonitorSelector, WindowDimension, WindowLevel,
+};
+use debug_console::ConsoleBuffer;
+use external::ExternalLink;
+use failover::Failover;
+use ipc::{IpcCommand, ZoomAction};
+use navigation::{
+    is_allowed, js_string, loopback_origin, navigation_allowlist, parse_navigation_target,
+    with_query_param, ApprovedNavigation, CurrentUrl, QueryStripper, UrlPatterns, UrlRewriter,
+    DEFAULT_TRACKING_PARAMS,
+};
+use schedule::Schedule;
+use state::{HostZoom, PinnedHome, SavedWindow, WindowSession, WindowState};
 use tauri_utils::config::{Config, WindowConfig};
 use wry::{
     application::{
-        event::{Event, StartCause, WindowEvent},
+        dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position},
+        event::{Event, StartCause, TrayEvent, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
         menu::MenuType,
-        window::{Fullscreen, Window, WindowBuilder},
+        monitor::MonitorHandle,
+        window::{Fullscreen, Theme, UserAttentionType, Window, WindowBuilder},
     },
-    webview::WebViewBuilder,
+    webview::{FileDropEvent, WebView, WebViewBuilder},
 };
 
 #[cfg(target_os = "macos")]
@@ -24,18 +84,95 @@ use wry::application::{
 #[cfg(target_os = "windows")]
 use wry::application::window::Icon;
 
-#[cfg(any(target_os = "linux", target_os = "windows"))]
 use wry::webview::WebContext;
 
-use dirs::download_dir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "macos")]
+const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.1 Safari/605.1.15";
+#[cfg(target_os = "windows")]
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36";
+#[cfg(target_os = "linux")]
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36";
+
+/// 页面迟迟没有上报首帧时，最多等待这么久就开始淡入
+const FADE_IN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// `blur_behavior` 为 `dim` 时失去焦点后的窗口不透明度
+const BLUR_DIM_OPACITY: f64 = 0.5;
+/// 失去焦点后等待最小化动画结束的时间
+const MINIMIZE_CHECK_DELAY: std::time::Duration = std::time::Duration::from_millis(600);
 
 enum UserEvent {
     DownloadStarted(String, String),
-    DownloadComplete(#[allow(dead_code)] Option<PathBuf>, bool), // path 字段保留用于未来扩展
+    DownloadComplete(String, Option<PathBuf>, bool),
+    Navigate(String),
+    Toast(String),
+    TogglePip,
+    ReportCurrentUrl,
+    BeforeUnloadResult(bool),
+    ToggleHighContrast,
+    CopyAsCurl,
+    ConsoleLog(String),
+    ShowDebugConsole,
+    RunScriptFile(String),
+    FadeIn,
+    WindowOpacity(f64),
+    SetUserAgent(String),
+    TitleChanged(String),
+    OpenWindow(String),
+    PageReady,
+    SlowLoad,
+    Reload,
+    CloseWindow,
+    ExportLogs,
+    ExportSettings,
+    SettingsImported,
+    NetworkReady(bool),
+    ThemeColor(String),
+    MonitorScreenshot,
+    Scroll(String),
+    ApplyHostZoom,
+    UpdateGestures,
+    Zoom(ZoomAction),
+    Print,
+    ToggleDecorations,
+    ToggleVisible,
+    PluginResponse { name: String, detail: String },
+    LoadTimeout(u64),
+    /// 主框架导航失败，内容为错误描述
+    LoadFailed(String),
+    RetryPrimary(u64),
+    RetryLoad,
+    RequestAttention { critical: bool },
+    JsError(String),
+    KeepAwake(bool),
+    PageState { state: health::PageState, url: String },
+    OverlayRegions(String),
+    OverlayPoll,
+    ReportPowerState,
+    PowerChanged(power::PowerState),
+    HighMemory(u64),
+    OpenExternal(String),
+    AutoLogin,
+    SystemResumed,
+    ResetZoom,
+    Preloaded { url: String, ok: bool },
+    PakeStateReset,
+    FilesDropped(String),
+    FaviconChanged(String),
+    FaviconLoaded { url: String, image: Option<favicon::FaviconImage> },
+    ConfirmNavigation(String),
+    /// macOS 上 Dock 菜单退出、注销等绕过菜单的退出请求
+    QuitRequested,
+    /// 失去焦点后检查窗口是否已最小化，用于 `minimize_to_tray`
+    CheckMinimized,
 }
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--diagnose") {
+        std::process::exit(diagnose::run());
+    }
+
     // 立即刷新输出，确保能看到日志
     use std::io::Write;
     let _ = std::io::stdout().flush();
@@ -58,7 +195,7 @@ fn main() {
             let _ = std::io::stderr().flush();
             
             // 写入日志文件
-            let log_path = std::env::var("APPDATA").unwrap_or_default() + "\\pake_crash.log";
+            let log_path = crash_log_path().unwrap_or_default().display().to_string();
             if let Ok(mut file) = OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -118,9 +255,82 @@ fn main() {
 
 fn main_inner() -> wry::Result<()> {
     println!("Pake 应用启动中...");
+    let mut pake_config = get_pake_config();
+    // import_settings 导入的配置叠加在打包的 pake.json 上，需要在读取其他配置之前完成；数据目录仍以打包的配置为准
+    let packaged_config = pake_config.clone();
+    let imported_config = get_windows_config()
+        .0
+        .and_then(|name| {
+            custom_data_dir(pake_config.data_dir.as_deref())
+                .or_else(|| default_data_dir(&name.to_lowercase()))
+        })
+        .and_then(|dir| settings::load_imported_config(&dir, &packaged_config));
+    if let Some(imported) = imported_config {
+        pake_config = imported;
+    }
+    if pake_config.lock_zoom && (pake_config.per_host_zoom || pake_config.initial_zoom.is_some()) {
+        println!("警告: 已开启 lock_zoom，忽略 initial_zoom 和 per_host_zoom");
+        pake_config.per_host_zoom = false;
+        pake_config.initial_zoom = None;
+    }
+    if pake_config.dialog_mode != DialogMode::Default {
+        println!("JS 对话框处理方式: {:?}", pake_config.dialog_mode);
+    }
+    if let Some(path) = pake_config.webview2_fixed_runtime_path.as_deref() {
+        #[cfg(target_os = "windows")]
+        engine::use_webview2_fixed_runtime(path);
+        #[cfg(not(target_os = "windows"))]
+        println!(
+            "警告: webview2_fixed_runtime_path 仅在 Windows 上生效，忽略: {}",
+            path.display()
+        );
+    }
+    #[cfg(target_os = "windows")]
+    if !engine::ensure_webview2_runtime() {
+        std::process::exit(1);
+    }
+    let webview_version = engine::webview_version();
+    println!("WebView 引擎版本: {}", webview_version);
+    let config_script = format!(
+        "{} window.pakeWebviewVersion = {};",
+        pake_config.init_script(),
+        js_string(&webview_version)
+    );
+    // 令牌直接写入 pake.js 源码，只存在于其闭包中，不经过页面可见的 __PAKE_CONFIG__
+    let ipc_token = match pake_config.secure_ipc.then(ipc::session_token) {
+        Some(Ok(token)) => Some(token),
+        // 令牌可被猜到时 secure_ipc 形同虚设，宁可不启动
+        Some(Err(e)) => {
+            eprintln!("错误: 无法生成 IPC 会话令牌: {}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    let pake_script =
+        include_str!("pake.js").replace("__PAKE_IPC_TOKEN__", ipc_token.as_deref().unwrap_or(""));
+    // 渲染相关的环境变量必须在创建 WebView 之前设置
+    if pake_config.disable_gpu {
+        disable_gpu_rendering();
+    }
+    if let Some(args) = pake_config.webview2_additional_args.as_deref() {
+        #[cfg(target_os = "windows")]
+        {
+            append_webview2_args(args);
+            println!("WebView2 额外启动参数: {}", args);
+        }
+        #[cfg(not(target_os = "windows"))]
+        println!("警告: webview2_additional_args 仅在 Windows 上生效，忽略: {}", args);
+    }
+    if let Some(timezone) = pake_config.override_timezone.as_deref() {
+        engine::override_timezone(timezone);
+    }
+    if let Some(locale) = pake_config.override_locale.as_deref() {
+        engine::override_locale(locale);
+    }
+    engine::apply_hosts_overrides(&pake_config.hosts_overrides);
     
     #[cfg(target_os = "macos")]
-    let (menu_bar_menu, close_item) = {
+    let (menu_bar_menu, close_item, quit_item) = {
         let mut menu_bar_menu = Menu::new();
         let mut first_menu = Menu::new();
         first_menu.add_native_item(MenuItem::Hide);
@@ -138,13 +348,24 @@ fn main_inner() -> wry::Result<()> {
             MenuItemAttributes::new("CloseWindow")
                 .with_accelerators(&Accelerator::new(SysMods::Cmd, KeyCode::KeyW)),
         );
-        first_menu.add_native_item(MenuItem::Quit);
+        // 原生的 Quit 会直接结束进程，需要确认时改用自定义菜单项，经过 exit_app 退出
+        let quit_item = if pake_config.confirm_quit {
+            let item = first_menu.add_item(
+                MenuItemAttributes::new("Quit")
+                    .with_accelerators(&Accelerator::new(SysMods::Cmd, KeyCode::KeyQ)),
+            );
+            Some(item.id())
+        } else {
+            first_menu.add_native_item(MenuItem::Quit);
+            None
+        };
         menu_bar_menu.add_submenu("App", true, first_menu);
-        (menu_bar_menu, close_item)
+        (menu_bar_menu, close_item, quit_item)
     };
 
     #[cfg(any(target_os = "linux", target_os = "windows"))]
     let (
+        product_name,
         package_name,
         WindowConfig {
             url,
@@ -152,6 +373,10 @@ fn main_inner() -> wry::Result<()> {
             height,
             resizable,
             fullscreen,
+            min_width,
+            min_height,
+            max_width,
+            max_height,
             ..
         },
     ) = {
@@ -164,32 +389,223 @@ fn main_inner() -> wry::Result<()> {
                 std::process::exit(1);
             }
         };
-        let package_name = match package_name {
-            Some(name) => name.to_lowercase(),
+        let product_name = match package_name {
+            Some(name) => name,
             None => {
                 eprintln!("错误: 配置文件中没有 package name");
                 std::process::exit(1);
             }
         };
+        let package_name = product_name.to_lowercase();
         let config = windows_config.unwrap_or_default();
         println!("配置读取成功: package_name={}, url={}", package_name, config.url.to_string());
-        (package_name, config)
+        (product_name, package_name, config)
     };
 
     #[cfg(target_os = "macos")]
-    let WindowConfig {
-        url,
-        width,
-        height,
-        resizable,
-        transparent,
-        fullscreen,
-        ..
-    } = get_windows_config().1.unwrap_or_default();
+    let (
+        product_name,
+        package_name,
+        WindowConfig {
+            url,
+            width,
+            height,
+            resizable,
+            transparent,
+            fullscreen,
+            min_width,
+            min_height,
+            max_width,
+            max_height,
+            ..
+        },
+    ) = {
+        let (package_name, windows_config) = get_windows_config();
+        let product_name = package_name.unwrap_or_default();
+        (
+            product_name.clone(),
+            product_name.to_lowercase(),
+            windows_config.unwrap_or_default(),
+        )
+    };
+
+    let build_info = pake_config
+        .splash_show_version
+        .then(|| splash::BuildInfo::new(&product_name));
+    let splash_script = if pake_config.splash_image.is_some() || build_info.is_some() {
+        splash::splash_script(pake_config.splash_image.as_deref(), pake_config.splash_fit, build_info)
+    } else {
+        String::new()
+    };
+
+    let data_dir = get_data_dir(&package_name, pake_config.data_dir.as_deref());
+    // 必须在创建 WebContext 之前迁移，否则 WebView 会先在新目录中写入空的存储
+    if !pake_config.migrate_from.is_empty() {
+        let previous_dirs: Vec<PathBuf> = pake_config
+            .migrate_from
+            .iter()
+            .filter_map(|name| default_data_dir(&name.to_lowercase()))
+            .collect();
+        migrate::migrate_data_dir(&data_dir, &previous_dirs);
+    }
+    if let Some(min_free_mb) = pake_config.min_free_space_mb {
+        let low_space: Vec<String> = [download::downloads_dir(), data_dir.clone()]
+            .iter()
+            .filter_map(|dir| disk::check_free_space(dir, min_free_mb).err())
+            .collect();
+        if !low_space.is_empty() {
+            eprintln!("警告: 磁盘剩余空间不足: {}", low_space.join(" "));
+            dialog::warning(
+                "Low disk space",
+                &format!("{}\n\nDownloads may fail until space is freed.", low_space.join("\n")),
+            );
+        }
+    }
+    if std::env::args().any(|arg| arg == "--reset") {
+        println!("清除 Pake 状态: {}", data_dir.display());
+        state::reset_all(&data_dir);
+    }
+    if std::env::args().any(|arg| arg == "--export-settings") {
+        match settings::export(&pake_config, &data_dir) {
+            Ok(path) => {
+                println!("设置已导出: {}", path.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("错误: 无法导出设置: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(path) = std::env::args()
+        .skip_while(|arg| arg != "--import-settings")
+        .nth(1)
+    {
+        match settings::import(Path::new(&path), &data_dir, &packaged_config) {
+            Ok(files) => {
+                println!("设置已导入，下次启动时生效，状态文件: {:?}", files);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("错误: 无法导入设置 {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(max_launches) = pake_config.crash_loop_max_launches {
+        let window = pake_config
+            .crash_loop_window_secs
+            .unwrap_or(launch_guard::DEFAULT_WINDOW_SECS);
+        match launch_guard::LaunchGuard::check(
+            &data_dir,
+            max_launches,
+            std::time::Duration::from_secs(window),
+        ) {
+            Ok(guard) => {
+                guard.reset_after(std::time::Duration::from_secs(launch_guard::DEFAULT_RESET_SECS))
+            }
+            Err(launches) => {
+                eprintln!("错误: {} 秒内已启动 {} 次均未正常运行，停止启动", window, launches);
+                let log_hint = crash_log_path()
+                    .map(|path| format!("\n\nCrash log: {}", path.display()))
+                    .unwrap_or_default();
+                dialog::error(
+                    "App keeps crashing",
+                    &format!(
+                        "The app crashed {} times within {} seconds and will not be restarted.{}",
+                        launches, window, log_hint
+                    ),
+                );
+                std::process::exit(1);
+            }
+        }
+    }
 
     let event_loop: EventLoop<UserEvent> = EventLoop::with_user_event();
     let proxy = event_loop.create_proxy();
-    let common_window = WindowBuilder::new()
+    #[cfg(target_os = "macos")]
+    if pake_config.confirm_quit {
+        window_ext::intercept_app_terminate(proxy.clone());
+    }
+
+    // 上次退出时打开的窗口，第一个为主窗口；没有保存过窗口时按默认方式启动。
+    // 白名单可能在两次运行之间收紧，不再允许的地址直接丢弃
+    let restore_allowlist =
+        navigation_allowlist(&pake_config.navigation_allowlist, &[url.to_string()]);
+    let mut restored_windows = if pake_config.restore_windows {
+        WindowSession::load(&data_dir).windows
+    } else {
+        Vec::new()
+    }
+    .into_iter()
+    .filter(|saved| {
+        let allowed = is_allowed(&restore_allowlist, &saved.url);
+        if !allowed {
+            eprintln!("警告: 保存的窗口地址不在白名单内，不再恢复: {}", saved.url);
+        }
+        allowed
+    });
+    let restored_main = restored_windows.next();
+
+    // 按时间表决定启动地址，之后由定时器负责切换
+    let schedule = Schedule::new(&pake_config.schedule);
+    let start_url = schedule
+        .current_url()
+        .map(String::from)
+        .or_else(|| restored_main.as_ref().map(|main| main.url.clone()))
+        .unwrap_or_else(|| url.to_string());
+    // 时间表中的地址与启动地址一样始终允许导航
+    let schedule_urls: Vec<String> = schedule.urls().map(String::from).collect();
+    if !schedule.is_empty() {
+        schedule.spawn(url.to_string(), proxy.clone());
+    }
+    if pake_config.localhost_secure_context {
+        match loopback_origin(&start_url) {
+            Some(origin) => engine::treat_as_secure_origin(&origin),
+            None => println!("启动地址不是本机地址，忽略 localhost_secure_context"),
+        }
+    }
+    let saved_window_state = if pake_config.remember_window_state {
+        WindowState::load(&data_dir)
+    } else {
+        None
+    }
+    .or_else(|| restored_main.as_ref().map(|main| main.geometry));
+    let start_monitor = pake_config
+        .start_monitor
+        .as_ref()
+        .and_then(|selector| find_start_monitor(&event_loop, selector));
+    // 百分比尺寸按启动显示器（默认主显示器）的工作区换算，换算后在工作区内居中显示
+    let mut centered_position = None;
+    let (width, height) = match start_monitor.clone().or_else(|| event_loop.primary_monitor()) {
+        Some(monitor)
+            if pake_config.window_width.is_some()
+                || pake_config.window_height.is_some()
+                || start_monitor.is_some() =>
+        {
+            let scale = monitor.scale_factor();
+            let area = window_ext::work_area(&monitor);
+            let (width, height) = resolve_window_size(
+                (&pake_config.window_width, &pake_config.window_height),
+                area.size.to_logical(scale),
+                (width, height),
+                (min_width, min_height),
+                (max_width, max_height),
+            );
+            let uses_percent = [&pake_config.window_width, &pake_config.window_height]
+                .iter()
+                .any(|d| d.as_ref().map_or(false, WindowDimension::is_percent));
+            if uses_percent || start_monitor.is_some() {
+                centered_position =
+                    Some(area.centered(LogicalSize::new(width, height).to_physical(scale)));
+            }
+            println!("窗口尺寸: {}x{}", width, height);
+            (width, height)
+        }
+        _ => (width, height),
+    };
+    let mut common_window = WindowBuilder::new()
         .with_title("")
         .with_resizable(resizable)
         .with_fullscreen(if fullscreen {
@@ -197,54 +613,61 @@ fn main_inner() -> wry::Result<()> {
         } else {
             None
         })
-        .with_inner_size(wry::application::dpi::LogicalSize::new(width, height));
-
-    #[cfg(target_os = "windows")]
-    let window = {
-        // 获取可执行文件所在目录，用于查找资源文件
-        let exe_dir = match std::env::current_exe() {
-            Ok(exe_path) => {
-                exe_path.parent().map(|p| p.to_path_buf()).unwrap_or_default()
-            }
-            Err(e) => {
-                println!("警告: 无法获取可执行文件路径: {:?}，使用当前目录", e);
-                std::env::current_dir().unwrap_or_default()
+        .with_inner_size(LogicalSize::new(width, height));
+    if let Some(position) = centered_position {
+        common_window = common_window.with_position(position);
+    }
+    if let Some(theme) = pake_config.force_theme {
+        common_window = common_window.with_theme(Some(forced_theme(theme)));
+    }
+    if let (Some(w), Some(h)) = (
+        pake_config.resize_increment_width,
+        pake_config.resize_increment_height,
+    ) {
+        if w > 0.0 && h > 0.0 {
+            #[cfg(target_os = "linux")]
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                println!("警告: Wayland 下窗口管理器可能不支持调整大小步进");
             }
-        };
-        println!("可执行文件目录: {}", exe_dir.display());
-        
-        // 尝试查找图标文件，支持中文名称和英文哈希名称
-        // 先尝试相对路径（开发环境），再尝试可执行文件目录（安装后）
-        let mut icon_paths = vec![
-            format!("png/{}_32.ico", package_name),
-            exe_dir.join(format!("png/{}_32.ico", package_name)).to_string_lossy().to_string(),
-        ];
-        
-        // 如果 package_name 包含非 ASCII 字符，添加英文哈希名称路径
-        let has_non_ascii = package_name.chars().any(|c| c as u32 > 127);
-        if has_non_ascii {
-            let hash = md5::compute(package_name.as_bytes());
-            let hash_hex = format!("{:x}", hash);
-            let hash_prefix = &hash_hex[..8.min(hash_hex.len())];
-            icon_paths.push(format!("png/app{}_32.ico", hash_prefix));
-            icon_paths.push(exe_dir.join(format!("png/app{}_32.ico", hash_prefix)).to_string_lossy().to_string());
+            common_window = common_window.with_resize_increments(LogicalSize::new(w, h));
+        } else {
+            eprintln!("警告: 调整大小步进必须为正数，已忽略: {}x{}", w, h);
         }
-        
-        // 添加默认图标路径
-        icon_paths.push("png/icon_32.ico".to_string());
-        icon_paths.push(exe_dir.join("png/icon_32.ico").to_string_lossy().to_string());
-        
-        // 尝试每个路径，找到第一个存在的
-        let mut icon_path = None;
-        for path_str in &icon_paths {
-            let path = std::path::Path::new(path_str);
-            if path.exists() {
-                println!("找到图标文件: {}", path_str);
-                icon_path = Some(path_str.clone());
-                break;
+    }
+    if pake_config.window_level == WindowLevel::AlwaysOnTop {
+        common_window = common_window.with_always_on_top(true);
+    }
+    // 悬浮层模式：置顶、透明、无边框，透明区域的点击穿透到下面的窗口
+    if pake_config.overlay_mode {
+        common_window = common_window
+            .with_always_on_top(true)
+            .with_transparent(true)
+            .with_decorations(false);
+    }
+    if let Some(state) = saved_window_state {
+        println!("恢复窗口状态: {:?}", state);
+        // 恢复为最大化时不再设置具体大小，避免与最大化冲突
+        if state.maximized {
+            common_window = common_window.with_maximized(true);
+        } else if state.has_geometry() {
+            common_window =
+                common_window.with_inner_size(PhysicalSize::new(state.width, state.height));
+            // 指定了启动显示器时，只有上次的位置仍在该显示器上才沿用，否则保持居中
+            let on_start_monitor = start_monitor.as_ref().map_or(true, |monitor| {
+                window_ext::work_area(monitor).contains(state.x, state.y)
+            });
+            if on_start_monitor {
+                common_window = common_window.with_position(PhysicalPosition::new(state.x, state.y));
             }
         }
-        
+        if state.fullscreen {
+            common_window = common_window.with_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    let window = {
+        let icon_path = find_icon_path(&package_name);
         let icon = if let Some(ref path_str) = icon_path {
             match load_icon(std::path::Path::new(path_str)) {
                 Ok(icon) => {
@@ -261,7 +684,7 @@ fn main_inner() -> wry::Result<()> {
             None
         };
         
-        let mut window_builder = common_window.with_decorations(true);
+        let mut window_builder = common_window.with_decorations(!pake_config.overlay_mode);
         if let Some(icon) = icon {
             window_builder = window_builder.with_window_icon(Some(icon));
         }
@@ -286,6 +709,7 @@ fn main_inner() -> wry::Result<()> {
         .with_titlebar_buttons_hidden(false)
         .with_titlebar_transparent(transparent)
         .with_title_hidden(true)
+        .with_has_shadow(!pake_config.overlay_mode)
         .with_menu(menu_bar_menu)
         .build(&event_loop)
         .map_err(|e| {
@@ -293,31 +717,335 @@ fn main_inner() -> wry::Result<()> {
             e
         })?;
 
+    // 导航白名单，启动地址所在域名始终允许
+    let error_page_url = pake_config
+        .custom_error_page()
+        .then(|| protocol::builtin_url("error.html"));
+    // 开启 wait_for_network 时先加载等待页，连上启动地址的主机后再跳转
+    let network_target = network::target(&start_url).filter(|_| pake_config.wait_for_network);
+    let waiting_page_url = network_target
+        .is_some()
+        .then(|| protocol::builtin_url("waiting.html"));
+    let start_urls: Vec<String> = std::iter::once(url.to_string())
+        .chain(pake_config.fallback_urls.iter().cloned())
+        .chain(schedule_urls)
+        .chain(error_page_url.clone())
+        .chain(waiting_page_url.clone())
+        .collect();
+    let allowlist = navigation_allowlist(&pake_config.navigation_allowlist, &start_urls);
+
+    let current_url = CurrentUrl::new(start_url.clone());
+
+    if pake_config.disable_ipc {
+        println!("已禁用 IPC，页面发来的消息都会被忽略");
+    }
     // Handling events of JS -> Rust
-    let handler = move |window: &Window, req: String| {
-        if req == "drag_window" {
-            let _ = window.drag_window();
-        } else if req == "fullscreen" {
-            let is_maximized = window.is_maximized();
-            window.set_maximized(!is_maximized);
-        } else if req.starts_with("open_browser") {
-            let href = req.replace("open_browser:", "");
-            if let Err(e) = webbrowser::open(&href) {
-                eprintln!("警告: 无法打开浏览器: {:?}", e);
+    let handler = {
+        let proxy = proxy.clone();
+        let allowlist = allowlist.clone();
+        let current_url = current_url.clone();
+        let drag_opacity = pake_config.drag_opacity;
+        let confirm_external_open = pake_config.confirm_external_open;
+        let per_host_zoom = pake_config.per_host_zoom;
+        let scoped_gestures = !pake_config.disable_gestures_on.is_empty();
+        let auto_login = pake_config.auto_login.is_some();
+        let trusted_hosts = std::cell::RefCell::new(std::collections::HashSet::new());
+        let data_dir = data_dir.clone();
+        let default_home = url.to_string();
+        let home = std::cell::RefCell::new(
+            PinnedHome::load(&data_dir)
+                .map(|home| home.url)
+                .unwrap_or_else(|| default_home.clone()),
+        );
+        // 页面进入 HTML5 全屏前的窗口状态，退出时用于恢复
+        let html5_restore_state = std::cell::RefCell::new(None);
+        move |window: &Window, req: String| match ipc::parse_ipc_command(&req, ipc_token.as_deref()) {
+            IpcCommand::Html5Fullscreen(enter) => {
+                if enter && window.fullscreen().is_none() {
+                    *html5_restore_state.borrow_mut() = Some((
+                        window.outer_position().ok(),
+                        window.inner_size(),
+                        window.is_maximized(),
+                    ));
+                    window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+                } else if !enter {
+                    if let Some((position, size, maximized)) = html5_restore_state.borrow_mut().take() {
+                        window.set_fullscreen(None);
+                        if maximized {
+                            window.set_maximized(true);
+                        } else {
+                            window.set_inner_size(size);
+                            if let Some(position) = position {
+                                window.set_outer_position(position);
+                            }
+                        }
+                    }
+                }
+            }
+            IpcCommand::UrlChanged(url) => {
+                current_url.set(url);
+                if per_host_zoom {
+                    let _ = proxy.send_event(UserEvent::ApplyHostZoom);
+                }
+                if scoped_gestures {
+                    let _ = proxy.send_event(UserEvent::UpdateGestures);
+                }
+                if auto_login {
+                    let _ = proxy.send_event(UserEvent::AutoLogin);
+                }
+            }
+            IpcCommand::Zoom(action) => {
+                if per_host_zoom {
+                    let _ = proxy.send_event(UserEvent::Zoom(action));
+                }
+            }
+            IpcCommand::ReloadWithQuery { key, value } => {
+                match with_query_param(&current_url.get(), key, value) {
+                    Ok(target) => {
+                        println!("带参数重新加载: {}", target);
+                        let _ = proxy.send_event(UserEvent::Navigate(target));
+                    }
+                    Err(e) => eprintln!("警告: 无法修改查询参数 {}={}: {}", key, value, e),
+                }
+            }
+            IpcCommand::GoHome => {
+                let _ = proxy.send_event(UserEvent::Navigate(home.borrow().clone()));
+            }
+            IpcCommand::SetHome => {
+                let current = current_url.get();
+                if is_allowed(&allowlist, &current) {
+                    println!("设置首页: {}", current);
+                    PinnedHome { url: current.clone() }.save(&data_dir);
+                    *home.borrow_mut() = current;
+                    let _ = proxy.send_event(UserEvent::Toast("Home page updated".to_string()));
+                } else {
+                    eprintln!("警告: 首页不在白名单内: {}", current);
+                    let _ = proxy.send_event(UserEvent::Toast("This page can't be set as home".to_string()));
+                }
+            }
+            IpcCommand::ResetHome => {
+                println!("恢复默认首页: {}", default_home);
+                PinnedHome::clear(&data_dir);
+                *home.borrow_mut() = default_home.clone();
+                let _ = proxy.send_event(UserEvent::Toast("Home page reset".to_string()));
+            }
+            IpcCommand::CurlInfo(info) => {
+                let message = match serde_json::from_str::<curl::CurlRequest>(info) {
+                    Ok(request) => match curl::copy_to_clipboard(&curl::format_curl(&request)) {
+                        Ok(_) => "Copied as curl".to_string(),
+                        Err(e) => {
+                            eprintln!("警告: 无法写入剪贴板: {}", e);
+                            "Copy failed".to_string()
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!("警告: 无效的 curl_info: {:?}", e);
+                        "Copy failed".to_string()
+                    }
+                };
+                let _ = proxy.send_event(UserEvent::Toast(message));
+            }
+            IpcCommand::SaveFile(payload) => {
+                let message = match serde_json::from_str::<download::SaveFileRequest>(payload)
+                    .map_err(|e| format!("{:?}", e))
+                    .and_then(|request| download::save_file(&request))
+                {
+                    Ok(path) => {
+                        println!("已保存文件: {}", path.display());
+                        format!("Saved to {}", path.display())
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 无法保存文件: {}", e);
+                        "Save failed".to_string()
+                    }
+                };
+                let _ = proxy.send_event(UserEvent::Toast(message));
+            }
+            IpcCommand::DragWindow => {
+                if let Some(opacity) = drag_opacity {
+                    window_ext::set_opacity(window, opacity);
+                }
+                let _ = window.drag_window();
+            }
+            IpcCommand::DragEnd => {
+                if drag_opacity.is_some() {
+                    window_ext::set_opacity(window, 1.0);
+                }
             }
+            IpcCommand::Fullscreen => {
+                let is_maximized = window.is_maximized();
+                window.set_maximized(!is_maximized);
+            }
+            IpcCommand::ResetPakeState => {
+                let message = "Reset saved window position, zoom levels, home page and imported settings? Cookies and cache are kept.";
+                if dialog::confirm("Reset app state", message) {
+                    state::reset_all(&data_dir);
+                    *home.borrow_mut() = default_home.clone();
+                    let _ = proxy.send_event(UserEvent::PakeStateReset);
+                }
+            }
+            IpcCommand::ImportSettings => {
+                let path = match dialog::pick_json_file("Import settings") {
+                    Some(path) => path,
+                    None => return,
+                };
+                let message = match settings::import(&path, &data_dir, &packaged_config) {
+                    Ok(files) => {
+                        println!("设置已导入: {}，状态文件: {:?}", path.display(), files);
+                        if let Some(pinned) = PinnedHome::load(&data_dir) {
+                            *home.borrow_mut() = pinned.url;
+                        }
+                        let _ = proxy.send_event(UserEvent::SettingsImported);
+                        "Settings imported. Restart the app to apply them.".to_string()
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 无法导入设置 {}: {}", path.display(), e);
+                        "Import failed".to_string()
+                    }
+                };
+                let _ = proxy.send_event(UserEvent::Toast(message));
+            }
+            IpcCommand::Navigate(input) => match parse_navigation_target(input) {
+                Ok(target) if is_allowed(&allowlist, target.as_str()) => {
+                    let _ = proxy.send_event(UserEvent::Navigate(target.to_string()));
+                }
+                Ok(target) => {
+                    eprintln!("警告: 导航目标不在白名单内: {}", target);
+                    let _ = proxy.send_event(UserEvent::Toast(format!(
+                        "Navigation blocked: {}",
+                        target.host_str().unwrap_or_default()
+                    )));
+                }
+                Err(e) => {
+                    eprintln!("警告: 无效的导航地址 {}: {}", input, e);
+                    let _ = proxy.send_event(UserEvent::Toast(format!("Invalid URL: {}", input)));
+                }
+            },
+            IpcCommand::OpenWindow(input) => match parse_navigation_target(input) {
+                Ok(target) if is_allowed(&allowlist, target.as_str()) => {
+                    let _ = proxy.send_event(UserEvent::OpenWindow(target.to_string()));
+                }
+                Ok(target) => {
+                    eprintln!("警告: 新窗口地址不在白名单内: {}", target);
+                    let _ = proxy.send_event(UserEvent::Toast(format!(
+                        "Navigation blocked: {}",
+                        target.host_str().unwrap_or_default()
+                    )));
+                }
+                Err(e) => eprintln!("警告: 无效的窗口地址 {}: {}", input, e),
+            },
+            IpcCommand::OpenBrowser(href) => {
+                if confirm_external_open {
+                    // 同一域名确认一次后，本次运行期间不再询问
+                    let host = url_host(href);
+                    if !trusted_hosts.borrow().contains(&host) {
+                        let message = format!(
+                            "Open this link in your browser?\n\n{}\n\nYou won't be asked again for {} until the app restarts.",
+                            href, host
+                        );
+                        if !dialog::confirm("Open external link", &message) {
+                            return;
+                        }
+                        trusted_hosts.borrow_mut().insert(host);
+                    }
+                }
+                if let Err(e) = webbrowser::open(href) {
+                    eprintln!("警告: 无法打开浏览器: {:?}", e);
+                }
+            }
+            IpcCommand::Event(event) => {
+                let _ = proxy.send_event(event);
+            }
+            IpcCommand::Rejected(name) => eprintln!("警告: 已拒绝缺少有效令牌的 IPC 命令: {}", name),
+            IpcCommand::Unknown => eprintln!("警告: 未知的 IPC 命令: {}", req),
+        }
+    };
+
+    let approved_navigation = ApprovedNavigation::default();
+    let navigation_handler = {
+        let proxy = proxy.clone();
+        let approved_navigation = approved_navigation.clone();
+        let allowlist = allowlist.clone();
+        let confirm_offsite = pake_config.confirm_offsite_navigation;
+        let rewriter = UrlRewriter::new(&pake_config.url_rewrites);
+        let stripper = if !pake_config.strip_query_params.is_empty() {
+            Some(QueryStripper::new(&pake_config.strip_query_params))
+        } else if pake_config.strip_tracking_params {
+            let defaults: Vec<String> = DEFAULT_TRACKING_PARAMS.iter().map(|p| p.to_string()).collect();
+            Some(QueryStripper::new(&defaults))
+        } else {
+            None
+        };
+        let external_schemes = pake_config.external_schemes.clone();
+        let audit = pake_config.audit_navigation.then(|| {
+            let max_kb = pake_config
+                .audit_max_kb
+                .unwrap_or(audit::DEFAULT_AUDIT_MAX_KB);
+            AuditLog::new(&data_dir, max_kb)
+        });
+        move |url: String| {
+            let (allowed, outcome) = if let Some(target) = rewriter.rewrite(&url) {
+                println!("重写导航: {} -> {}", url, target);
+                let _ = proxy.send_event(UserEvent::Navigate(target));
+                (false, "rewritten")
+            } else if let Some(target) = stripper.as_ref().and_then(|stripper| stripper.strip(&url)) {
+                println!("去掉跟踪参数: {} -> {}", url, target);
+                let _ = proxy.send_event(UserEvent::Navigate(target));
+                (false, "stripped")
+            } else if let Some(link) = external::classify(&external_schemes, &url) {
+                match link {
+                    ExternalLink::Open => {
+                        let _ = proxy.send_event(UserEvent::OpenExternal(url.clone()));
+                        (false, "external")
+                    }
+                    ExternalLink::Blocked(scheme) => {
+                        eprintln!("警告: 已拦截未允许的外部协议 {}: {}", scheme, url);
+                        (false, "blocked")
+                    }
+                }
+            } else if approved_navigation.take(&url) {
+                (true, "confirmed")
+            } else if !is_allowed(&allowlist, &url) {
+                if confirm_offsite {
+                    // 先取消导航，用户确认后再重新发起
+                    let _ = proxy.send_event(UserEvent::ConfirmNavigation(url.clone()));
+                    (false, "pending")
+                } else {
+                    eprintln!("警告: 已拦截白名单外的导航: {}", url);
+                    (false, "blocked")
+                }
+            } else {
+                (true, "allowed")
+            };
+            if let Some(audit) = &audit {
+                audit.record(&url, outcome);
+            }
+            allowed
+        }
+    };
+
+    let protocol_handler = {
+        let assets_dir = protocol::assets_dir();
+        let error_page = pake_config.error_page_path.clone();
+        move |request: &wry::http::Request<Vec<u8>>| {
+            protocol::handle(&assets_dir, error_page.as_deref(), request)
         }
     };
 
     let download_started = {
         let proxy = proxy.clone();
+        let min_free_mb = pake_config
+            .min_free_space_mb
+            .filter(|_| pake_config.refuse_downloads_on_low_space);
         move |uri: String, default_path: &mut PathBuf| {
-            let path = match download_dir() {
-                Some(dir) => dir.join(default_path.display().to_string()).as_path().to_path_buf(),
-                None => {
-                    eprintln!("警告: 无法找到下载目录，使用临时目录");
-                    std::env::temp_dir().join(default_path.display().to_string())
+            if let Some(min_free_mb) = min_free_mb {
+                if let Err(message) = disk::check_free_space(&download::downloads_dir(), min_free_mb) {
+                    eprintln!("警告: 磁盘剩余空间不足，已拒绝下载 {}: {}", uri, message);
+                    let _ = proxy.send_event(UserEvent::Toast("Not enough disk space to download".to_string()));
+                    return false;
                 }
-            };
+            }
+            let path = download::download_path(&default_path.display().to_string());
             *default_path = path.clone();
             let submitted = proxy
                 .send_event(UserEvent::DownloadStarted(uri, path.display().to_string()))
@@ -327,15 +1055,43 @@ fn main_inner() -> wry::Result<()> {
     };
 
     let download_completed = {
-        move |_uri, path, success| {
-            let _ = proxy.send_event(UserEvent::DownloadComplete(path, success));
+        let proxy = proxy.clone();
+        move |uri, path, success| {
+            let _ = proxy.send_event(UserEvent::DownloadComplete(uri, path, success));
         }
     };
 
+    // 只在开启时接管拖放，否则保留 WebView 自带的拖放行为
+    let file_drop_handler = pake_config.forward_file_drops.then(|| {
+        let proxy = proxy.clone();
+        move |window: &Window, event: FileDropEvent| {
+            if let FileDropEvent::Dropped(paths) = event {
+                // 拖放事件不带位置，松开时光标所在处即为放置位置
+                let scale = window.scale_factor();
+                let point = window_ext::cursor_position()
+                    .zip(window.inner_position().ok())
+                    .and_then(|(cursor, inner)| {
+                        file_drop::DropPoint::from_cursor(cursor.to_physical(scale), inner, scale)
+                    });
+                file_drop::spawn_read(paths, point, proxy.clone());
+            }
+            true
+        }
+    });
+
+    // 缓存只在创建 WebView 之前清理，运行中删除会破坏 WebView 正在使用的缓存文件
+    if let Some(max_cache_mb) = pake_config.max_cache_mb {
+        cache::enforce_limit(&cache::webview_cache_dirs(&data_dir), max_cache_mb);
+    }
+
+    // macOS 上偏好设置和内容拦截规则只能在创建 WebView 之后设置，先加载空白页，设置完成后再加载启动地址，
+    // 保证启动页同样生效
+    let defer_first_load = cfg!(target_os = "macos")
+        && (pake_config.content_blocker_rules.is_some() || !pake_config.wkwebview_prefs.is_empty());
+
     #[cfg(target_os = "macos")]
     let webview = {
-        let user_agent_string = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.1 Safari/605.1.15";
-        let url_str = url.to_string();
+        let url_str = waiting_page_url.clone().unwrap_or_else(|| start_url.clone());
         println!("[4/5] 正在加载 URL: {}", url_str);
         let _ = std::io::stdout().flush();
         
@@ -347,26 +1103,38 @@ fn main_inner() -> wry::Result<()> {
                 "URL 为空或无效"
             )));
         }
-        
+
         println!("[4/5] 正在创建 WebView...");
         let _ = std::io::stdout().flush();
         
         let webview_result = WebViewBuilder::new(window)
-            .with_user_agent(user_agent_string)
-            .with_url(&url_str);
+            .with_user_agent(USER_AGENT)
+            .with_url(if defer_first_load { "about:blank" } else { &url_str });
         
         match webview_result {
             Ok(mut builder) => {
                 println!("[4/5] WebView 构建器创建成功，继续配置...");
                 let _ = std::io::stdout().flush();
-                builder
+                builder = builder
                     .with_devtools(cfg!(feature = "devtools"))
-                    .with_initialization_script(include_str!("pake.js"))
-                    .with_ipc_handler(handler)
+                    .with_transparent(pake_config.overlay_mode)
+                    .with_initialization_script(&config_script)
+                    .with_initialization_script(&splash_script)
+                    .with_initialization_script(&pake_script)
+                    .with_navigation_handler(navigation_handler)
+                    .with_custom_protocol(protocol::PROTOCOL_NAME.into(), protocol_handler)
                     .with_back_forward_navigation_gestures(true)
                     .with_download_started_handler(download_started)
-                    .with_download_completed_handler(download_completed)
-                    .build()
+                    .with_download_completed_handler(download_completed);
+                builder = if pake_config.disable_ipc {
+                    builder.with_ipc_handler(|_: &Window, _: String| {})
+                } else {
+                    builder.with_ipc_handler(handler)
+                };
+                if let Some(file_drop_handler) = file_drop_handler {
+                    builder = builder.with_file_drop_handler(file_drop_handler);
+                }
+                builder.build()
             }
             Err(e) => {
                 eprintln!("[错误] 无法创建 WebView 或加载 URL '{}': {:?}", url_str, e);
@@ -376,94 +1144,1184 @@ fn main_inner() -> wry::Result<()> {
         }?
     };
 
+    // 主窗口和新窗口共用，保证数据目录和登录状态一致
+    let mut web_context = WebContext::new(Some(data_dir.clone()));
+
     #[cfg(any(target_os = "linux", target_os = "windows"))]
     let webview = {
-        let home_dir = match home::home_dir() {
-            Some(path1) => {
-                println!("找到用户主目录: {}", path1.display());
-                path1
-            }
-            None => {
-                eprintln!("错误: 无法找到用户主目录");
-                std::process::exit(1);
-            }
-        };
-        #[cfg(target_os = "windows")]
-        let data_dir = home_dir.join("AppData").join("Roaming").join(package_name);
-        #[cfg(target_os = "linux")]
-        let data_dir = home_dir.join(".config").join(package_name);
-        if !data_dir.exists() {
-            println!("创建数据目录: {}", data_dir.display());
-            if let Err(e) = std::fs::create_dir_all(&data_dir) {
-                eprintln!("警告: 无法创建数据目录 {}: {:?}", data_dir.display(), e);
-                // 继续执行，不因为目录创建失败而退出
-            } else {
-                println!("数据目录创建成功");
-            }
-        } else {
-            println!("数据目录已存在: {}", data_dir.display());
-        }
-        let mut web_content = WebContext::new(Some(data_dir));
-        #[cfg(target_os = "windows")]
-        let user_agent_string = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36";
-        #[cfg(target_os = "linux")]
-        let user_agent_string = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36";
-        let url_str = url.to_string();
+        let url_str = waiting_page_url.clone().unwrap_or_else(|| start_url.clone());
         println!("[4/5] 正在加载 URL: {}", url_str);
         let _ = std::io::stdout().flush();
-        WebViewBuilder::new(window)?
-            .with_user_agent(user_agent_string)
+        let mut builder = WebViewBuilder::new(window)?
+            .with_user_agent(USER_AGENT)
             .with_url(&url_str)?
             .with_devtools(cfg!(feature = "devtools"))
-            .with_initialization_script(include_str!("pake.js"))
-            .with_ipc_handler(handler)
-            .with_web_context(&mut web_content)
+            .with_transparent(pake_config.overlay_mode)
+            .with_initialization_script(&config_script)
+            .with_initialization_script(&splash_script)
+            .with_initialization_script(&pake_script)
+            .with_navigation_handler(navigation_handler)
+            .with_custom_protocol(protocol::PROTOCOL_NAME.into(), protocol_handler)
+            .with_web_context(&mut web_context)
             .with_download_started_handler(download_started)
-            .with_download_completed_handler(download_completed)
-            .build()?
+            .with_download_completed_handler(download_completed);
+        builder = if pake_config.disable_ipc {
+            builder.with_ipc_handler(|_: &Window, _: String| {})
+        } else {
+            builder.with_ipc_handler(handler)
+        };
+        if let Some(file_drop_handler) = file_drop_handler {
+            builder = builder.with_file_drop_handler(file_drop_handler);
+        }
+        builder.build()?
     };
+    if let Some(enabled) = pake_config.smooth_scrolling {
+        engine::apply_smooth_scrolling(&webview, enabled);
+    }
+    engine::apply_webkit_settings(&webview, &pake_config.webkit_settings);
+    engine::apply_wkwebview_prefs(&webview, &pake_config.wkwebview_prefs);
+    let deferred_url = defer_first_load
+        .then(|| waiting_page_url.clone().unwrap_or_else(|| start_url.clone()));
+    match &pake_config.content_blocker_rules {
+        Some(rules_path) => engine::install_content_blocker(&webview, rules_path, deferred_url),
+        None => {
+            if let Some(url) = deferred_url {
+                engine::load_url(&webview, &url);
+            }
+        }
+    }
+    if pake_config.disable_autofill {
+        engine::disable_autofill(&webview);
+    }
+    if pake_config.dialog_mode == DialogMode::Native {
+        script_dialog::install(&webview);
+    }
+    if pake_config.lock_zoom {
+        engine::disable_zoom_control(&webview);
+    }
+
     #[cfg(feature = "devtools")]
     {
         webview.open_devtools();
     }
 
-    event_loop.run(move |event, _, control_flow| {
+    // 部分 Linux 窗口管理器启动后不会把键盘焦点交给 WebView，需要主动聚焦
+    webview.window().set_focus();
+
+    if let Some(radius) = pake_config.rounded_corners.filter(|r| *r > 0.0) {
+        window_ext::set_rounded_corners(webview.window(), radius);
+    }
+
+    let titlebar_color = pake_config.titlebar_color.as_deref().and_then(|color| {
+        let rgb = window_ext::parse_hex_color(color);
+        if rgb.is_none() {
+            eprintln!("警告: 无效的标题栏颜色: {}", color);
+        }
+        rgb
+    });
+    if let Some(rgb) = titlebar_color {
+        if cfg!(target_os = "windows") {
+            window_ext::set_titlebar_color(webview.window(), Some(rgb));
+        } else if !pake_config.follow_theme_color {
+            println!("titlebar_color 仅在 Windows 上生效，忽略");
+        }
+    }
+    let mut theme_color = titlebar_color;
+
+    let always_below = pake_config.window_level == WindowLevel::AlwaysBelow;
+    if always_below {
+        window_ext::set_always_below(webview.window());
+    }
+
+    // 窗口先完全透明，等页面首帧绘制后再淡入；页面迟迟没有绘制时也不能让窗口一直不可见
+    let fade_in = (pake_config.fade_in_ms > 0 && window_ext::supports_opacity(webview.window()))
+        .then(|| std::time::Duration::from_millis(pake_config.fade_in_ms));
+    let mut faded_in = fade_in.is_none();
+    if fade_in.is_some() {
+        window_ext::set_opacity(webview.window(), 0.0);
+        let proxy = proxy.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(FADE_IN_TIMEOUT);
+            let _ = proxy.send_event(UserEvent::FadeIn);
+        });
+    }
+
+    // 配置了错误页时也需要监视加载超时，所有地址都失败后显示错误页
+    let mut failover = (!pake_config.fallback_urls.is_empty() || error_page_url.is_some()).then(|| {
+        let load_timeout = pake_config
+            .load_timeout_ms
+            .unwrap_or(failover::DEFAULT_LOAD_TIMEOUT_MS);
+        let cooldown = pake_config
+            .fallback_cooldown_secs
+            .unwrap_or(failover::DEFAULT_COOLDOWN_SECS);
+        let mut failover = Failover::new(
+            start_url.clone(),
+            &pake_config.fallback_urls,
+            std::time::Duration::from_millis(load_timeout),
+            std::time::Duration::from_secs(cooldown),
+            proxy.clone(),
+        );
+        // 等待网络期间不计入加载超时
+        if waiting_page_url.is_none() {
+            failover.watch();
+        }
+        load_failure::watch(&webview, proxy.clone());
+        failover
+    });
+    if let Some((host, port)) = network_target {
+        let timeout = pake_config
+            .network_wait_timeout_secs
+            .unwrap_or(network::DEFAULT_WAIT_SECS);
+        network::spawn_wait(host, port, std::time::Duration::from_secs(timeout), proxy.clone());
+    }
+    let network_wait_url = start_url.clone();
+    // 显示等待页期间页面发来的就绪通知来自等待页本身，不代表启动地址已加载
+    let mut waiting_for_network = waiting_page_url.is_some();
+
+    // 启动后超过 slow_load_ms 页面仍未就绪时，在启动画面上提示
+    let mut page_ready = false;
+    if let Some(slow_load_ms) = pake_config.slow_load_ms {
+        let proxy = proxy.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(slow_load_ms));
+            let _ = proxy.send_event(UserEvent::SlowLoad);
+        });
+    }
+
+    if let Some(interval) = pake_config.monitor_screenshot_interval_secs.filter(|&secs| secs > 0) {
+        println!("已开启定时截图，每 {} 秒保存一次", interval);
+        screenshot::spawn_monitor(std::time::Duration::from_secs(interval), proxy.clone());
+    }
+
+    let print_mode = pake_config.print_mode;
+    let scroll_amount = pake_config.scroll_amount;
+    let initial_zoom = pake_config.initial_zoom.unwrap_or(1.0);
+    let mut host_zoom = pake_config.per_host_zoom.then(|| HostZoom::load(&data_dir));
+    // 按当前地址开关前进后退手势，创建 WebView 时默认开启
+    let gesture_blocklist = UrlPatterns::new(&pake_config.disable_gestures_on);
+    let mut gestures_enabled = true;
+    if !gesture_blocklist.is_empty() {
+        let _ = proxy.send_event(UserEvent::UpdateGestures);
+    }
+    let auto_login = pake_config
+        .auto_login
+        .as_ref()
+        .and_then(auto_login::AutoLoginPage::new);
+    if host_zoom.is_none() && initial_zoom != 1.0 {
+        set_zoom(&webview, initial_zoom);
+    }
+    let title_template = pake_config.title_template.clone();
+    let force_theme = pake_config.force_theme.is_some();
+    let suppress_download_toast = pake_config.suppress_download_toast;
+    let close_to_tray = pake_config.close_to_tray;
+    let close_button_behavior = pake_config.close_button_behavior;
+    let blur_behavior = pake_config.blur_behavior;
+    let mut window_focused = true;
+    let minimize_to_tray = pake_config.minimize_to_tray;
+    let mut tray = if close_to_tray || minimize_to_tray {
+        tray::build_tray(&event_loop)
+    } else {
+        None
+    };
+
+    let mut window_state = pake_config.remember_window_state.then(|| {
+        let mut state = saved_window_state.unwrap_or_default();
+        state.track_geometry(webview.window());
+        state
+    });
+
+    // 关闭前先询问页面是否有未保存内容，等待 pake.js 回复期间再次关闭则直接退出
+    // 禁用 IPC 时页面无法回报检查结果，发起检查只会让第一次关闭没有反应
+    let confirm_beforeunload =
+        pake_config.beforeunload == BeforeUnloadMode::Confirm && !pake_config.disable_ipc;
+    let confirm_quit = pake_config.confirm_quit;
+    let show_on_cursor_monitor = pake_config.show_on_cursor_monitor;
+    let global_hotkey = pake_config
+        .global_hotkey
+        .as_deref()
+        .and_then(|hotkey| hotkey::register(&event_loop, hotkey));
+    let mut close_pending = false;
+
+    let mut console_buffer = ConsoleBuffer::default();
+    let dev_scripts_dir = pake_config.dev_scripts_dir.clone();
+    let mut debug_console: Option<WebView> = None;
+    let mut popups: Vec<popup::Popup> = restored_windows
+        .filter_map(
+            |saved| match popup::restore(&event_loop, &saved, &allowlist, &mut web_context) {
+                Ok(popup) => Some(popup),
+                Err(e) => {
+                    eprintln!("警告: 无法恢复窗口 {}: {:?}", saved.url, e);
+                    None
+                }
+            },
+        )
+        .collect();
+    let restore_windows = pake_config.restore_windows;
+    let mut settings_imported = false;
+    let exported_config = pake_config.clone();
+    let prevent_sleep = pake_config.prevent_sleep;
+    let mut sleep_inhibitor: Option<power::SleepInhibitor> = None;
+    let report_health = pake_config.report_page_state || pake_config.health_port.is_some();
+    let mut health_report = report_health.then(|| {
+        let file_dir = pake_config.report_page_state.then_some(data_dir.as_path());
+        let reporter = health::HealthReporter::new(file_dir);
+        if let Some(port) = pake_config.health_port {
+            if let Err(e) = reporter.serve(port) {
+                eprintln!("警告: 无法在端口 {} 上提供 /health: {:?}", port, e);
+            }
+        }
+        reporter
+    });
+    let mut js_error_log = pake_config
+        .capture_js_errors
+        .then(|| js_errors::JsErrorLog::new(&data_dir, pake_config.save_js_errors));
+    let mut downloads = download::DownloadTracker::new(
+        pake_config
+            .max_tracked_downloads
+            .unwrap_or(download::DEFAULT_MAX_TRACKED),
+        std::time::Duration::from_secs(
+            pake_config
+                .download_max_age_secs
+                .unwrap_or(download::DEFAULT_MAX_AGE_SECS),
+        ),
+    );
+    let mut favicons = favicon::FaviconCache::default();
+    let mut overlay = pake_config
+        .overlay_mode
+        .then(|| overlay::Overlay::new(proxy.clone()));
+    let on_high_memory = pake_config.on_high_memory;
+    if let Some(max_memory_mb) = pake_config.max_memory_mb {
+        let cooldown = pake_config
+            .high_memory_cooldown_secs
+            .unwrap_or(memory::DEFAULT_COOLDOWN_SECS);
+        memory::spawn_monitor(
+            max_memory_mb,
+            std::time::Duration::from_secs(cooldown),
+            proxy.clone(),
+        );
+    }
+    let mut current_favicon = String::new();
+    // 页面第一次查询电源状态后才开始定时检查
+    let mut power_monitor_started = false;
+    let reload_on_wake = pake_config.reload_on_wake;
+    power::watch_wake(webview.window(), proxy.clone());
+
+    event_loop.run(move |event, event_loop_target, control_flow| {
         *control_flow = ControlFlow::Wait;
 
         match event {
             Event::NewEvents(StartCause::Init) => println!("Wry has started!"),
+            Event::LoopDestroyed => {
+                // 导入设置后不再覆盖导入的窗口列表
+                if restore_windows && !settings_imported {
+                    let url = current_url.get();
+                    let zoom = host_zoom
+                        .as_ref()
+                        .and_then(|host_zoom| host_zoom.get(&url_host(&url)))
+                        .unwrap_or(initial_zoom);
+                    let main_window = SavedWindow::capture(webview.window(), url, zoom);
+                    let windows = std::iter::once(main_window)
+                        .chain(popups.iter().map(popup::Popup::saved))
+                        .collect();
+                    WindowSession { windows }.save(&data_dir);
+                }
+                // Linux 上的 systemd-inhibit 子进程不会随应用退出，需要主动释放
+                sleep_inhibitor.take();
+            }
             Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
+                event: WindowEvent::Resized(size),
+                window_id,
                 ..
-            } => *control_flow = ControlFlow::Exit,
-            Event::MenuEvent {
-                menu_id,
-                origin: MenuType::MenuBar,
+            } if window_id == webview.window().id()
+                && minimize_to_tray
+                && tray.is_some()
+                && size.width == 0
+                && size.height == 0 =>
+            {
+                // Windows 最小化时会收到大小为 0 的 Resized 事件；弹出窗口和调试控制台最小化时不隐藏主窗口
+                webview.window().set_visible(false);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_) | WindowEvent::Moved(_),
                 ..
             } => {
-                #[cfg(target_os = "macos")]
-                if menu_id == close_item.clone().id() {
-                    webview.window().set_minimized(true);
+                if let Some(state) = window_state.as_mut() {
+                    state.track_geometry(webview.window());
                 }
-                println!("Clicked on {menu_id:?}");
             }
-            Event::UserEvent(UserEvent::DownloadStarted(uri, temp_dir)) => {
-                println!("Download: {uri}");
-                println!("Will write to: {temp_dir:?}");
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                window_id,
+                ..
+            } if debug_console
+                .as_ref()
+                .map_or(false, |console| console.window().id() == window_id) =>
+            {
+                debug_console = None;
             }
-            Event::UserEvent(UserEvent::DownloadComplete(_, success)) => {
-                println!("Succeeded: {success}");
-                if success {
-                    let _ = webview.evaluate_script("window.pakeToast('Save in downloads folder')");
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                window_id,
+                ..
+            } if popups.iter().any(|popup| popup.webview.window().id() == window_id) => {
+                popups.retain(|popup| popup.webview.window().id() != window_id);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                if close_to_tray && tray.is_some() {
+                    webview.window().set_visible(false);
+                    return;
+                }
+                match close_button_behavior {
+                    CloseButtonBehavior::Hide if cfg!(target_os = "macos") || tray.is_some() => {
+                        window_ext::hide(webview.window());
+                        return;
+                    }
+                    CloseButtonBehavior::Hide | CloseButtonBehavior::Minimize => {
+                        webview.window().set_minimized(true);
+                        return;
+                    }
+                    CloseButtonBehavior::Quit => {}
+                }
+                if confirm_beforeunload && !close_pending {
+                    close_pending = true;
+                    let _ = webview.evaluate_script("window.pakeCheckBeforeUnload()");
+                    return;
+                }
+                exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit, control_flow);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ThemeChanged(theme),
+                ..
+            } => {
+                if let Some(script) = theme_changed_script(theme, force_theme) {
+                    println!("系统主题切换为: {}", theme_name(theme));
+                    let _ = webview.evaluate_script(&script);
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Focused(focused),
+                window_id,
+                ..
+            } if window_id == webview.window().id() => {
+                window_focused = focused;
+                // 只有 Windows 最小化时会收到大小为 0 的 Resized 事件；其他平台最小化时只会失去焦点，
+                // 等最小化动画结束后再检查窗口状态
+                if !focused && minimize_to_tray && tray.is_some() && !cfg!(target_os = "windows") {
+                    let proxy = proxy.clone();
+                    std::thread::spawn(move || {
+                        std::thread::sleep(MINIMIZE_CHECK_DELAY);
+                        let _ = proxy.send_event(UserEvent::CheckMinimized);
+                    });
+                }
+                // Windows 激活窗口时会将其提到前面，需要重新放到底层
+                #[cfg(target_os = "windows")]
+                if focused && always_below {
+                    window_ext::set_always_below(webview.window());
+                }
+                let can_hide = cfg!(target_os = "macos") || tray.is_some();
+                match blur_action(blur_behavior, focused, can_hide) {
+                    Some(BlurAction::Opacity(opacity)) => {
+                        window_ext::set_opacity(webview.window(), opacity);
+                    }
+                    Some(BlurAction::Hide) => window_ext::hide(webview.window()),
+                    Some(BlurAction::Minimize) => webview.window().set_minimized(true),
+                    None => {}
+                }
+                emit_to_page(&webview, "focus_changed", &focused.to_string());
+            }
+            Event::GlobalShortcutEvent(id)
+                if global_hotkey.as_ref().map_or(false, |hotkey| hotkey.id == id) =>
+            {
+                let _ = proxy.send_event(UserEvent::ToggleVisible);
+            }
+            Event::UserEvent(UserEvent::ToggleVisible) => {
+                let window = webview.window();
+                if window.is_visible() && !window.is_minimized() && window_focused {
+                    window_ext::hide(window);
+                } else {
+                    show_window(window, show_on_cursor_monitor);
+                }
+            }
+            Event::TrayEvent {
+                event: TrayEvent::LeftClick,
+                ..
+            } => show_window(webview.window(), show_on_cursor_monitor),
+            Event::MenuEvent {
+                menu_id,
+                origin: MenuType::ContextMenu,
+                ..
+            } => {
+                if let Some(tray) = &tray {
+                    if menu_id == tray.show_id {
+                        show_window(webview.window(), show_on_cursor_monitor);
+                    } else if menu_id == tray.quit_id {
+                        if confirm_beforeunload && !close_pending {
+                            close_pending = true;
+                            let _ = webview.evaluate_script("window.pakeCheckBeforeUnload()");
+                            return;
+                        }
+                        exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit, control_flow);
+                    }
+                }
+            }
+            Event::MenuEvent {
+                menu_id,
+                origin: MenuType::MenuBar,
+                ..
+            } => {
+                #[cfg(target_os = "macos")]
+                if menu_id == close_item.clone().id() {
+                    if minimize_to_tray && tray.is_some() {
+                        webview.window().set_visible(false);
+                    } else {
+                        webview.window().set_minimized(true);
+                    }
+                }
+                #[cfg(target_os = "macos")]
+                if quit_item == Some(menu_id) {
+                    exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit, control_flow);
+                    return;
+                }
+                println!("Clicked on {menu_id:?}");
+            }
+            Event::UserEvent(UserEvent::DownloadStarted(uri, temp_dir)) => {
+                println!("Download: {uri}");
+                println!("Will write to: {temp_dir:?}");
+                downloads.start(uri);
+                if let Some(health_report) = health_report.as_mut() {
+                    health_report.set_active_downloads(downloads.active_count());
+                }
+            }
+            Event::UserEvent(UserEvent::DownloadComplete(uri, path, success)) => {
+                println!("Succeeded: {success}");
+                downloads.complete(&uri);
+                if let Some(health_report) = health_report.as_mut() {
+                    health_report.set_active_downloads(downloads.active_count());
+                }
+                if suppress_download_toast {
+                    // 由页面自行展示下载结果
+                    let detail = serde_json::json!({ "path": path, "success": success });
+                    emit_to_page(&webview, "download_complete", &detail.to_string());
+                } else if success {
+                    let _ = webview.evaluate_script("window.pakeToast('Save in downloads folder')");
                 } else {
                     println!("No output path")
                 }
             }
+            Event::UserEvent(UserEvent::Navigate(url)) => {
+                let _ = webview.evaluate_script(&format!("window.location.href = {};", js_string(&url)));
+            }
+            Event::UserEvent(UserEvent::Toast(msg)) => {
+                let _ = webview.evaluate_script(&format!("window.pakeToast({})", js_string(&msg)));
+            }
+            Event::UserEvent(UserEvent::TogglePip) => {
+                let _ = webview.evaluate_script("window.pakeTogglePip()");
+            }
+            Event::UserEvent(UserEvent::BeforeUnloadResult(block)) => {
+                close_pending = false;
+                if !block
+                    || dialog::confirm(
+                        "Leave page?",
+                        "This page has unsaved changes. Close anyway?",
+                    )
+                {
+                    // 已经确认过离开页面时不再重复询问
+                    exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit && !block, control_flow);
+                }
+            }
+            Event::UserEvent(UserEvent::CheckMinimized) => {
+                if tray.is_some() && window_ext::is_minimized(webview.window()) {
+                    webview.window().set_visible(false);
+                }
+            }
+            Event::UserEvent(UserEvent::QuitRequested) => {
+                exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit, control_flow);
+            }
+            Event::UserEvent(UserEvent::ToggleHighContrast) => {
+                let _ = webview.evaluate_script("window.pakeToggleHighContrast()");
+            }
+            Event::UserEvent(UserEvent::CopyAsCurl) => {
+                let _ = webview.evaluate_script("window.pakeCopyAsCurl()");
+            }
+            Event::UserEvent(UserEvent::ConsoleLog(line)) => {
+                println!("{}", line);
+                if let Some(console) = &debug_console {
+                    debug_console::append(console, &line);
+                }
+                console_buffer.push(line);
+            }
+            Event::UserEvent(UserEvent::ShowDebugConsole) => match &debug_console {
+                Some(console) => console.window().set_focus(),
+                None => match debug_console::open(event_loop_target, &console_buffer) {
+                    Ok(console) => debug_console = Some(console),
+                    Err(e) => eprintln!("警告: 无法打开调试控制台: {:?}", e),
+                },
+            },
+            Event::UserEvent(UserEvent::RunScriptFile(path)) => {
+                let message = match dev_script::read_script(dev_scripts_dir.as_deref(), &path) {
+                    Ok((path, script)) => {
+                        println!("执行脚本: {}", path.display());
+                        match webview.evaluate_script(&script) {
+                            Ok(_) => format!("Ran {}", path.display()),
+                            Err(e) => {
+                                eprintln!("警告: 无法执行脚本 {}: {:?}", path.display(), e);
+                                format!("Failed to run {}", path.display())
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 无法读取脚本: {}", e);
+                        format!("Failed to run {}", path)
+                    }
+                };
+                let _ = webview.evaluate_script(&format!("window.pakeToast({})", js_string(&message)));
+            }
+            Event::UserEvent(UserEvent::FadeIn) => {
+                if let Some(duration) = fade_in.filter(|_| !faded_in) {
+                    faded_in = true;
+                    window_ext::spawn_fade_in(duration, proxy.clone());
+                }
+            }
+            Event::UserEvent(UserEvent::WindowOpacity(opacity)) => {
+                window_ext::set_opacity(webview.window(), opacity);
+            }
+            Event::UserEvent(UserEvent::SetUserAgent(user_agent)) => {
+                // 仅在本次运行期间生效，传空字符串恢复默认 User-Agent
+                let user_agent = if user_agent.is_empty() {
+                    USER_AGENT
+                } else {
+                    user_agent.as_str()
+                };
+                if engine::set_user_agent(&webview, user_agent) {
+                    println!("当前 User-Agent: {}", user_agent);
+                    let _ = webview.evaluate_script("window.location.reload()");
+                }
+            }
+            Event::UserEvent(UserEvent::TitleChanged(page_title)) => {
+                if let Some(template) = &title_template {
+                    let window_title = title::format_title(
+                        template,
+                        &page_title,
+                        &current_url.get(),
+                        &product_name,
+                    );
+                    webview.window().set_title(&window_title);
+                }
+            }
+            Event::UserEvent(UserEvent::OpenWindow(url)) => {
+                let zoom = host_zoom
+                    .as_ref()
+                    .and_then(|host_zoom| host_zoom.get(&url_host(&url)))
+                    .unwrap_or(initial_zoom);
+                match popup::open(event_loop_target, &url, zoom, &allowlist, &mut web_context) {
+                    Ok(popup) => popups.push(popup),
+                    Err(e) => eprintln!("警告: 无法打开新窗口 {}: {:?}", url, e),
+                }
+            }
+            Event::UserEvent(UserEvent::PageReady) => {
+                if waiting_for_network {
+                    return;
+                }
+                page_ready = true;
+                if let Some(failover) = failover.as_mut() {
+                    failover.loaded();
+                }
+            }
+            Event::UserEvent(UserEvent::NetworkReady(reachable)) => {
+                if reachable {
+                    println!("网络已就绪，加载 {}", network_wait_url);
+                } else {
+                    eprintln!("警告: 等待网络超时，仍然加载 {}", network_wait_url);
+                }
+                waiting_for_network = false;
+                if let Some(failover) = failover.as_mut() {
+                    failover.watch();
+                }
+                // 用 replace 替换掉等待页，后退时不会回到等待页
+                let _ = webview.evaluate_script(&format!(
+                    "window.location.replace({});",
+                    js_string(&network_wait_url)
+                ));
+            }
+            Event::UserEvent(UserEvent::SlowLoad) => {
+                if !page_ready {
+                    let _ = webview.evaluate_script("window.pakeShowSlowLoad && window.pakeShowSlowLoad()");
+                }
+            }
+            Event::UserEvent(UserEvent::Reload) => {
+                let _ = webview.evaluate_script(&format!(
+                    "window.location.href = {};",
+                    js_string(&current_url.get())
+                ));
+            }
+            Event::UserEvent(UserEvent::ExportLogs) => {
+                let message = match logs::export(&console_buffer, &data_dir, &webview_version) {
+                    Ok(path) => {
+                        println!("日志已导出: {}", path.display());
+                        format!("Logs saved to {}", path.display())
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 无法导出日志: {}", e);
+                        "Export failed".to_string()
+                    }
+                };
+                let _ = webview.evaluate_script(&format!("window.pakeToast({})", js_string(&message)));
+            }
+            Event::UserEvent(UserEvent::ExportSettings) => {
+                let message = match settings::export(&exported_config, &data_dir) {
+                    Ok(path) => {
+                        println!("设置已导出: {}", path.display());
+                        format!("Settings saved to {}", path.display())
+                    }
+                    Err(e) => {
+                        eprintln!("警告: 无法导出设置: {}", e);
+                        "Export failed".to_string()
+                    }
+                };
+                let _ = webview.evaluate_script(&format!("window.pakeToast({})", js_string(&message)));
+            }
+            Event::UserEvent(UserEvent::SettingsImported) => {
+                // 本次运行不再保存窗口状态和缩放，否则会覆盖导入的内容
+                settings_imported = true;
+                window_state = None;
+                if let Some(host_zoom) = host_zoom.as_mut() {
+                    *host_zoom = HostZoom::load(&data_dir);
+                }
+            }
+            Event::UserEvent(UserEvent::Scroll(direction)) => {
+                let amount = scroll_amount.map_or("null".to_string(), |a| a.to_string());
+                let _ = webview.evaluate_script(&format!(
+                    "window.pakeScroll({}, {})",
+                    js_string(&direction),
+                    amount
+                ));
+            }
+            Event::UserEvent(UserEvent::ApplyHostZoom) => {
+                if let Some(host_zoom) = &host_zoom {
+                    let host = url_host(&current_url.get());
+                    set_zoom(&webview, host_zoom.get(&host).unwrap_or(initial_zoom));
+                }
+            }
+            Event::UserEvent(UserEvent::UpdateGestures) => {
+                let enabled = !gesture_blocklist.is_match(&current_url.get());
+                if gestures_enabled != enabled {
+                    gestures_enabled = enabled;
+                    engine::set_navigation_gestures(&webview, enabled);
+                }
+            }
+            Event::UserEvent(UserEvent::AutoLogin) => {
+                // url_changed 只用来触发检查，是否为登录页以 WebView 的真实地址为准
+                let url = webview.url();
+                if let Some(script) = auto_login.as_ref().and_then(|page| page.script_for(&url)) {
+                    // 脚本中含有密码，只记录地址
+                    println!("自动登录: {}", url);
+                    let _ = webview.evaluate_script(&script);
+                }
+            }
+            Event::UserEvent(UserEvent::Zoom(action)) => {
+                if let Some(host_zoom) = host_zoom.as_mut() {
+                    let host = url_host(&current_url.get());
+                    let current = host_zoom.get(&host).unwrap_or(initial_zoom);
+                    let zoom = match action {
+                        ZoomAction::In => (current + 0.1).min(2.0),
+                        ZoomAction::Out => (current - 0.1).max(0.3),
+                        ZoomAction::Reset => initial_zoom,
+                    };
+                    set_zoom(&webview, zoom);
+                    host_zoom.set(&host, zoom);
+                    host_zoom.save(&data_dir);
+                }
+            }
+            Event::UserEvent(UserEvent::ToggleDecorations) => {
+                // 隐藏标题栏后仍可通过页面中的拖动区域（drag_window）移动窗口
+                let decorated = !webview.window().is_decorated();
+                webview.window().set_decorations(decorated);
+                println!("窗口标题栏: {}", if decorated { "显示" } else { "隐藏" });
+                emit_to_page(&webview, "decorations_changed", &decorated.to_string());
+            }
+            Event::UserEvent(UserEvent::PluginResponse { name, detail }) => {
+                emit_to_page(&webview, &format!("plugin:{}", name), &detail);
+            }
+            Event::UserEvent(UserEvent::Print) => {
+                print::print(&webview, print_mode, proxy.clone());
+            }
+            Event::UserEvent(UserEvent::MonitorScreenshot) => {
+                screenshot::capture(&webview, data_dir.join(screenshot::MONITOR_SCREENSHOT_FILE));
+            }
+            Event::UserEvent(UserEvent::CloseWindow) => {
+                exit_app(&webview, window_state.as_mut(), &data_dir, confirm_quit, control_flow);
+            }
+            Event::UserEvent(event @ (UserEvent::LoadTimeout(_) | UserEvent::LoadFailed(_))) => {
+                if let Some(failover) = failover.as_mut() {
+                    let next = match event {
+                        UserEvent::LoadTimeout(generation) => failover.timeout(generation),
+                        UserEvent::LoadFailed(error) => {
+                            eprintln!("警告: 页面加载失败: {}", error);
+                            failover.failed()
+                        }
+                        _ => None,
+                    };
+                    if let Some(url) = next {
+                        if let Some(health_report) = health_report.as_mut() {
+                            health_report.update(&current_url.get(), health::PageState::Error);
+                        }
+                        let target = match &error_page_url {
+                            Some(error_page_url) if failover.exhausted() => {
+                                eprintln!("警告: 所有地址都无法加载，显示错误页");
+                                error_page_url.clone()
+                            }
+                            _ => url,
+                        };
+                        let _ = webview.evaluate_script(&format!("window.location.href = {};", js_string(&target)));
+                    }
+                }
+            }
+            Event::UserEvent(UserEvent::PageState { state, url }) => {
+                if let Some(health_report) = health_report.as_mut() {
+                    health_report.update(&url, state);
+                }
+            }
+            Event::UserEvent(UserEvent::OverlayRegions(json)) => {
+                if let Some(overlay) = overlay.as_mut() {
+                    match overlay::parse_regions(&json) {
+                        Ok(regions) => overlay.set_regions(webview.window(), regions),
+                        Err(e) => eprintln!("警告: 无法解析可交互区域: {}", e),
+                    }
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            Event::UserEvent(UserEvent::OverlayPoll) => {
+                if let Some(overlay) = overlay.as_mut() {
+                    overlay.update_click_through(webview.window());
+                }
+            }
+            Event::UserEvent(UserEvent::KeepAwake(active)) if prevent_sleep => {
+                if active && sleep_inhibitor.is_none() {
+                    match power::SleepInhibitor::acquire("Media is playing") {
+                        Ok(inhibitor) => {
+                            println!("已阻止系统休眠");
+                            sleep_inhibitor = Some(inhibitor);
+                        }
+                        Err(e) => eprintln!("警告: 无法阻止系统休眠: {}", e),
+                    }
+                } else if !active && sleep_inhibitor.take().is_some() {
+                    println!("已恢复系统休眠");
+                }
+            }
+            Event::UserEvent(UserEvent::JsError(error)) => {
+                if let Some(log) = js_error_log.as_mut() {
+                    log.record(&error, &current_url.get());
+                }
+            }
+            Event::UserEvent(UserEvent::RequestAttention { critical }) => {
+                // 窗口在前台时用户已经能看到，不再打扰
+                if !window_focused {
+                    webview.window().request_user_attention(Some(if critical {
+                        UserAttentionType::Critical
+                    } else {
+                        UserAttentionType::Informational
+                    }));
+                }
+            }
+            Event::UserEvent(UserEvent::RetryLoad) => {
+                if let Some(failover) = failover.as_mut() {
+                    let url = failover.restart();
+                    let _ = webview.evaluate_script(&format!("window.location.href = {};", js_string(&url)));
+                }
+            }
+            Event::UserEvent(UserEvent::RetryPrimary(generation)) => {
+                if let Some(url) = failover.as_mut().and_then(|f| f.retry_primary(generation)) {
+                    let _ = webview.evaluate_script(&format!("window.location.href = {};", js_string(&url)));
+                }
+            }
+            Event::UserEvent(UserEvent::ReportPowerState) => {
+                if !power_monitor_started {
+                    power_monitor_started = true;
+                    power::spawn_monitor(proxy.clone());
+                }
+                let state = serde_json::to_string(&power::read()).unwrap_or_else(|_| "{}".to_string());
+                emit_to_page(&webview, "power_state", &state);
+            }
+            Event::UserEvent(UserEvent::PowerChanged(state)) => {
+                let state = serde_json::to_string(&state).unwrap_or_else(|_| "{}".to_string());
+                emit_to_page(&webview, "power_changed", &state);
+            }
+            Event::UserEvent(UserEvent::HighMemory(used_mb)) => match on_high_memory {
+                HighMemoryAction::None => {}
+                HighMemoryAction::Notify => emit_to_page(&webview, "high_memory", &used_mb.to_string()),
+                HighMemoryAction::Reload => {
+                    println!("内存占用过高，重新加载页面");
+                    let _ = proxy.send_event(UserEvent::Reload);
+                }
+            },
+            Event::UserEvent(UserEvent::OpenExternal(url)) => {
+                println!("交给系统打开: {}", url);
+                if let Err(e) = external::open_uri(&url) {
+                    eprintln!("警告: 无法打开 {}: {}", url, e);
+                    let _ = webview.evaluate_script("window.pakeToast('No app can open this link')");
+                }
+            }
+            Event::UserEvent(UserEvent::FilesDropped(files)) => {
+                emit_to_page(&webview, "files_dropped", &files);
+            }
+            Event::UserEvent(UserEvent::PakeStateReset) => {
+                // 本次运行不再保存窗口状态，否则退出时会重新写入
+                window_state = None;
+                if let Some(host_zoom) = host_zoom.as_mut() {
+                    *host_zoom = HostZoom::default();
+                    set_zoom(&webview, initial_zoom);
+                }
+                let _ = webview.evaluate_script("window.pakeToast('App state reset')");
+            }
+            Event::UserEvent(UserEvent::ThemeColor(color)) => {
+                // 页面没有 theme-color 时退回 titlebar_color，两者都没有时恢复系统颜色
+                let color = window_ext::parse_hex_color(&color).or(titlebar_color);
+                if color != theme_color {
+                    theme_color = color;
+                    window_ext::set_titlebar_color(webview.window(), color);
+                    window_ext::set_background_color(webview.window(), color);
+                }
+            }
+            Event::UserEvent(UserEvent::FaviconChanged(url)) => {
+                if url == current_favicon {
+                    return;
+                }
+                current_favicon = url.clone();
+                match favicons.get(&url) {
+                    Some(image) => apply_favicon(webview.window(), tray.as_mut(), image),
+                    None => favicon::spawn_fetch(url, proxy.clone()),
+                }
+            }
+            Event::UserEvent(UserEvent::FaviconLoaded { url, image }) => {
+                favicons.insert(url.clone(), image);
+                // 下载期间页面可能已经换了图标
+                if url == current_favicon {
+                    apply_favicon(webview.window(), tray.as_mut(), favicons.get(&url).flatten());
+                }
+            }
+            Event::UserEvent(UserEvent::Preloaded { url, ok }) => {
+                if ok {
+                    println!("已预加载: {}", url);
+                } else {
+                    eprintln!("警告: 预加载失败: {}", url);
+                }
+            }
+            Event::UserEvent(UserEvent::ResetZoom) => {
+                set_zoom(&webview, 1.0);
+            }
+            Event::UserEvent(UserEvent::SystemResumed) => {
+                emit_to_page(&webview, "system_resumed", "{}");
+                if reload_on_wake {
+                    let _ = proxy.send_event(UserEvent::Reload);
+                }
+            }
+            Event::UserEvent(UserEvent::ConfirmNavigation(url)) => {
+                let host = url::Url::parse(&url)
+                    .ok()
+                    .and_then(|u| u.host_str().map(String::from))
+                    .unwrap_or_else(|| url.clone());
+                if dialog::confirm("Leave the app?", &format!("Leave the app and go to {}?", host)) {
+                    approved_navigation.approve(&url);
+                    let _ = webview.evaluate_script(&format!("window.location.href = {};", js_string(&url)));
+                } else {
+                    eprintln!("警告: 用户取消了白名单外的导航: {}", url);
+                }
+            }
+            Event::UserEvent(UserEvent::ReportCurrentUrl) => {
+                emit_to_page(&webview, "current_url", &js_string(&current_url.get()));
+            }
             _ => (),
         }
     });
 }
 
+/// 地址中的域名，无法解析时返回空字符串
+fn url_host(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn forced_theme(theme: ForceTheme) -> Theme {
+    match theme {
+        ForceTheme::Light => Theme::Light,
+        ForceTheme::Dark => Theme::Dark,
+    }
+}
+
+/// 转发给页面的主题名称，与 `prefers-color-scheme` 的取值一致
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+/// 系统主题变化时通知页面的脚本；配置了 `force_theme` 时窗口主题固定，不转发
+fn theme_changed_script(theme: Theme, forced: bool) -> Option<String> {
+    (!forced).then(|| page_event_script("theme_changed", &js_string(theme_name(theme))))
+}
+
+/// 在页面中派发 `pake:<name>` 自定义事件，`detail` 为 JS 表达式
+fn emit_to_page(webview: &WebView, name: &str, detail: &str) {
+    let _ = webview.evaluate_script(&page_event_script(name, detail));
+}
+
+/// 窗口焦点变化时按 `blur_behavior` 对窗口做的处理
+#[derive(Debug, PartialEq)]
+enum BlurAction {
+    Opacity(f64),
+    Hide,
+    Minimize,
+}
+
+/// `dim` 失去焦点时变暗、获得焦点时恢复；`hide` 只在失去焦点时隐藏，规则与 `close_button_behavior`
+/// 的 `hide` 相同，没有托盘又不是 macOS 时无处恢复窗口，改为最小化
+fn blur_action(behavior: BlurBehavior, focused: bool, can_hide: bool) -> Option<BlurAction> {
+    match behavior {
+        BlurBehavior::None => None,
+        BlurBehavior::Dim if focused => Some(BlurAction::Opacity(1.0)),
+        BlurBehavior::Dim => Some(BlurAction::Opacity(BLUR_DIM_OPACITY)),
+        BlurBehavior::Hide if focused => None,
+        BlurBehavior::Hide if can_hide => Some(BlurAction::Hide),
+        BlurBehavior::Hide => Some(BlurAction::Minimize),
+    }
+}
+
+/// 在页面上派发 `pake:<name>` 事件的脚本，`detail` 须是 JS 表达式
+fn page_event_script(name: &str, detail: &str) -> String {
+    format!(
+        "window.dispatchEvent(new CustomEvent('pake:{}', {{ detail: {} }}))",
+        name, detail
+    )
+}
+
+/// 设置 WebView 缩放并告知 pake.js，让注入的界面反向缩放保持原大小
+fn set_zoom(webview: &WebView, zoom: f64) {
+    webview.zoom(zoom);
+    emit_to_page(webview, "zoom_changed", &zoom.to_string());
+}
+
+/// 所有退出路径最终都经过这里，保存需要持久化的状态后退出事件循环；`confirm` 为 true 时先弹窗确认，
+/// 用户取消则继续运行
+fn exit_app(
+    webview: &WebView,
+    window_state: Option<&mut WindowState>,
+    data_dir: &Path,
+    confirm: bool,
+    control_flow: &mut ControlFlow,
+) {
+    if !should_exit(confirm, || dialog::confirm("Quit", "Are you sure you want to quit?")) {
+        println!("用户取消了退出");
+        return;
+    }
+    if let Some(state) = window_state {
+        state.track_flags(webview.window());
+        state.save(data_dir);
+    }
+    launch_guard::clear(data_dir);
+    *control_flow = ControlFlow::Exit;
+}
+
+/// 不需要确认时直接退出，否则由 `ask` 弹窗的结果决定
+fn should_exit(confirm: bool, ask: impl FnOnce() -> bool) -> bool {
+    !confirm || ask()
+}
+
+/// `PAKE_DATA_DIR` 环境变量或配置指定的数据目录，相对路径以可执行文件所在目录为基准
+fn custom_data_dir(configured: Option<&Path>) -> Option<PathBuf> {
+    let dir = std::env::var_os("PAKE_DATA_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| configured.map(Path::to_path_buf))?;
+    if dir.is_relative() {
+        Some(
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(|parent| parent.join(&dir)))
+                .unwrap_or(dir),
+        )
+    } else {
+        Some(dir)
+    }
+}
+
+/// 按包名得到的默认数据目录，找不到用户主目录时返回 None
+fn default_data_dir(package_name: &str) -> Option<PathBuf> {
+    let home_dir = home::home_dir()?;
+    #[cfg(target_os = "windows")]
+    let data_dir = home_dir.join("AppData").join("Roaming").join(package_name);
+    #[cfg(target_os = "linux")]
+    let data_dir = home_dir.join(".config").join(package_name);
+    #[cfg(target_os = "macos")]
+    let data_dir = home_dir
+        .join("Library")
+        .join("Application Support")
+        .join(package_name);
+    Some(data_dir)
+}
+
+/// Pake 与 WebView 共用的数据目录，不存在时自动创建
+///
+/// 优先使用环境变量 `PAKE_DATA_DIR`，其次是配置中的 `data_dir`，相对路径以可执行文件所在目录为基准，
+/// 便于放在 U 盘上的便携版；自定义目录不可写时退回默认目录
+fn get_data_dir(package_name: &str, configured: Option<&Path>) -> PathBuf {
+    if let Some(dir) = custom_data_dir(configured) {
+        match std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|_| check_writable(&dir))
+        {
+            Ok(_) => {
+                println!("使用自定义数据目录: {}", dir.display());
+                // WKWebView 的网站数据存储没有公开接口指定任意目录，自定义目录只用于窗口状态、日志等
+                #[cfg(target_os = "macos")]
+                println!("macOS 上网站数据（Cookie、缓存等）仍保存在系统默认位置");
+                return dir;
+            }
+            Err(e) => eprintln!(
+                "警告: 自定义数据目录 {} 不可用: {}，使用默认目录",
+                dir.display(),
+                e
+            ),
+        }
+    }
+    let data_dir = match default_data_dir(package_name) {
+        Some(data_dir) => data_dir,
+        None => {
+            eprintln!("错误: 无法找到用户主目录");
+            std::process::exit(1);
+        }
+    };
+    if !data_dir.exists() {
+        println!("创建数据目录: {}", data_dir.display());
+        if let Err(e) = std::fs::create_dir_all(&data_dir) {
+            eprintln!("警告: 无法创建数据目录 {}: {:?}", data_dir.display(), e);
+            // 继续执行，不因为目录创建失败而退出
+        } else {
+            println!("数据目录创建成功");
+        }
+    } else {
+        println!("数据目录已存在: {}", data_dir.display());
+    }
+    data_dir
+}
+
+/// panic 时写入的崩溃日志，仅 Windows 记录
+fn crash_log_path() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("pake_crash.log"))
+    } else {
+        None
+    }
+}
+
+/// 写入并删除一个临时文件，确认目录可写
+fn check_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(".pake-write-test");
+    std::fs::write(&probe, b"ok").map_err(|e| format!("{}: 不可写 {:?}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 按 `start_monitor` 查找显示器，找不到时返回 None 并改用主显示器
+fn find_start_monitor(
+    event_loop: &EventLoop<UserEvent>,
+    selector: &MonitorSelector,
+) -> Option<MonitorHandle> {
+    let monitors: Vec<MonitorHandle> = event_loop.available_monitors().collect();
+    let names: Vec<Option<String>> = monitors.iter().map(MonitorHandle::name).collect();
+    match selector.find(&names) {
+        Some(index) => {
+            println!("启动显示器: {}", names[index].as_deref().unwrap_or("未命名"));
+            monitors.into_iter().nth(index)
+        }
+        None => {
+            eprintln!(
+                "警告: 未找到显示器 {:?}，使用主显示器。可用显示器: {:?}",
+                selector, names
+            );
+            None
+        }
+    }
+}
+
+/// 按显示器工作区（逻辑像素）换算配置的初始窗口尺寸，无效的尺寸使用默认值，结果限制在最小/最大尺寸内
+fn resolve_window_size(
+    dimensions: (&Option<WindowDimension>, &Option<WindowDimension>),
+    area: LogicalSize<f64>,
+    default: (f64, f64),
+    min: (Option<f64>, Option<f64>),
+    max: (Option<f64>, Option<f64>),
+) -> (f64, f64) {
+    let resolve = |dimension: &Option<WindowDimension>, screen: f64, default: f64| {
+        dimension
+            .as_ref()
+            .and_then(|d| d.resolve(screen))
+            .unwrap_or_else(|| {
+                if let Some(d) = dimension {
+                    eprintln!("警告: 无效的窗口尺寸 {:?}，使用默认值 {}", d, default);
+                }
+                default
+            })
+    };
+    let width = resolve(dimensions.0, area.width, default.0)
+        .max(min.0.unwrap_or(0.0))
+        .min(max.0.unwrap_or(f64::MAX));
+    let height = resolve(dimensions.1, area.height, default.1)
+        .max(min.1.unwrap_or(0.0))
+        .min(max.1.unwrap_or(f64::MAX));
+    (width, height)
+}
+
+/// 从托盘等入口显示窗口，按 `show_on_cursor_monitor` 先移到光标所在的显示器
+fn show_window(window: &Window, on_cursor_monitor: bool) {
+    if on_cursor_monitor {
+        center_on_cursor_monitor(window);
+    }
+    tray::restore_window(window);
+}
+
+/// 把窗口居中到光标所在的显示器，光标不在任何已知显示器上时使用主显示器
+fn center_on_cursor_monitor(window: &Window) {
+    let monitor = window_ext::cursor_position()
+        .and_then(|cursor| {
+            window
+                .available_monitors()
+                .find(|monitor| monitor_contains(monitor, cursor))
+        })
+        .or_else(|| window.primary_monitor());
+    let monitor = match monitor {
+        Some(monitor) => monitor,
+        None => {
+            eprintln!("警告: 无法确定光标所在的显示器，保持窗口位置");
+            return;
+        }
+    };
+    window.set_outer_position(window_ext::work_area(&monitor).centered(window.outer_size()));
+}
+
+fn monitor_contains(monitor: &MonitorHandle, point: Position) -> bool {
+    point_on_monitor(point, monitor.position(), monitor.size(), monitor.scale_factor())
+}
+
+/// 判断屏幕坐标是否落在显示器内，逻辑坐标按该显示器自己的缩放比例换算
+fn point_on_monitor(
+    point: Position,
+    origin: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+) -> bool {
+    let point: PhysicalPosition<f64> = point.to_physical(scale_factor);
+    point.x >= origin.x as f64
+        && point.x < origin.x as f64 + size.width as f64
+        && point.y >= origin.y as f64
+        && point.y < origin.y as f64 + size.height as f64
+}
+
 fn get_windows_config() -> (Option<String>, Option<WindowConfig>) {
     let config_file = include_str!("../tauri.conf.json");
     let config: Config = match serde_json::from_str(config_file) {
@@ -480,6 +2338,55 @@ fn get_windows_config() -> (Option<String>, Option<WindowConfig>) {
     )
 }
 
+/// 查找窗口图标，支持中文名称和英文哈希名称
+#[cfg(target_os = "windows")]
+fn find_icon_path(package_name: &str) -> Option<String> {
+    // 获取可执行文件所在目录，用于查找资源文件
+    let exe_dir = match std::env::current_exe() {
+        Ok(exe_path) => {
+            exe_path.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+        }
+        Err(e) => {
+            println!("警告: 无法获取可执行文件路径: {:?}，使用当前目录", e);
+            std::env::current_dir().unwrap_or_default()
+        }
+    };
+    println!("可执行文件目录: {}", exe_dir.display());
+    
+    // 尝试查找图标文件，支持中文名称和英文哈希名称
+    // 先尝试相对路径（开发环境），再尝试可执行文件目录（安装后）
+    let mut icon_paths = vec![
+        format!("png/{}_32.ico", package_name),
+        exe_dir.join(format!("png/{}_32.ico", package_name)).to_string_lossy().to_string(),
+    ];
+    
+    // 如果 package_name 包含非 ASCII 字符，添加英文哈希名称路径
+    let has_non_ascii = package_name.chars().any(|c| c as u32 > 127);
+    if has_non_ascii {
+        let hash = md5::compute(package_name.as_bytes());
+        let hash_hex = format!("{:x}", hash);
+        let hash_prefix = &hash_hex[..8.min(hash_hex.len())];
+        icon_paths.push(format!("png/app{}_32.ico", hash_prefix));
+        icon_paths.push(exe_dir.join(format!("png/app{}_32.ico", hash_prefix)).to_string_lossy().to_string());
+    }
+    
+    // 添加默认图标路径
+    icon_paths.push("png/icon_32.ico".to_string());
+    icon_paths.push(exe_dir.join("png/icon_32.ico").to_string_lossy().to_string());
+    
+    // 尝试每个路径，找到第一个存在的
+    let mut icon_path = None;
+    for path_str in &icon_paths {
+        let path = std::path::Path::new(path_str);
+        if path.exists() {
+            println!("找到图标文件: {}", path_str);
+            icon_path = Some(path_str.clone());
+            break;
+        }
+    }
+    icon_path
+}
+
 #[cfg(target_os = "windows")]
 fn load_icon(path: &std::path::Path) -> Result<Icon, String> {
     let image = match image::open(path) {
@@ -492,3 +2399,193 @@ fn load_icon(path: &std::path::Path) -> Result<Icon, String> {
     Icon::from_rgba(rgba, width, height)
         .map_err(|e| format!("无法创建图标: {:?}", e))
 }
+
+/// 把 favicon 设置为窗口和托盘图标，没有可用的 favicon 时恢复打包的图标
+fn apply_favicon(
+    window: &Window,
+    tray: Option<&mut tray::Tray>,
+    image: Option<&favicon::FaviconImage>,
+) {
+    let icon = match image.map(favicon::FaviconImage::to_icon) {
+        Some(Ok(icon)) => Ok(icon),
+        Some(Err(e)) => {
+            eprintln!("警告: 无法创建 favicon 图标: {}，使用默认图标", e);
+            tray::bundled_icon()
+        }
+        None => tray::bundled_icon(),
+    };
+    match icon {
+        Ok(icon) => {
+            if let Some(tray) = tray {
+                tray.set_icon(icon.clone());
+            }
+            window.set_window_icon(Some(icon));
+        }
+        Err(e) => eprintln!("警告: 无法加载默认图标: {}", e),
+    }
+}
+
+/// 通过环境变量让 WebView 使用软件渲染
+fn disable_gpu_rendering() {
+    #[cfg(target_os = "linux")]
+    {
+        std::env::set_var("WEBKIT_DISABLE_COMPOSITING_MODE", "1");
+        std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+        println!("已关闭 GPU 加速 (WebKitGTK 合成模式已禁用)");
+    }
+    #[cfg(target_os = "windows")]
+    {
+        append_webview2_args("--disable-gpu --disable-gpu-compositing");
+        println!("已关闭 GPU 加速 (WebView2 --disable-gpu)");
+    }
+    #[cfg(target_os = "macos")]
+    println!("警告: macOS 上 WKWebView 不支持关闭 GPU 加速，忽略 disable_gpu");
+}
+
+/// 追加 WebView2 启动参数，保留用户环境中已有的参数
+#[cfg(target_os = "windows")]
+fn append_webview2_args(args: &str) {
+    const KEY: &str = "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS";
+    let merged = match std::env::var(KEY) {
+        Ok(existing) if !existing.trim().is_empty() => format!("{} {}", existing, args),
+        _ => args.to_string(),
+    };
+    std::env::set_var(KEY, merged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quit_without_confirmation_never_asks() {
+        assert!(should_exit(false, || panic!("不应弹窗")));
+    }
+
+    #[test]
+    fn quit_confirmation_follows_the_answer() {
+        assert!(should_exit(true, || true));
+        assert!(!should_exit(true, || false));
+    }
+
+    #[test]
+    fn theme_changes_are_forwarded_unless_the_theme_is_forced() {
+        assert_eq!(
+            theme_changed_script(Theme::Dark, false).as_deref(),
+            Some(
+                "window.dispatchEvent(new CustomEvent('pake:theme_changed', { detail: \"dark\" }))"
+            )
+        );
+        assert_eq!(
+            theme_changed_script(Theme::Light, false).as_deref(),
+            Some("window.dispatchEvent(new CustomEvent('pake:theme_changed', { detail: \"light\" }))")
+        );
+        assert_eq!(theme_changed_script(Theme::Dark, true), None);
+        assert_eq!(theme_changed_script(Theme::Light, true), None);
+    }
+
+    #[test]
+    fn cursor_monitor_lookup_scales_logical_points_per_monitor() {
+        let size = PhysicalSize::new(2880, 1800);
+        let origin = PhysicalPosition::new(0, 0);
+        let logical =
+            |x: f64, y: f64| Position::Logical(wry::application::dpi::LogicalPosition::new(x, y));
+        assert!(point_on_monitor(logical(1439.0, 899.0), origin, size, 2.0));
+        assert!(!point_on_monitor(logical(1440.0, 10.0), origin, size, 2.0));
+        assert!(point_on_monitor(logical(1440.0, 10.0), origin, size, 1.0));
+    }
+
+    #[test]
+    fn cursor_monitor_lookup_handles_negative_origins_and_far_edges() {
+        let origin = PhysicalPosition::new(-1920, 0);
+        let size = PhysicalSize::new(1920, 1080);
+        let physical = |x: i32, y: i32| Position::Physical(PhysicalPosition::new(x, y));
+        assert!(point_on_monitor(physical(-1920, 0), origin, size, 1.0));
+        assert!(point_on_monitor(physical(-1, 1079), origin, size, 1.0));
+        assert!(!point_on_monitor(physical(0, 500), origin, size, 1.0));
+        assert!(!point_on_monitor(physical(-100, 1080), origin, size, 1.0));
+    }
+
+    #[test]
+    fn blur_behavior_decides_what_happens_on_focus_changes() {
+        for (focused, can_hide) in [(true, true), (false, true), (false, false)] {
+            assert_eq!(blur_action(BlurBehavior::None, focused, can_hide), None);
+        }
+        assert_eq!(
+            blur_action(BlurBehavior::Dim, false, false),
+            Some(BlurAction::Opacity(BLUR_DIM_OPACITY))
+        );
+        assert_eq!(
+            blur_action(BlurBehavior::Dim, true, false),
+            Some(BlurAction::Opacity(1.0))
+        );
+        assert_eq!(
+            blur_action(BlurBehavior::Hide, false, true),
+            Some(BlurAction::Hide)
+        );
+        assert_eq!(
+            blur_action(BlurBehavior::Hide, false, false),
+            Some(BlurAction::Minimize)
+        );
+        assert_eq!(blur_action(BlurBehavior::Hide, true, true), None);
+    }
+
+    #[test]
+    fn decorations_state_is_sent_as_a_boolean_detail() {
+        assert_eq!(
+            page_event_script("decorations_changed", &false.to_string()),
+            "window.dispatchEvent(new CustomEvent('pake:decorations_changed', { detail: false }))"
+        );
+        assert_eq!(
+            page_event_script("decorations_changed", &true.to_string()),
+            "window.dispatchEvent(new CustomEvent('pake:decorations_changed', { detail: true }))"
+        );
+    }
+
+    #[test]
+    fn forced_theme_maps_to_the_window_theme() {
+        let config: config::PakeConfig =
+            serde_json::from_str(r#"{ "force_theme": "dark" }"#).unwrap();
+        assert_eq!(config.force_theme.map(forced_theme), Some(Theme::Dark));
+        assert_eq!(forced_theme(ForceTheme::Light), Theme::Light);
+    }
+
+    fn dimension(value: &str) -> Option<WindowDimension> {
+        Some(WindowDimension::Text(value.to_string()))
+    }
+
+    #[test]
+    fn percent_sizes_resolve_against_the_work_area() {
+        // 1920x1080 的显示器去掉 40 像素高的任务栏
+        let area = LogicalSize::new(1920.0, 1040.0);
+        let size = resolve_window_size(
+            (&dimension("50%"), &dimension("50%")),
+            area,
+            (800.0, 600.0),
+            (None, None),
+            (None, None),
+        );
+        assert_eq!(size, (960.0, 520.0));
+    }
+
+    #[test]
+    fn window_size_falls_back_and_respects_limits() {
+        let area = LogicalSize::new(1000.0, 800.0);
+        let size = resolve_window_size(
+            (&dimension("abc"), &Some(WindowDimension::Pixels(2000.0))),
+            area,
+            (800.0, 600.0),
+            (Some(900.0), None),
+            (None, Some(700.0)),
+        );
+        assert_eq!(size, (900.0, 700.0));
+        let defaults = resolve_window_size(
+            (&None, &None),
+            area,
+            (800.0, 600.0),
+            (None, None),
+            (None, None),
+        );
+        assert_eq!(defaults, (800.0, 600.0));
+    }
+}
@@ -0,0 +1,152 @@
+//! 定期采样应用及其 WebView 子进程的内存占用，超过 `max_memory_mb` 时通知事件循环
+//!
+//! Windows 和 Linux 上 WebView 的渲染进程是本进程的后代进程，会一并统计；macOS 上 WebContent 进程由
+//! launchd 启动，不是本进程的子进程，只能统计应用自身
+use crate::UserEvent;
+use std::collections::HashMap;
+use std::time::Duration;
+use wry::application::event_loop::EventLoopProxy;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+pub const DEFAULT_COOLDOWN_SECS: u64 = 600;
+
+/// 在后台线程定期采样，超过上限时发送 `HighMemory`，之后等待冷却时间再继续检查
+pub fn spawn_monitor(max_mb: u64, cooldown: Duration, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SAMPLE_INTERVAL);
+        let used_mb = match sample_mb() {
+            Some(used_mb) => used_mb,
+            None => continue,
+        };
+        if used_mb <= max_mb {
+            continue;
+        }
+        eprintln!("警告: 内存占用 {} MB，超过上限 {} MB", used_mb, max_mb);
+        if proxy.send_event(UserEvent::HighMemory(used_mb)).is_err() {
+            break;
+        }
+        std::thread::sleep(cooldown);
+    });
+}
+
+/// 本进程及其所有后代进程的常驻内存之和（MB）
+pub fn sample_mb() -> Option<u64> {
+    let processes = list_processes()?;
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for process in &processes {
+        children
+            .entry(process.parent)
+            .or_default()
+            .push(process.pid);
+    }
+    let rss: HashMap<u32, u64> = processes.iter().map(|p| (p.pid, p.rss_bytes)).collect();
+    let mut pending = vec![std::process::id()];
+    let mut total = 0;
+    while let Some(pid) = pending.pop() {
+        total += rss.get(&pid).copied().unwrap_or_default();
+        if let Some(list) = children.get(&pid) {
+            pending.extend(list.iter().filter(|child| **child != pid));
+        }
+    }
+    Some(total / 1024 / 1024)
+}
+
+struct ProcessInfo {
+    pid: u32,
+    parent: u32,
+    rss_bytes: u64,
+}
+
+#[cfg(not(target_os = "windows"))]
+fn list_processes() -> Option<Vec<ProcessInfo>> {
+    // macOS 和 Linux 的 ps 都支持这些列，rss 单位为 KB
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "pid=,ppid=,rss="])
+        .output()
+        .map_err(|e| eprintln!("警告: 无法采样内存占用: {:?}", e))
+        .ok()?;
+    let processes = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().map(str::parse::<u64>);
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(Ok(pid)), Some(Ok(parent)), Some(Ok(rss_kb))) => Some(ProcessInfo {
+                    pid: pid as u32,
+                    parent: parent as u32,
+                    rss_bytes: rss_kb * 1024,
+                }),
+                _ => None,
+            }
+        })
+        .collect();
+    Some(processes)
+}
+
+#[cfg(target_os = "windows")]
+fn list_processes() -> Option<Vec<ProcessInfo>> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)
+            .map_err(|e| eprintln!("警告: 无法采样内存占用: {:?}", e))
+            .ok()?;
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut processes = Vec::new();
+        let mut found = Process32FirstW(snapshot, &mut entry).as_bool();
+        while found {
+            processes.push(ProcessInfo {
+                pid: entry.th32ProcessID,
+                parent: entry.th32ParentProcessID,
+                rss_bytes: 0,
+            });
+            found = Process32NextW(snapshot, &mut entry).as_bool();
+        }
+        CloseHandle(snapshot);
+
+        // 只查询本进程的后代，避免打开所有进程
+        let descendants = descendants_of(&processes, std::process::id());
+        for process in processes.iter_mut() {
+            if !descendants.contains(&process.pid) {
+                continue;
+            }
+            let handle = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process.pid) {
+                Ok(handle) => handle,
+                Err(_) => continue,
+            };
+            let mut counters = PROCESS_MEMORY_COUNTERS::default();
+            let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+            if K32GetProcessMemoryInfo(handle, &mut counters, size).as_bool() {
+                process.rss_bytes = counters.WorkingSetSize as u64;
+            }
+            CloseHandle(handle);
+        }
+        Some(processes)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn descendants_of(processes: &[ProcessInfo], root: u32) -> Vec<u32> {
+    let mut result = vec![root];
+    let mut index = 0;
+    while index < result.len() {
+        let pid = result[index];
+        result.extend(
+            processes
+                .iter()
+                .filter(|p| p.parent == pid && p.pid != pid && !result.contains(&p.pid))
+                .map(|p| p.pid)
+                .collect::<Vec<_>>(),
+        );
+        index += 1;
+    }
+    result
+}
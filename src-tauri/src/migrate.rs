@@ -0,0 +1,195 @@
+//! 应用改名后，把旧包名数据目录中的 Cookie、本地存储和 Pake 状态复制到新的数据目录
+//!
+//! macOS 上 WKWebView 的 Cookie 和本地存储保存在 `~/Library/WebKit/<Bundle ID>` 下，不在数据目录中，
+//! 这里只能迁移 Pake 自己的状态文件
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const MIGRATED_FILE: &str = "pake-migrated.json";
+/// 先复制到数据目录旁的临时目录，全部完成后再移入，避免中途退出留下一半的数据
+const STAGING_SUFFIX: &str = ".pake-migrating";
+
+#[derive(Serialize)]
+struct MigrationRecord<'a> {
+    from: &'a Path,
+    migrated_at: String,
+    skipped_files: usize,
+}
+
+/// 数据目录为空且从未迁移过时，从第一个存在且非空的旧目录复制数据
+///
+/// 无法读取的单个文件会跳过并记录数量；整体失败时清理临时目录，下次启动重试
+pub fn migrate_data_dir(data_dir: &Path, previous_dirs: &[PathBuf]) {
+    if data_dir.join(MIGRATED_FILE).exists() || !is_empty_dir(data_dir) {
+        return;
+    }
+    let source = match previous_dirs
+        .iter()
+        .find(|dir| *dir != data_dir && dir.is_dir() && !is_empty_dir(dir))
+    {
+        Some(source) => source,
+        None => return,
+    };
+    println!(
+        "迁移旧数据目录: {} -> {}",
+        source.display(),
+        data_dir.display()
+    );
+    let mut staging = data_dir.as_os_str().to_owned();
+    staging.push(STAGING_SUFFIX);
+    let staging = PathBuf::from(staging);
+    let _ = std::fs::remove_dir_all(&staging);
+
+    let result = copy_dir(source, &staging).and_then(|skipped| {
+        move_entries(&staging, data_dir)?;
+        Ok(skipped)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    match result {
+        Ok(skipped) => {
+            if skipped > 0 {
+                eprintln!("警告: 迁移时跳过了 {} 个无法读取的文件", skipped);
+            }
+            let record = MigrationRecord {
+                from: source,
+                migrated_at: chrono::Local::now().to_rfc3339(),
+                skipped_files: skipped,
+            };
+            let result = serde_json::to_string(&record)
+                .map_err(std::io::Error::from)
+                .and_then(|json| std::fs::write(data_dir.join(MIGRATED_FILE), json));
+            if let Err(e) = result {
+                eprintln!("警告: 无法写入迁移记录: {:?}", e);
+            }
+            println!("数据迁移完成");
+        }
+        Err(e) => eprintln!("警告: 数据迁移失败，下次启动时重试: {:?}", e),
+    }
+}
+
+fn is_empty_dir(dir: &Path) -> bool {
+    std::fs::read_dir(dir).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// 递归复制目录，返回跳过的文件数；无法创建目标目录时整体失败
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<usize> {
+    std::fs::create_dir_all(to)?;
+    let mut skipped = 0;
+    for entry in std::fs::read_dir(from)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("警告: 无法读取 {}: {:?}", from.display(), e);
+                skipped += 1;
+                continue;
+            }
+        };
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => skipped += copy_dir(&source, &target)?,
+            Ok(file_type) if file_type.is_file() => {
+                if let Err(e) = std::fs::copy(&source, &target) {
+                    eprintln!("警告: 无法复制 {}: {:?}", source.display(), e);
+                    skipped += 1;
+                }
+            }
+            // 符号链接等特殊文件不迁移
+            _ => skipped += 1,
+        }
+    }
+    Ok(skipped)
+}
+
+/// 把临时目录中的条目逐个移入数据目录；中途失败时删除已经移入的条目，
+/// 让数据目录回到空的状态，下次启动可以重新迁移
+fn move_entries(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    let mut moved = Vec::new();
+    let result = std::fs::read_dir(from).and_then(|entries| {
+        for entry in entries {
+            let entry = entry?;
+            let target = to.join(entry.file_name());
+            std::fs::rename(entry.path(), &target)?;
+            moved.push(target);
+        }
+        Ok(())
+    });
+    if result.is_err() {
+        for path in &moved {
+            let _ = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pake-migrate-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrates_a_populated_old_dir_into_an_empty_new_dir() {
+        let root = temp_dir("populated");
+        let old = root.join("old");
+        let new = root.join("new");
+        std::fs::create_dir_all(old.join("EBWebView/Default")).unwrap();
+        std::fs::write(old.join("EBWebView/Default/Cookies"), "cookies").unwrap();
+        std::fs::write(old.join("pake-home.json"), "{}").unwrap();
+        std::fs::create_dir_all(&new).unwrap();
+
+        migrate_data_dir(&new, &[root.join("missing"), old.clone()]);
+        assert_eq!(
+            std::fs::read_to_string(new.join("EBWebView/Default/Cookies")).unwrap(),
+            "cookies"
+        );
+        assert!(new.join("pake-home.json").exists());
+        assert!(new.join(MIGRATED_FILE).exists());
+        // 旧目录保持不变，临时目录已清理
+        assert!(old.join("pake-home.json").exists());
+        assert!(!root.join(format!("new{}", STAGING_SUFFIX)).exists());
+    }
+
+    #[test]
+    fn skips_non_empty_or_already_migrated_dirs() {
+        let root = temp_dir("skip");
+        let old = root.join("old");
+        let new = root.join("new");
+        std::fs::create_dir_all(&old).unwrap();
+        std::fs::write(old.join("pake-home.json"), "{}").unwrap();
+        std::fs::create_dir_all(&new).unwrap();
+        std::fs::write(new.join("existing"), "").unwrap();
+
+        migrate_data_dir(&new, &[old]);
+        assert!(!new.join("pake-home.json").exists());
+        assert!(!new.join(MIGRATED_FILE).exists());
+    }
+
+    #[test]
+    fn failed_move_removes_the_entries_already_moved() {
+        let root = temp_dir("rollback");
+        let from = root.join("from");
+        let to = root.join("to");
+        std::fs::create_dir_all(from.join("b")).unwrap();
+        std::fs::write(from.join("a"), "a").unwrap();
+        std::fs::write(from.join("b/file"), "b").unwrap();
+        // 目标中已有非空的同名目录，移动 b 时失败
+        std::fs::create_dir_all(to.join("b")).unwrap();
+        std::fs::write(to.join("b/keep"), "keep").unwrap();
+
+        assert!(move_entries(&from, &to).is_err());
+        assert!(!to.join("a").exists());
+        assert!(to.join("b/keep").exists());
+    }
+}
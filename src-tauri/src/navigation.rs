@@ -0,0 +1,551 @@
+use crate::config::{RewriteKind, UrlRewrite};
+use regex::Regex;
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// 连续重写次数上限，防止规则之间互相跳转造成死循环
+const MAX_CONSECUTIVE_REWRITES: u32 = 5;
+
+enum Matcher {
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn apply(&self, url: &str, to: &str) -> Option<String> {
+        match self {
+            Matcher::Prefix(prefix) => url
+                .strip_prefix(prefix.as_str())
+                .map(|rest| format!("{}{}", to, rest)),
+            Matcher::Regex(re) => {
+                if re.is_match(url) {
+                    Some(re.replace(url, to).into_owned())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// 按 `url_rewrites` 配置重写顶层导航
+pub struct UrlRewriter {
+    rules: Vec<(Matcher, String)>,
+    consecutive: Cell<u32>,
+}
+
+impl UrlRewriter {
+    pub fn new(rewrites: &[UrlRewrite]) -> Self {
+        let rules = rewrites
+            .iter()
+            .filter_map(|rule| {
+                let matcher = match rule.kind {
+                    RewriteKind::Prefix => Matcher::Prefix(rule.from_pattern.clone()),
+                    RewriteKind::Regex => match Regex::new(&rule.from_pattern) {
+                        Ok(re) => Matcher::Regex(re),
+                        Err(e) => {
+                            eprintln!("警告: 忽略无效的重写规则 {}: {:?}", rule.from_pattern, e);
+                            return None;
+                        }
+                    },
+                };
+                Some((matcher, rule.to.clone()))
+            })
+            .collect();
+        UrlRewriter {
+            rules,
+            consecutive: Cell::new(0),
+        }
+    }
+
+    /// 返回重写后的地址；未命中或连续重写超过上限时返回 None
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        let target = self
+            .rules
+            .iter()
+            .find_map(|(matcher, to)| matcher.apply(url, to))
+            .filter(|target| target != url);
+        match target {
+            Some(target) => {
+                let count = self.consecutive.get() + 1;
+                if count > MAX_CONSECUTIVE_REWRITES {
+                    eprintln!(
+                        "警告: 连续重写超过 {} 次，放行 {}",
+                        MAX_CONSECUTIVE_REWRITES, url
+                    );
+                    self.consecutive.set(0);
+                    return None;
+                }
+                self.consecutive.set(count);
+                Some(target)
+            }
+            None => {
+                self.consecutive.set(0);
+                None
+            }
+        }
+    }
+}
+
+/// 一组匹配完整地址的正则表达式，首尾自动锚定，只匹配子串时需要写 `.*`；无效的表达式在启动时忽略
+pub struct UrlPatterns(Vec<Regex>);
+
+impl UrlPatterns {
+    pub fn new(patterns: &[String]) -> Self {
+        UrlPatterns(
+            patterns
+                .iter()
+                .filter_map(|pattern| match Regex::new(&format!("^(?:{})$", pattern)) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        eprintln!("警告: 忽略无效的地址规则 {}: {:?}", pattern, e);
+                        None
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn is_match(&self, url: &str) -> bool {
+        self.0.iter().any(|re| re.is_match(url))
+    }
+}
+
+/// 未配置 `strip_query_params` 时去掉的常见跟踪参数
+pub const DEFAULT_TRACKING_PARAMS: [&str; 12] = [
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "mc_cid", "mc_eid",
+    "igshid", "yclid", "_hsenc",
+];
+
+/// 从导航地址中去掉跟踪参数，参数名支持 `*` 通配符，不区分大小写
+///
+/// 导航处理器拿不到请求方法，去掉参数后总是以 GET 重新导航；地址带跟踪参数的 POST 表单提交会丢失表单内容
+pub struct QueryStripper(Vec<Regex>);
+
+impl QueryStripper {
+    pub fn new(patterns: &[String]) -> Self {
+        QueryStripper(
+            patterns
+                .iter()
+                .filter_map(|pattern| {
+                    let re = format!("(?i)^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+                    match Regex::new(&re) {
+                        Ok(re) => Some(re),
+                        Err(e) => {
+                            eprintln!("警告: 忽略无效的参数规则 {}: {:?}", pattern, e);
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// 返回去掉匹配参数后的地址；没有参数被去掉时返回 None，避免重复导航。其余参数保持原有顺序和编码
+    pub fn strip(&self, url: &str) -> Option<String> {
+        let mut parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return None;
+        }
+        let query = parsed.query()?;
+        let kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| {
+                let raw_key = pair.split('=').next().unwrap_or_default();
+                let key = url::form_urlencoded::parse(raw_key.as_bytes())
+                    .next()
+                    .map(|(key, _)| key.into_owned())
+                    .unwrap_or_default();
+                !self.0.iter().any(|re| re.is_match(&key))
+            })
+            .collect();
+        if kept.len() == query.split('&').count() {
+            return None;
+        }
+        let kept = kept.join("&");
+        parsed.set_query((!kept.is_empty()).then(|| kept.as_str()));
+        Some(parsed.to_string())
+    }
+}
+
+/// 生成实际生效的导航白名单：配置非空时自动加入启动地址及备用地址的域名
+pub fn navigation_allowlist(configured: &[String], start_urls: &[String]) -> Vec<String> {
+    let mut allowlist = configured.to_vec();
+    if !allowlist.is_empty() {
+        allowlist.extend(start_urls.iter().filter_map(|start_url| {
+            Url::parse(start_url)
+                .ok()
+                .and_then(|url| url.host_str().map(String::from))
+        }));
+    }
+    allowlist
+}
+
+/// 判断地址是否允许导航，白名单为空时不限制
+///
+/// 配置了白名单时，http(s) 地址按域名匹配，本地文件和无法解析的地址一律拒绝；
+/// 应用自己的 `pake:` 页面、`about:` 以及 `mailto:` 等外部协议不受白名单限制，外部协议由 `external_schemes` 决定
+pub fn is_allowed(allowlist: &[String], url: &str) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+    let url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    match url.scheme() {
+        "http" | "https" => {}
+        "file" => return false,
+        _ => return true,
+    }
+    let host = url.host_str().unwrap_or_default();
+    allowlist
+        .iter()
+        .any(|allowed| host == allowed || host.ends_with(&format!(".{}", allowed)))
+}
+
+/// 地址指向本机（localhost、*.localhost 或回环 IP）时返回其 origin，如 `http://localhost:3000`
+pub fn loopback_origin(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let loopback = match url.host()? {
+        url::Host::Domain(domain) => domain == "localhost" || domain.ends_with(".localhost"),
+        url::Host::Ipv4(ip) => ip.is_loopback(),
+        url::Host::Ipv6(ip) => ip.is_loopback(),
+    };
+    loopback.then(|| url.origin().ascii_serialization())
+}
+
+/// 输入是否以 `协议://` 开头，只看开头，路径或查询参数里的 `://`（如跳转参数）不算
+fn has_scheme(input: &str) -> bool {
+    input.split_once("://").map_or(false, |(scheme, _)| {
+        let mut chars = scheme.chars();
+        chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// 解析地址栏输入，不以 `协议://` 开头时视为缺少协议：本机地址补全为 http，其余补全为 https。
+/// 不能先交给 `Url::parse`，`localhost:3000` 会被解析成协议为 localhost 的地址
+pub fn parse_navigation_target(input: &str) -> Result<Url, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("地址为空".to_string());
+    }
+    let url = if has_scheme(input) {
+        Url::parse(input)
+    } else {
+        Url::parse(&format!("https://{}", input)).and_then(|url| {
+            let loopback = match url.host() {
+                Some(url::Host::Domain(domain)) => domain == "localhost",
+                Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+                Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+                None => false,
+            };
+            if loopback {
+                Url::parse(&format!("http://{}", input))
+            } else {
+                Ok(url)
+            }
+        })
+    }
+    .map_err(|e| e.to_string())?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!("不支持的协议: {}", scheme)),
+    }
+}
+
+/// 设置地址中的查询参数，`value` 为空时删除该参数，保留其他参数和锚点
+pub fn with_query_param(url: &str, key: &str, value: &str) -> Result<String, String> {
+    let mut url = Url::parse(url).map_err(|e| e.to_string())?;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != key)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    {
+        let mut query = url.query_pairs_mut();
+        query.clear();
+        query.extend_pairs(pairs);
+        if !value.is_empty() {
+            query.append_pair(key, value);
+        }
+    }
+    // 没有任何参数时去掉多余的 `?`
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+    Ok(url.to_string())
+}
+
+/// 页面当前地址，由 pake.js 在每次导航（包括 SPA 的 pushState）后通过 `url_changed:` 上报
+#[derive(Clone, Default)]
+pub struct CurrentUrl(Arc<Mutex<String>>);
+
+impl CurrentUrl {
+    pub fn new(url: String) -> Self {
+        CurrentUrl(Arc::new(Mutex::new(url)))
+    }
+
+    pub fn get(&self) -> String {
+        self.0.lock().map(|url| url.clone()).unwrap_or_default()
+    }
+
+    pub fn set(&self, url: &str) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = url.to_string();
+        }
+    }
+}
+
+/// 用户确认放行的白名单外地址，只对下一次导航到该地址生效
+#[derive(Clone, Default)]
+pub struct ApprovedNavigation(Arc<Mutex<Option<String>>>);
+
+impl ApprovedNavigation {
+    pub fn approve(&self, url: &str) {
+        if let Ok(mut approved) = self.0.lock() {
+            *approved = Some(url.to_string());
+        }
+    }
+
+    /// 地址已被放行时返回 true，并清除放行记录
+    pub fn take(&self, url: &str) -> bool {
+        match self.0.lock() {
+            Ok(mut approved) if approved.as_deref() == Some(url) => {
+                *approved = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 将字符串转为可安全拼接进脚本的 JS 字符串字面量
+pub fn js_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &[&str]) -> UrlPatterns {
+        UrlPatterns::new(&list.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    fn rewrite(from_pattern: &str, to: &str, kind: RewriteKind) -> UrlRewrite {
+        UrlRewrite {
+            from_pattern: from_pattern.to_string(),
+            to: to.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn url_rewriter_applies_prefix_and_regex_rules() {
+        let rewriter = UrlRewriter::new(&[
+            rewrite(
+                "http://old.example.com/",
+                "https://new.example.com/",
+                RewriteKind::Prefix,
+            ),
+            rewrite(
+                r"^https://example\.com/item/(\d+)$",
+                "https://example.com/items?id=$1",
+                RewriteKind::Regex,
+            ),
+        ]);
+        assert_eq!(
+            rewriter.rewrite("http://old.example.com/a?b=1").as_deref(),
+            Some("https://new.example.com/a?b=1")
+        );
+        assert_eq!(
+            rewriter.rewrite("https://example.com/item/42").as_deref(),
+            Some("https://example.com/items?id=42")
+        );
+        assert_eq!(rewriter.rewrite("https://example.com/other"), None);
+    }
+
+    #[test]
+    fn url_rewriter_skips_invalid_rules_and_identity_rewrites() {
+        let rewriter = UrlRewriter::new(&[
+            rewrite("(", "https://never.example.com/", RewriteKind::Regex),
+            rewrite(
+                "https://example.com/",
+                "https://example.com/",
+                RewriteKind::Prefix,
+            ),
+        ]);
+        assert_eq!(rewriter.rewrite("https://example.com/a"), None);
+    }
+
+    #[test]
+    fn url_rewriter_stops_rewrite_loops() {
+        let rewriter = UrlRewriter::new(&[
+            rewrite(
+                "https://a.example.com/",
+                "https://b.example.com/",
+                RewriteKind::Prefix,
+            ),
+            rewrite(
+                "https://b.example.com/",
+                "https://a.example.com/",
+                RewriteKind::Prefix,
+            ),
+        ]);
+        let mut url = "https://a.example.com/".to_string();
+        for _ in 0..MAX_CONSECUTIVE_REWRITES {
+            url = rewriter.rewrite(&url).expect("上限之内应继续重写");
+        }
+        assert_eq!(rewriter.rewrite(&url), None);
+        // 放行后计数清零，下一次导航重新开始计数
+        assert!(rewriter.rewrite(&url).is_some());
+    }
+
+    #[test]
+    fn allowlist_matches_hosts_and_subdomains_only() {
+        let allowlist = navigation_allowlist(
+            &["example.com".to_string()],
+            &["https://app.test/start".to_string()],
+        );
+        assert!(is_allowed(&allowlist, "https://example.com/a"));
+        assert!(is_allowed(&allowlist, "https://docs.example.com/"));
+        assert!(is_allowed(&allowlist, "https://app.test/other"));
+        assert!(!is_allowed(&allowlist, "https://badexample.com/"));
+        assert!(!is_allowed(&allowlist, "https://example.com.evil.test/"));
+        assert!(is_allowed(&[], "https://anything.test/"));
+        assert!(is_allowed(&[], "file:///etc/passwd"));
+    }
+
+    #[test]
+    fn allowlist_denies_local_files_and_unparsable_urls() {
+        let allowlist = vec!["example.com".to_string()];
+        assert!(!is_allowed(&allowlist, "file:///etc/passwd"));
+        assert!(!is_allowed(
+            &allowlist,
+            "file://example.com/share/report.html"
+        ));
+        assert!(!is_allowed(&allowlist, "not a url"));
+        assert!(!is_allowed(&allowlist, ""));
+        // 这些协议不受白名单限制
+        assert!(is_allowed(&allowlist, "pake://app/__pake/error.html"));
+        assert!(is_allowed(&allowlist, "about:blank"));
+        assert!(is_allowed(&allowlist, "mailto:someone@example.org"));
+    }
+
+    #[test]
+    fn approved_navigation_is_used_once_for_the_same_url() {
+        let approved = ApprovedNavigation::default();
+        assert!(!approved.take("https://offsite.test/"));
+        approved.approve("https://offsite.test/");
+        assert!(!approved.take("https://other.test/"));
+        assert!(approved.clone().take("https://offsite.test/"));
+        assert!(!approved.take("https://offsite.test/"));
+    }
+
+    #[test]
+    fn url_patterns_match_the_full_url() {
+        let login = patterns(&[r"https://example\.com/login.*"]);
+        assert!(login.is_match("https://example.com/login"));
+        assert!(login.is_match("https://example.com/login?next=/"));
+        assert!(!login.is_match("https://evil.test/?x=https://example.com/login"));
+        assert!(!login.is_match("https://example.com/logout"));
+    }
+
+    #[test]
+    fn url_patterns_ignore_invalid_expressions() {
+        let list = patterns(&["(", r"https://maps\.example\.com/.*"]);
+        assert!(!list.is_empty());
+        assert!(list.is_match("https://maps.example.com/place"));
+        assert!(patterns(&["("]).is_empty());
+    }
+
+    fn tracking_stripper() -> QueryStripper {
+        let defaults: Vec<String> = DEFAULT_TRACKING_PARAMS
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        QueryStripper::new(&defaults)
+    }
+
+    #[test]
+    fn query_stripper_removes_tracking_params_and_keeps_the_rest() {
+        let stripper = tracking_stripper();
+        assert_eq!(
+            stripper
+                .strip("https://example.com/p?id=7&utm_source=x&UTM_Medium=y&fbclid=z&q=a%20b#top")
+                .as_deref(),
+            Some("https://example.com/p?id=7&q=a%20b#top")
+        );
+        assert_eq!(
+            stripper.strip("https://example.com/?gclid=1").as_deref(),
+            Some("https://example.com/")
+        );
+    }
+
+    #[test]
+    fn query_stripper_leaves_clean_urls_alone() {
+        let stripper = tracking_stripper();
+        assert_eq!(
+            stripper.strip("https://example.com/p?id=7&utm=1&ref=x"),
+            None
+        );
+        assert_eq!(stripper.strip("https://example.com/p"), None);
+        assert_eq!(
+            stripper.strip("pake://localhost/error.html?utm_source=x"),
+            None
+        );
+    }
+
+    #[test]
+    fn query_stripper_uses_configured_params_only() {
+        let stripper = QueryStripper::new(&["ref".to_string(), "session_*".to_string()]);
+        assert_eq!(
+            stripper
+                .strip("https://example.com/?ref=a&session_id=b&utm_source=c")
+                .as_deref(),
+            Some("https://example.com/?utm_source=c")
+        );
+    }
+
+    #[test]
+    fn navigation_target_adds_a_scheme_when_missing() {
+        let parse = |input| parse_navigation_target(input).map(|url| url.to_string());
+        assert_eq!(
+            parse("example.com/a").as_deref(),
+            Ok("https://example.com/a")
+        );
+        assert_eq!(
+            parse("localhost:3000").as_deref(),
+            Ok("http://localhost:3000/")
+        );
+        assert_eq!(
+            parse(" 127.0.0.1:8080/x ").as_deref(),
+            Ok("http://127.0.0.1:8080/x")
+        );
+        assert_eq!(
+            parse("http://example.com").as_deref(),
+            Ok("http://example.com/")
+        );
+        assert_eq!(
+            parse("example.com/r?to=https://x.test/").as_deref(),
+            Ok("https://example.com/r?to=https://x.test/")
+        );
+        assert_eq!(
+            parse("localhost:3000/cb?next=http://localhost:3000/").as_deref(),
+            Ok("http://localhost:3000/cb?next=http://localhost:3000/")
+        );
+        assert!(parse("").is_err());
+        assert!(parse("ftp://example.com").is_err());
+        assert!(parse("javascript://alert(1)").is_err());
+    }
+}
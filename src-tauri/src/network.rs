@@ -0,0 +1,112 @@
+//! `wait_for_network`：启动时先确认能连上启动地址的主机再加载，适合开机自启时网络还没就绪的自助终端
+use crate::UserEvent;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use url::Url;
+use wry::application::event_loop::EventLoopProxy;
+
+pub const DEFAULT_WAIT_SECS: u64 = 60;
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 需要等待的主机和端口，只处理 http(s) 地址
+pub fn target(url: &str) -> Option<(String, u16)> {
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+}
+
+/// 在后台线程反复尝试 TCP 连接，连上或超时后发送 `NetworkReady`，参数表示是否连上
+pub fn spawn_wait(host: String, port: u16, timeout: Duration, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        println!("等待网络: {}:{}", host, port);
+        let reachable = wait_until_reachable(&host, port, timeout, RETRY_INTERVAL);
+        let _ = proxy.send_event(UserEvent::NetworkReady(reachable));
+    });
+}
+
+/// 每隔 `interval` 尝试连接一次，直到连上或超过 `timeout`
+fn wait_until_reachable(host: &str, port: u16, timeout: Duration, interval: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if is_reachable(host, port) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// 域名解析失败（如 DHCP 尚未完成）同样视为不可达
+fn is_reachable(host: &str, port: u16) -> bool {
+    let addrs = match (host, port).to_socket_addrs() {
+        Ok(addrs) => addrs,
+        Err(_) => return false,
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// 找一个当前没有监听的本地端口
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn target_uses_the_host_and_default_port() {
+        assert_eq!(
+            target("https://example.com/app"),
+            Some(("example.com".to_string(), 443))
+        );
+        assert_eq!(
+            target("http://10.0.0.2:8080/"),
+            Some(("10.0.0.2".to_string(), 8080))
+        );
+        assert_eq!(target("pake://localhost/waiting.html"), None);
+        assert_eq!(target("not a url"), None);
+    }
+
+    #[test]
+    fn waits_for_a_listener_that_binds_later() {
+        let port = free_port();
+        let server = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+            let _ = listener.accept();
+        });
+        assert!(wait_until_reachable(
+            "127.0.0.1",
+            port,
+            Duration::from_secs(10),
+            Duration::from_millis(50)
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn gives_up_after_the_timeout() {
+        let port = free_port();
+        let started = Instant::now();
+        assert!(!wait_until_reachable(
+            "127.0.0.1",
+            port,
+            Duration::from_millis(200),
+            Duration::from_millis(50)
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,176 @@
+//! 悬浮层模式：pake.js 上报页面中可交互的区域，其余透明部分的点击穿透到下面的窗口
+use crate::window_ext;
+use crate::UserEvent;
+use serde::Deserialize;
+use wry::application::{event_loop::EventLoopProxy, window::Window};
+
+#[cfg(not(target_os = "linux"))]
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+#[cfg(not(target_os = "linux"))]
+use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
+use wry::application::dpi::PhysicalPosition;
+
+/// macOS 和 Windows 只能整窗切换是否穿透，有可交互区域时按这个间隔检查光标是否位于区域内
+#[cfg(not(target_os = "linux"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 可交互区域，单位为相对窗口内容区左上角的物理像素
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Region {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Region {
+    #[cfg(any(not(target_os = "linux"), test))]
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+pub fn parse_regions(json: &str) -> Result<Vec<Region>, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+/// 页面最新上报的可交互区域，以及窗口当前是否处于穿透状态
+pub struct Overlay {
+    #[cfg(not(target_os = "linux"))]
+    regions: Vec<Region>,
+    #[cfg(not(target_os = "linux"))]
+    click_through: Option<bool>,
+    /// 每次启动或停止轮询都加一，轮询线程发现编号变化后退出
+    #[cfg(not(target_os = "linux"))]
+    poll_generation: Arc<AtomicU64>,
+    #[cfg(not(target_os = "linux"))]
+    polling: bool,
+    #[cfg(not(target_os = "linux"))]
+    proxy: EventLoopProxy<UserEvent>,
+}
+
+impl Overlay {
+    #[cfg(target_os = "linux")]
+    pub fn new(_proxy: EventLoopProxy<UserEvent>) -> Self {
+        Overlay {}
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn new(proxy: EventLoopProxy<UserEvent>) -> Self {
+        Overlay {
+            regions: Vec::new(),
+            click_through: None,
+            poll_generation: Arc::new(AtomicU64::new(0)),
+            polling: false,
+            proxy,
+        }
+    }
+
+    /// Linux 直接把区域设为窗口的输入区域；其他平台有区域时开始轮询光标，没有区域时停止轮询并整窗穿透
+    pub fn set_regions(&mut self, window: &Window, regions: Vec<Region>) {
+        #[cfg(target_os = "linux")]
+        window_ext::set_input_region(window, &regions);
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.regions = regions;
+            if self.regions.is_empty() {
+                self.stop_poll();
+                self.set_click_through(window, true);
+            } else {
+                self.start_poll();
+            }
+        }
+    }
+
+    /// 光标在可交互区域内时接收点击，否则整窗穿透
+    #[cfg(not(target_os = "linux"))]
+    pub fn update_click_through(&mut self, window: &Window) {
+        let inside = match (window_ext::cursor_position(), window.inner_position()) {
+            (Some(cursor), Ok(origin)) => {
+                let cursor: PhysicalPosition<f64> = cursor.to_physical(window.scale_factor());
+                let (x, y) = (cursor.x - origin.x as f64, cursor.y - origin.y as f64);
+                self.regions.iter().any(|region| region.contains(x, y))
+            }
+            // 拿不到光标位置时保持可点击，避免窗口完全无法操作
+            _ => true,
+        };
+        self.set_click_through(window, !inside);
+    }
+
+    /// 只在状态变化时调用系统接口
+    #[cfg(not(target_os = "linux"))]
+    fn set_click_through(&mut self, window: &Window, ignore: bool) {
+        if self.click_through != Some(ignore) {
+            window_ext::set_click_through(window, ignore);
+            self.click_through = Some(ignore);
+        }
+    }
+
+    /// 在后台线程中定时发送 `OverlayPoll`，直到 `stop_poll`
+    #[cfg(not(target_os = "linux"))]
+    fn start_poll(&mut self) {
+        if self.polling {
+            return;
+        }
+        self.polling = true;
+        let generation = self.poll_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current = self.poll_generation.clone();
+        let proxy = self.proxy.clone();
+        std::thread::spawn(move || {
+            while current.load(Ordering::SeqCst) == generation
+                && proxy.send_event(UserEvent::OverlayPoll).is_ok()
+            {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn stop_poll(&mut self) {
+        if self.polling {
+            self.polling = false;
+            self.poll_generation.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_regions_reported_by_the_page() {
+        let regions =
+            parse_regions(r#"[{ "x": 10, "y": 20, "width": 100, "height": 50 }]"#).unwrap();
+        assert_eq!(
+            regions,
+            vec![Region {
+                x: 10.0,
+                y: 20.0,
+                width: 100.0,
+                height: 50.0
+            }]
+        );
+        assert_eq!(parse_regions("[]").unwrap(), vec![]);
+        assert!(parse_regions(r#"[{ "x": 10 }]"#).is_err());
+    }
+
+    #[test]
+    fn region_contains_its_top_left_but_not_its_far_edges() {
+        let region = Region {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 50.0,
+        };
+        assert!(region.contains(10.0, 20.0));
+        assert!(region.contains(109.9, 69.9));
+        assert!(!region.contains(110.0, 30.0));
+        assert!(!region.contains(50.0, 70.0));
+        assert!(!region.contains(9.9, 30.0));
+    }
+}
@@ -7,6 +7,19 @@
  * @type {Record<KeyboardKey, OnKeyDown>}
  */
 
+/**
+ * 由 Rust 端注入的 pake.json 配置
+ */
+const pakeConfig = window.__PAKE_CONFIG__ || {};
+
+installLocaleOverrides();
+
+/** 注入的提示、工具栏等界面是否从右到左排列 */
+const uiRtl = isRtlUi();
+
+/** WebView 原生缩放比例，由 Rust 在每次缩放后通过 `pake:zoom_changed` 告知 */
+let engineZoom = pakeConfig.initial_zoom || 1;
+
 const metaKeyShortcuts = {
   ArrowUp: () => scrollTo(0, 0),
   ArrowDown: () => scrollTo(0, document.body.scrollHeight),
@@ -16,7 +29,7 @@ const metaKeyShortcuts = {
   "-": () => zoomOut(),
   "=": () => zoomIn(),
   "+": () => zoomIn(),
-  0: () => zoomCommon(() => "100%"),
+  0: () => zoomReset(),
 };
 
 const ctrlKeyShortcuts = {
@@ -28,7 +41,7 @@ const ctrlKeyShortcuts = {
   "-": () => zoomOut(),
   "=": () => zoomIn(),
   "+": () => zoomIn(),
-  0: () => zoomCommon(() => "100%"),
+  0: () => zoomReset(),
 };
 
 window.addEventListener("DOMContentLoaded", (_event) => {
@@ -298,6 +311,15 @@ window.addEventListener("DOMContentLoaded", (_event) => {
     }
   `;
   document.head.append(style);
+  installScrollBehavior();
+  installHighContrast();
+  if (pakeConfig.auto_focus_input) {
+    focusFirstInput();
+  }
+  // 以下拖动窗口、全屏、快捷键和外部链接都需要 Rust 配合
+  if (pakeConfig.disable_ipc) {
+    return;
+  }
   const topDom = document.createElement("div");
   topDom.id = "pack-top-dom";
   document.body.appendChild(topDom);
@@ -308,6 +330,9 @@ window.addEventListener("DOMContentLoaded", (_event) => {
     e && e.preventDefault();
     if (e.buttons === 1 && e.detail !== 2) {
       window.ipc.postMessage("drag_window");
+      if (typeof pakeConfig.drag_opacity === "number") {
+        notifyDragEnd();
+      }
     }
   });
 
@@ -319,6 +344,13 @@ window.addEventListener("DOMContentLoaded", (_event) => {
     window.ipc.postMessage("fullscreen");
   });
 
+  const onFullscreenChange = () => {
+    const isFullscreen = !!(document.fullscreenElement || document.webkitFullscreenElement);
+    window.ipc.postMessage(`html5_fullscreen:${isFullscreen}`);
+  };
+  document.addEventListener("fullscreenchange", onFullscreenChange);
+  document.addEventListener("webkitfullscreenchange", onFullscreenChange);
+
   document.addEventListener("keyup", function (event) {
     const preventDefault = (f) => {
       event.preventDefault();
@@ -335,7 +367,126 @@ window.addEventListener("DOMContentLoaded", (_event) => {
       }
     }
   });
+});
 
+setDefaultZoom();
+showSplash();
+installDialogHandler();
+installReferrerStripping();
+notifyPageReady();
+disableAutofill();
+// 配置了 disable_ipc 时 Rust 忽略页面发来的所有消息，依赖 IPC 的功能都不安装
+if (!pakeConfig.disable_ipc) {
+  installZoomLock();
+  notifyFirstPaint();
+  installConsoleForwarding();
+  installErrorReporting();
+  installKeepAwake();
+  installKeyboardScroll();
+  installUrlTracking();
+  reportPageState();
+  installTitleTracking();
+  installFaviconSync();
+  installThemeColorSync();
+  installFileDropForwarding();
+  installOverlayRegions();
+  preloadUrls();
+
+  // 开启 secure_ipc 时 Rust 会把令牌写入这里；令牌和发送函数只存在于这个块的闭包中，页面脚本无法读取或调用。
+  // 页面脚本仍可以伪造点击和按键触发这些处理函数，所以只对用户真实操作（event.isTrusted）附带令牌
+  {
+    const token = "__PAKE_IPC_TOKEN__";
+    const native = nativeIpcSender();
+    const postSensitive = (command) =>
+      native && token ? native(`secure:${token}:${command}`) : window.ipc.postMessage(command);
+    installLinkHandler(postSensitive);
+    installMiddleClick(postSensitive);
+    installCustomShortcuts(postSensitive);
+    installToolbar(postSensitive);
+    installPrintHandler(postSensitive);
+    installSlowLoadCancel(postSensitive);
+  }
+}
+
+/**
+ * 在页面脚本运行之前取得 WebView 原生的消息发送函数
+ *
+ * `window.ipc.postMessage` 只是 wry 注入的转发函数，每次调用时才去找 `chrome.webview.postMessage`
+ * 或 `webkit.messageHandlers.ipc.postMessage`，页面脚本替换这两个方法就能读到经过它们的令牌；
+ * 这里提前绑定，之后的替换不影响带令牌的消息。找不到时返回 null，敏感命令不附带令牌
+ * @returns {((message: string) => void) | null}
+ */
+function nativeIpcSender() {
+  const webview = window.chrome && window.chrome.webview;
+  if (webview && typeof webview.postMessage === "function") {
+    return webview.postMessage.bind(webview);
+  }
+  const handler = window.webkit && window.webkit.messageHandlers && window.webkit.messageHandlers.ipc;
+  if (handler && typeof handler.postMessage === "function") {
+    return handler.postMessage.bind(handler);
+  }
+  return null;
+}
+
+/**
+ * 配置了 overlay_mode 时让页面背景透明，并上报可交互区域，区域以外的点击穿透到下面的窗口
+ *
+ * 页面可以用 `data-pake-interactive` 标记可交互的元素，没有标记时取 body 下所有可见的直接子元素
+ */
+function installOverlayRegions() {
+  if (!pakeConfig.overlay_mode || window.top !== window) {
+    return;
+  }
+  const REPORT_DELAY_MS = 100;
+  let timer;
+  let lastReport = "";
+  const report = () => {
+    timer = undefined;
+    const marked = document.querySelectorAll("[data-pake-interactive]");
+    const elements = marked.length ? [...marked] : [...document.body.children];
+    // Rust 按物理像素判断，devicePixelRatio 已包含页面缩放
+    const ratio = window.devicePixelRatio;
+    const regions = elements
+      .map((el) => el.getBoundingClientRect())
+      .filter((rect) => rect.width > 0 && rect.height > 0)
+      .map((rect) => ({
+        x: rect.left * ratio,
+        y: rect.top * ratio,
+        width: rect.width * ratio,
+        height: rect.height * ratio,
+      }));
+    const json = JSON.stringify(regions);
+    if (json !== lastReport) {
+      lastReport = json;
+      window.ipc.postMessage(`overlay_regions:${json}`);
+    }
+  };
+  const scheduleReport = () => {
+    if (!timer) {
+      timer = setTimeout(report, REPORT_DELAY_MS);
+    }
+  };
+
+  window.addEventListener("DOMContentLoaded", () => {
+    const style = document.createElement("style");
+    style.innerHTML = "html, body { background: transparent !important; }";
+    document.head.append(style);
+    new MutationObserver(scheduleReport).observe(document.body, {
+      childList: true,
+      subtree: true,
+      attributes: true,
+    });
+    window.addEventListener("resize", scheduleReport);
+    window.addEventListener("scroll", scheduleReport, true);
+    report();
+  });
+}
+
+/**
+ * 所有链接都在当前窗口打开，其他域名的 `target="_blank"` 链接交给系统浏览器
+ * @param {(command: string) => void} postSensitive 附带会话令牌发送敏感命令
+ */
+function installLinkHandler(postSensitive) {
   document.addEventListener("click", (e) => {
     const origin = e.target.closest("a");
     if (origin && origin.href) {
@@ -344,49 +495,1324 @@ window.addEventListener("DOMContentLoaded", (_event) => {
       const hrefUrl = new URL(origin.href)
 
       if (
+        // 配置了 external_schemes 时，其他协议交给导航处理，由白名单决定是否打开
+        (!(pakeConfig.external_schemes || []).length || /^https?:$/.test(hrefUrl.protocol)) &&
         window.location.host !== hrefUrl.host && // 如果 a 标签内链接的域名和当前页面的域名不一致 且
         target === '_blank' // a 标签内链接的 target 属性为 _blank 时
       ) {
         e.preventDefault();
-        window.ipc.postMessage(`open_browser:${origin.href}`);
+        if (e.isTrusted) {
+          postSensitive(`open_browser:${origin.href}`);
+        }
       }
     }
   });
-});
+}
 
-setDefaultZoom();
+/**
+ * 注入界面的书写方向：优先使用 `rtl` 配置，未配置时按 override_locale 判断，默认从左到右
+ */
+function isRtlUi() {
+  if (typeof pakeConfig.rtl === "boolean") {
+    return pakeConfig.rtl;
+  }
+  const language = (pakeConfig.override_locale || "").split(/[-_]/)[0].toLowerCase();
+  return ["ar", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi"].includes(language);
+}
+
+/**
+ * 配置了 override_timezone / override_locale 时替换页面看到的默认时区和语言，需要在页面脚本之前执行
+ *
+ * 时区只替换 Intl 默认值、getTimezoneOffset 和 toLocale*String，getHours() 等方法仍使用系统时区
+ */
+function installLocaleOverrides() {
+  const timeZone = pakeConfig.override_timezone;
+  const locale = pakeConfig.override_locale;
+  if (!timeZone && !locale) {
+    return;
+  }
+  if (timeZone) {
+    try {
+      new Intl.DateTimeFormat("en-US", { timeZone });
+    } catch (e) {
+      console.warn(`Invalid override_timezone: ${timeZone}`);
+      return;
+    }
+  }
+
+  const withDefaults = (locales, options) => [
+    locales === undefined && locale ? locale : locales,
+    timeZone && !(options && options.timeZone) ? { ...options, timeZone } : options,
+  ];
+  const NativeDateTimeFormat = Intl.DateTimeFormat;
+  Intl.DateTimeFormat = function DateTimeFormat(locales, options) {
+    return new NativeDateTimeFormat(...withDefaults(locales, options));
+  };
+  Intl.DateTimeFormat.prototype = NativeDateTimeFormat.prototype;
+  Intl.DateTimeFormat.supportedLocalesOf = NativeDateTimeFormat.supportedLocalesOf;
+  for (const method of ["toLocaleString", "toLocaleDateString", "toLocaleTimeString"]) {
+    const original = Date.prototype[method];
+    Date.prototype[method] = function (locales, options) {
+      return original.call(this, ...withDefaults(locales, options));
+    };
+  }
+
+  if (timeZone) {
+    Date.prototype.getTimezoneOffset = function () {
+      if (isNaN(this.getTime())) {
+        return NaN;
+      }
+      // 把同一时刻在目标时区的墙上时间当作 UTC 解析，差值即为偏移
+      const parts = {};
+      for (const { type, value } of new NativeDateTimeFormat("en-US", {
+        timeZone,
+        hourCycle: "h23",
+        year: "numeric",
+        month: "numeric",
+        day: "numeric",
+        hour: "numeric",
+        minute: "numeric",
+        second: "numeric",
+      }).formatToParts(this)) {
+        parts[type] = Number(value);
+      }
+      const wallClock = Date.UTC(parts.year, parts.month - 1, parts.day, parts.hour, parts.minute, parts.second);
+      return Math.round((Math.floor(this.getTime() / 1000) * 1000 - wallClock) / 60000);
+    };
+  }
+
+  if (locale) {
+    const NativeNumberFormat = Intl.NumberFormat;
+    Intl.NumberFormat = function NumberFormat(locales, options) {
+      return new NativeNumberFormat(locales === undefined ? locale : locales, options);
+    };
+    Intl.NumberFormat.prototype = NativeNumberFormat.prototype;
+    Intl.NumberFormat.supportedLocalesOf = NativeNumberFormat.supportedLocalesOf;
+    Object.defineProperty(Navigator.prototype, "language", { get: () => locale, configurable: true });
+    Object.defineProperty(Navigator.prototype, "languages", { get: () => [locale], configurable: true });
+  }
+}
+
+/**
+ * 按配置接管 alert/confirm/prompt，kiosk 模式下避免对话框阻塞页面
+ */
+function installDialogHandler() {
+  const mode = pakeConfig.dialog_mode;
+  if (mode === "accept") {
+    window.alert = () => {};
+    window.confirm = () => true;
+    window.prompt = (_message, defaultValue) => defaultValue ?? "";
+  } else if (mode === "dismiss") {
+    window.alert = () => {};
+    window.confirm = () => false;
+    window.prompt = () => null;
+  }
+}
+
+/**
+ * 按配置覆盖滚动行为：smooth_scrolling 控制 scroll-behavior，scroll_multiplier 调整滚轮灵敏度
+ */
+function installScrollBehavior() {
+  if (typeof pakeConfig.smooth_scrolling === "boolean") {
+    const style = document.createElement("style");
+    const behavior = pakeConfig.smooth_scrolling ? "smooth" : "auto";
+    style.innerHTML = `html, body { scroll-behavior: ${behavior} !important; }`;
+    document.head.append(style);
+  }
+
+  const multiplier = pakeConfig.scroll_multiplier;
+  if (typeof multiplier !== "number" || multiplier <= 0 || multiplier === 1) {
+    return;
+  }
+  window.addEventListener(
+    "wheel",
+    (event) => {
+      // 按住 Ctrl 时是缩放手势，交给页面或 WebView 处理
+      if (event.ctrlKey || event.defaultPrevented) {
+        return;
+      }
+      const target = findScrollable(event.target) || document.scrollingElement;
+      if (!target) {
+        return;
+      }
+      event.preventDefault();
+      target.scrollBy({
+        left: event.deltaX * multiplier,
+        top: event.deltaY * multiplier,
+        behavior: "auto",
+      });
+    },
+    { passive: false }
+  );
+}
+
+/**
+ * 从事件目标向上查找第一个可滚动的元素
+ * @param {Element} el
+ */
+function findScrollable(el) {
+  while (el && el !== document.body && el !== document.documentElement) {
+    const { overflowY, overflowX } = getComputedStyle(el);
+    const canScrollY = /auto|scroll/.test(overflowY) && el.scrollHeight > el.clientHeight;
+    const canScrollX = /auto|scroll/.test(overflowX) && el.scrollWidth > el.clientWidth;
+    if (canScrollY || canScrollX) {
+      return el;
+    }
+    el = el.parentElement;
+  }
+  return null;
+}
+
+/**
+ * 注册 pake.json 中 shortcuts 配置的快捷键，按下时发送对应的 IPC 命令
+ * @param {(command: string) => void} postSensitive 附带会话令牌发送命令，页面伪造的按键不处理
+ */
+function installCustomShortcuts(postSensitive) {
+  const shortcuts = Object.entries(pakeConfig.shortcuts || {});
+  if (shortcuts.length === 0) {
+    return;
+  }
+  window.addEventListener("keydown", (event) => {
+    for (const [command, accelerator] of shortcuts) {
+      if (matchAccelerator(accelerator, event)) {
+        event.preventDefault();
+        if (event.isTrusted) {
+          postSensitive(command);
+        }
+        return;
+      }
+    }
+  });
+}
+
+/**
+ * 需要时接管 window.print()，由 Rust 按 print_mode 统一处理：打印为 PDF 时各平台都接管，
+ * 弹出对话框时只有 WKWebView 的原生实现不起作用需要接管，其他平台保留页面原有的打印行为
+ *
+ * 页面通常在点击处理函数中调用 window.print()，拿不到事件本身，所以只在用户真实的点击或按键
+ * 之后不久附带令牌，避免页面脚本在用户不知情时把 PDF 写入下载目录
+ * @param {(command: string) => void} postSensitive
+ */
+function installPrintHandler(postSensitive) {
+  const isMac = /macintosh|mac os x/i.test(navigator.userAgent);
+  if (pakeConfig.print_mode !== "pdf" && !isMac) {
+    return;
+  }
+  const USER_ACTION_WINDOW_MS = 1000;
+  let lastUserAction = -Infinity;
+  const track = (event) => {
+    if (event.isTrusted) {
+      lastUserAction = Date.now();
+    }
+  };
+  window.addEventListener("click", track, true);
+  window.addEventListener("keydown", track, true);
+  window.__pakeNativePrint = window.print.bind(window);
+  window.print = () => {
+    if (Date.now() - lastUserAction <= USER_ACTION_WINDOW_MS) {
+      postSensitive("print");
+    } else {
+      window.ipc.postMessage("print");
+    }
+  };
+}
+
+/**
+ * 加载过慢提示中的“取消”按钮会关闭应用，由这里在用户真实点击时附带令牌发送 `close_window`
+ * @param {(command: string) => void} postSensitive
+ */
+function installSlowLoadCancel(postSensitive) {
+  document.addEventListener(
+    "click",
+    (event) => {
+      const button = event.target.closest && event.target.closest("#pake-slow-load [data-pake-cancel]");
+      if (button && event.isTrusted) {
+        postSensitive("close_window");
+      }
+    },
+    true,
+  );
+}
+
+/**
+ * 配置了 keyboard_scroll 时，PageDown/PageUp/Home/End 交给 Rust 的滚动命令处理，输入框内不拦截
+ */
+function installKeyboardScroll() {
+  if (!pakeConfig.keyboard_scroll) {
+    return;
+  }
+  const commands = {
+    PageDown: "scroll_down",
+    PageUp: "scroll_up",
+    Home: "scroll_top",
+    End: "scroll_bottom",
+  };
+  window.addEventListener("keydown", (event) => {
+    const command = commands[event.key];
+    const target = event.target;
+    const editable = target.isContentEditable || ["INPUT", "TEXTAREA", "SELECT"].includes(target.tagName);
+    if (!command || editable || event.ctrlKey || event.metaKey || event.altKey) {
+      return;
+    }
+    event.preventDefault();
+    window.ipc.postMessage(command);
+  });
+}
+
+/**
+ * 配置了 show_toolbar 时显示悬浮工具栏，按钮调用已有的 IPC 命令或快捷键对应的函数
+ *
+ * 工具栏平时收起，鼠标移到窗口底部边缘时展开，离开后延迟收起
+ * @param {(command: string) => void} postSensitive 附带会话令牌发送命令，页面伪造的点击不处理
+ */
+function installToolbar(postSensitive) {
+  if (!pakeConfig.show_toolbar || window.top !== window) {
+    return;
+  }
+  const actions = {
+    back: [uiRtl ? "→" : "←", "Back", () => window.history.back()],
+    forward: [uiRtl ? "←" : "→", "Forward", () => window.history.forward()],
+    home: ["⌂", "Home", () => window.ipc.postMessage("go_home")],
+    set_home: ["★", "Set as home", () => postSensitive("set_home")],
+    reload: ["↻", "Reload", () => window.ipc.postMessage("reload")],
+    zoom_in: ["+", "Zoom in", zoomIn],
+    zoom_out: ["−", "Zoom out", zoomOut],
+    zoom_reset: ["1:1", "Actual size", zoomReset],
+    print: ["⎙", "Print", () => window.print()],
+  };
+  const names = pakeConfig.toolbar_buttons?.length
+    ? pakeConfig.toolbar_buttons
+    : ["back", "forward", "home", "reload", "zoom_out", "zoom_in"];
+  const HIDE_DELAY_MS = 800;
+
+  window.addEventListener("DOMContentLoaded", () => {
+    const toolbar = document.createElement("div");
+    toolbar.id = "pake-toolbar";
+    toolbar.dir = uiRtl ? "rtl" : "ltr";
+    toolbar.style.cssText =
+      "position:fixed;left:50%;bottom:8px;z-index:2147483646;display:flex;gap:4px;padding:4px 8px;border-radius:8px;background:rgba(0,0,0,.75);transform:translate(-50%,calc(100% + 16px));transition:transform .2s ease-out;zoom:var(--pake-ui-scale, 1);";
+    for (const name of names) {
+      const action = actions[name];
+      if (!action) {
+        console.warn(`Unknown toolbar button: ${name}`);
+        continue;
+      }
+      const [label, title, run] = action;
+      const button = document.createElement("button");
+      button.textContent = label;
+      button.title = title;
+      button.style.cssText =
+        "min-width:32px;height:28px;border:0;border-radius:4px;background:transparent;color:#fff;font:14px -apple-system,BlinkMacSystemFont,sans-serif;cursor:pointer;";
+      button.onclick = (event) => event.isTrusted && run();
+      toolbar.appendChild(button);
+    }
+
+    // 底部边缘的感应区，鼠标进入时展开工具栏
+    const hotZone = document.createElement("div");
+    hotZone.style.cssText = "position:fixed;left:0;right:0;bottom:0;height:6px;z-index:2147483645;";
+
+    let hideTimer;
+    const show = () => {
+      clearTimeout(hideTimer);
+      toolbar.style.transform = "translate(-50%,0)";
+    };
+    const hide = () => {
+      hideTimer = setTimeout(() => {
+        toolbar.style.transform = "translate(-50%,calc(100% + 16px))";
+      }, HIDE_DELAY_MS);
+    };
+    hotZone.addEventListener("mouseenter", show);
+    hotZone.addEventListener("mouseleave", hide);
+    toolbar.addEventListener("mouseenter", show);
+    toolbar.addEventListener("mouseleave", hide);
+    document.body.append(hotZone, toolbar);
+  });
+}
+
+/**
+ * 由 Rust 调用滚动页面；文档本身不能滚动时，滚动焦点所在或面积最大的可滚动容器
+ * @param {"down" | "up" | "top" | "bottom"} direction
+ * @param {number | null} amount 翻页距离（像素），为空时取可视高度的 90%
+ */
+function pakeScroll(direction, amount) {
+  const canScroll = (el) => {
+    if (el.scrollHeight <= el.clientHeight) {
+      return false;
+    }
+    if (el === document.scrollingElement) {
+      return true;
+    }
+    return ["auto", "scroll", "overlay"].includes(getComputedStyle(el).overflowY);
+  };
+  let target = document.scrollingElement || document.documentElement;
+  if (!canScroll(target)) {
+    let el = document.activeElement;
+    while (el && el !== document.body && !canScroll(el)) {
+      el = el.parentElement;
+    }
+    if (el && el !== document.body) {
+      target = el;
+    } else {
+      let largest = 0;
+      for (const candidate of document.querySelectorAll("*")) {
+        const area = candidate.clientWidth * candidate.clientHeight;
+        if (area > largest && canScroll(candidate)) {
+          largest = area;
+          target = candidate;
+        }
+      }
+    }
+  }
+  const page = amount || target.clientHeight * 0.9;
+  const options = { behavior: "auto" };
+  if (direction === "down") {
+    target.scrollBy({ top: page, ...options });
+  } else if (direction === "up") {
+    target.scrollBy({ top: -page, ...options });
+  } else if (direction === "top") {
+    target.scrollTo({ top: 0, ...options });
+  } else if (direction === "bottom") {
+    target.scrollTo({ top: target.scrollHeight, ...options });
+  }
+}
+
+/**
+ * @param {string} accelerator 形如 `CmdOrCtrl+Shift+P` 的快捷键
+ * @param {KeyboardEvent} event
+ */
+function matchAccelerator(accelerator, event) {
+  const parts = accelerator.split("+").map((part) => part.trim().toLowerCase());
+  const key = parts.pop();
+  const isMac = /macintosh|mac os x/i.test(navigator.userAgent);
+  const want = { ctrl: false, meta: false, alt: false, shift: false };
+  for (const mod of parts) {
+    if (mod === "cmdorctrl" || mod === "commandorcontrol") {
+      want[isMac ? "meta" : "ctrl"] = true;
+    } else if (mod === "ctrl" || mod === "control") {
+      want.ctrl = true;
+    } else if (["cmd", "command", "meta", "super"].includes(mod)) {
+      want.meta = true;
+    } else if (mod === "alt" || mod === "option") {
+      want.alt = true;
+    } else if (mod === "shift") {
+      want.shift = true;
+    }
+  }
+  return (
+    event.ctrlKey === want.ctrl &&
+    event.metaKey === want.meta &&
+    event.altKey === want.alt &&
+    event.shiftKey === want.shift &&
+    event.key.toLowerCase() === key
+  );
+}
+
+/**
+ * 切换画中画：优先选择正在播放的视频，否则选择面积最大的视频
+ */
+function pakeTogglePip() {
+  if (document.pictureInPictureElement) {
+    document.exitPictureInPicture();
+    return;
+  }
+  const videos = Array.from(document.querySelectorAll("video"));
+  if (videos.length === 0) {
+    pakeToast("No video found");
+    return;
+  }
+  const playing = videos.find((video) => !video.paused && !video.ended);
+  const target =
+    playing ||
+    videos.reduce((a, b) =>
+      a.clientWidth * a.clientHeight >= b.clientWidth * b.clientHeight ? a : b
+    );
+  if (document.pictureInPictureEnabled && target.requestPictureInPicture) {
+    target.requestPictureInPicture().catch(() => pakeToast("Picture-in-picture failed"));
+  } else if (target.webkitSupportsPresentationMode && target.webkitSupportsPresentationMode("picture-in-picture")) {
+    // WKWebView 使用 WebKit 私有的 presentation mode API
+    const mode = target.webkitPresentationMode === "picture-in-picture" ? "inline" : "picture-in-picture";
+    target.webkitSetPresentationMode(mode);
+  } else {
+    pakeToast("Picture-in-picture is not supported");
+  }
+}
+
+/**
+ * 每次地址变化（包括 SPA 的 pushState/replaceState）时通过 IPC 上报给 Rust
+ */
+function installUrlTracking() {
+  let lastUrl = "";
+  const report = () => {
+    if (window.location.href !== lastUrl) {
+      lastUrl = window.location.href;
+      window.ipc.postMessage(`url_changed:${lastUrl}`);
+    }
+  };
+  for (const method of ["pushState", "replaceState"]) {
+    const original = history[method];
+    history[method] = function (...args) {
+      const result = original.apply(this, args);
+      report();
+      return result;
+    };
+  }
+  window.addEventListener("popstate", report);
+  window.addEventListener("hashchange", report);
+  window.addEventListener("DOMContentLoaded", report);
+}
+
+/**
+ * 配置了 report_page_state 或 health_port 时上报页面加载状态：脚本注入时为 loading，load 后为 ready，Pake 错误页为 error；
+ * 配置了 health_port 时 Rust 会把注入的 report_page_state 设为 true
+ */
+function reportPageState() {
+  if (!pakeConfig.report_page_state || window.top !== window) {
+    return;
+  }
+  const report = (state) => window.ipc.postMessage(`page_state:${state}:${window.location.href}`);
+  if (window.location.pathname === "/__pake/error.html") {
+    report("error");
+    return;
+  }
+  report("loading");
+  window.addEventListener("load", () => report("ready"), { once: true });
+}
+
+/**
+ * 配置了 title_template 时，页面标题变化后上报给 Rust 更新窗口标题
+ */
+function installTitleTracking() {
+  if (!pakeConfig.title_template || window.top !== window) {
+    return;
+  }
+  let lastTitle = null;
+  const report = () => {
+    if (document.title !== lastTitle) {
+      lastTitle = document.title;
+      window.ipc.postMessage(`title_changed:${lastTitle}`);
+    }
+  };
+  window.addEventListener("DOMContentLoaded", () => {
+    report();
+    new MutationObserver(report).observe(document.head || document.documentElement, {
+      subtree: true,
+      childList: true,
+      characterData: true,
+    });
+  });
+}
+
+/**
+ * 配置了 sync_favicon 时，把当前 favicon 的地址上报给 Rust 作为窗口图标
+ */
+function installFaviconSync() {
+  if (!pakeConfig.sync_favicon || window.top !== window) {
+    return;
+  }
+  let lastHref = null;
+  const report = () => {
+    const link = document.querySelector('link[rel~="icon"][href]');
+    const href = link ? link.href : `${window.location.origin}/favicon.ico`;
+    if (href !== lastHref && /^https?:/.test(href)) {
+      lastHref = href;
+      window.ipc.postMessage(`favicon:${href}`);
+    }
+  };
+  window.addEventListener("DOMContentLoaded", () => {
+    report();
+    new MutationObserver(report).observe(document.head || document.documentElement, {
+      subtree: true,
+      childList: true,
+      attributes: true,
+      attributeFilter: ["href", "rel"],
+    });
+  });
+}
+
+/**
+ * 把任意 CSS 颜色转换为 `#rrggbb`，无效或完全透明的颜色返回空字符串
+ *
+ * 在不插入文档的 canvas 上画一个像素再读回，不会触发页面的 MutationObserver，
+ * 也能处理 `color(srgb …)`、`transparent` 等写法
+ * @param {string} value
+ */
+function cssColorToHex(value) {
+  const canvas = document.createElement("canvas");
+  canvas.width = 1;
+  canvas.height = 1;
+  const context = canvas.getContext("2d", { willReadFrequently: true });
+  if (!context) {
+    return "";
+  }
+  // 无效的颜色不会改变 fillStyle：分别以黑、白为初值赋值，结果不同说明颜色无效
+  context.fillStyle = "#000";
+  context.fillStyle = value;
+  const onBlack = context.fillStyle;
+  context.fillStyle = "#fff";
+  context.fillStyle = value;
+  if (context.fillStyle !== onBlack) {
+    return "";
+  }
+  context.clearRect(0, 0, 1, 1);
+  context.fillRect(0, 0, 1, 1);
+  const [r, g, b, a] = context.getImageData(0, 0, 1, 1).data;
+  if (a === 0) {
+    return "";
+  }
+  return "#" + [r, g, b].map((n) => n.toString(16).padStart(2, "0")).join("");
+}
+
+/**
+ * 配置了 follow_theme_color 时，把页面 theme-color 上报给 Rust，颜色统一转换为 `#rrggbb`，没有时上报空值
+ */
+function installThemeColorSync() {
+  if (!pakeConfig.follow_theme_color || window.top !== window) {
+    return;
+  }
+  let lastColor = null;
+  const report = () => {
+    // 带 media 的 theme-color 只取与当前配色方案匹配的一个
+    const meta = [...document.querySelectorAll('meta[name="theme-color"]')].find(
+      (el) => !el.media || window.matchMedia(el.media).matches
+    );
+    const color = meta ? cssColorToHex(meta.content) : "";
+    if (color !== lastColor) {
+      lastColor = color;
+      window.ipc.postMessage(`theme_color:${color}`);
+    }
+  };
+  window.addEventListener("DOMContentLoaded", () => {
+    report();
+    new MutationObserver(report).observe(document.head || document.documentElement, {
+      subtree: true,
+      childList: true,
+      attributes: true,
+      attributeFilter: ["content", "media"],
+    });
+    window.matchMedia("(prefers-color-scheme: dark)").addEventListener("change", report);
+  });
+}
+
+/**
+ * 配置了 forward_file_drops 时，接收 Rust 读取的拖入文件并交给页面
+ *
+ * 焦点在文件输入框上时直接填入，否则对放置位置的元素模拟一次 drop，都没有时使用页面上第一个文件输入框。
+ * 放置位置由 Rust 在松开拖放时按光标位置换算，系统拖放过程中页面收不到 mousemove
+ */
+function installFileDropForwarding() {
+  if (!pakeConfig.forward_file_drops || window.top !== window) {
+    return;
+  }
+  const isFileInput = (el) => el && el.tagName === "INPUT" && el.type === "file";
+  window.addEventListener("pake:files_dropped", (event) => {
+    const { files, point } = event.detail;
+    const dataTransfer = new DataTransfer();
+    for (const { name, base64_content } of files) {
+      const binary = atob(base64_content);
+      const bytes = new Uint8Array(binary.length);
+      for (let i = 0; i < binary.length; i++) {
+        bytes[i] = binary.charCodeAt(i);
+      }
+      dataTransfer.items.add(new File([bytes], name));
+    }
+    const focused = document.activeElement;
+    const hovered = point && document.elementFromPoint(point.x, point.y);
+    const input = isFileInput(focused)
+      ? focused
+      : isFileInput(hovered)
+      ? hovered
+      : !hovered && document.querySelector('input[type="file"]');
+    if (input) {
+      if (input.multiple || dataTransfer.files.length <= 1) {
+        input.files = dataTransfer.files;
+      } else {
+        const single = new DataTransfer();
+        single.items.add(dataTransfer.files[0]);
+        input.files = single.files;
+      }
+      input.dispatchEvent(new Event("input", { bubbles: true }));
+      input.dispatchEvent(new Event("change", { bubbles: true }));
+      return;
+    }
+    const target = hovered || document.body;
+    for (const type of ["dragenter", "dragover", "drop"]) {
+      target.dispatchEvent(
+        new DragEvent(type, {
+          bubbles: true,
+          cancelable: true,
+          clientX: point ? point.x : 0,
+          clientY: point ? point.y : 0,
+          dataTransfer,
+        })
+      );
+    }
+  });
+}
+
+/**
+ * 配置了 strip_referrer 时，主窗口中点击链接和提交表单引起的跳转不发送 Referer
+ *
+ * 只影响顶层页面的导航，页面内的图片、脚本等请求照常发送；启动时的首次加载本身就没有 Referer
+ */
+function installReferrerStripping() {
+  if (!pakeConfig.strip_referrer || window.top !== window) {
+    return;
+  }
+  document.addEventListener(
+    "click",
+    (e) => {
+      const link = e.target.closest && e.target.closest("a[href], area[href]");
+      if (link) {
+        link.referrerPolicy = "no-referrer";
+      }
+    },
+    true,
+  );
+  document.addEventListener(
+    "submit",
+    (e) => {
+      const form = e.target;
+      if (form instanceof HTMLFormElement && !/\bnoreferrer\b/.test(form.rel)) {
+        form.rel = `${form.rel} noreferrer`.trim();
+      }
+    },
+    true,
+  );
+}
+
+/**
+ * 按 middle_click_behavior 处理中键点击链接，左键点击不受影响
+ * @param {(command: string) => void} postSensitive 附带会话令牌发送敏感命令，只在用户真实操作时调用
+ */
+function installMiddleClick(postSensitive) {
+  const behavior = pakeConfig.middle_click_behavior;
+  if (!behavior || behavior === "default") {
+    return;
+  }
+  const linkOf = (e) => e.button === 1 && e.target.closest && e.target.closest("a[href]");
+  // 阻止中键按下时的自动滚动
+  document.addEventListener(
+    "mousedown",
+    (e) => {
+      if (linkOf(e)) {
+        e.preventDefault();
+      }
+    },
+    true,
+  );
+  document.addEventListener(
+    "auxclick",
+    (e) => {
+      const link = linkOf(e);
+      if (!link) {
+        return;
+      }
+      e.preventDefault();
+      e.stopPropagation();
+      if (!e.isTrusted) {
+        return;
+      }
+      if (behavior === "external") {
+        postSensitive(`open_browser:${link.href}`);
+      } else if (behavior === "new_window") {
+        postSensitive(`open_window:${link.href}`);
+      }
+    },
+    true,
+  );
+}
+
+/**
+ * 页面加载完成后逐个请求 preload_urls，放入 WebView 的 HTTP 缓存
+ *
+ * 每个请求之间间隔一段时间，避免启动时占满带宽；同一会话只预加载一次
+ */
+function preloadUrls() {
+  const urls = pakeConfig.preload_urls || [];
+  if (window.top !== window || !urls.length || sessionStorage.getItem("pakePreloaded")) {
+    return;
+  }
+  sessionStorage.setItem("pakePreloaded", "1");
+  const PRELOAD_INTERVAL_MS = 1000;
+  const idle = window.requestIdleCallback || ((callback) => setTimeout(callback, 0));
+  const preload = (index) => {
+    if (index >= urls.length) {
+      return;
+    }
+    const url = urls[index];
+    fetch(url, { mode: "no-cors", credentials: "include" })
+      .then(() => window.ipc.postMessage(`preloaded:ok:${url}`))
+      .catch(() => window.ipc.postMessage(`preloaded:failed:${url}`))
+      .finally(() => setTimeout(() => idle(() => preload(index + 1)), PRELOAD_INTERVAL_MS));
+  };
+  window.addEventListener("load", () => idle(() => preload(0)));
+}
+
+/**
+ * 页面就绪后通知 Rust，用于备用地址切换和加载过慢提示
+ *
+ * 配置了 ready_selector 时等待该元素出现才算就绪（同时关闭启动画面），
+ * 超过 ready_timeout_ms 仍未出现则按已就绪处理
+ */
+function notifyPageReady() {
+  if (window.top !== window) {
+    return;
+  }
+  const selector = pakeConfig.ready_selector;
+  if (!selector) {
+    window.addEventListener("DOMContentLoaded", () => window.ipc.postMessage("page_ready"));
+    return;
+  }
+  let done = false;
+  let observer;
+  let timer;
+  const finish = () => {
+    if (done) {
+      return;
+    }
+    done = true;
+    observer?.disconnect();
+    clearTimeout(timer);
+    window.ipc.postMessage("page_ready");
+    hideSplash();
+  };
+  const check = () => {
+    try {
+      if (document.querySelector(selector)) {
+        finish();
+      }
+    } catch (e) {
+      console.warn(`Invalid ready_selector: ${selector}`);
+      finish();
+    }
+  };
+  window.addEventListener("DOMContentLoaded", () => {
+    check();
+    if (done) {
+      return;
+    }
+    observer = new MutationObserver(check);
+    observer.observe(document.documentElement, { childList: true, subtree: true });
+    timer = setTimeout(finish, pakeConfig.ready_timeout_ms || 10000);
+  });
+}
+
+/**
+ * 配置了 disable_autofill 时为所有表单和输入框关闭自动填充，包括之后动态添加的
+ */
+function disableAutofill() {
+  if (!pakeConfig.disable_autofill) {
+    return;
+  }
+  const selector = "form, input, textarea";
+  const apply = (root) => {
+    const elements = [...root.querySelectorAll(selector)];
+    if (root.matches?.(selector)) {
+      elements.push(root);
+    }
+    elements.forEach((el) => {
+      el.setAttribute("autocomplete", el.type === "password" ? "new-password" : "off");
+    });
+  };
+  window.addEventListener("DOMContentLoaded", () => {
+    apply(document);
+    new MutationObserver((mutations) => {
+      mutations.forEach((m) => m.addedNodes.forEach((node) => node.nodeType === 1 && apply(node)));
+    }).observe(document.body, { childList: true, subtree: true });
+  });
+}
+
+/**
+ * 关闭窗口前由 Rust 调用，检查页面的 beforeunload 监听是否要阻止关闭
+ */
+function pakeCheckBeforeUnload() {
+  let block = false;
+  try {
+    const event = document.createEvent("BeforeUnloadEvent");
+    event.initEvent("beforeunload", false, true);
+    window.dispatchEvent(event);
+    block = event.defaultPrevented || !!event.returnValue;
+  } catch (_e) {
+    block = false;
+  }
+  window.ipc.postMessage(`beforeunload_result:${block}`);
+}
+
+/**
+ * 高对比度模式：只覆盖颜色和字号，不修改尺寸和定位，避免破坏页面布局
+ */
+function installHighContrast() {
+  const style = document.createElement("style");
+  style.innerHTML = `
+    html.pake-high-contrast {
+      font-size: 120% !important;
+    }
+    html.pake-high-contrast body,
+    html.pake-high-contrast body *:not(img):not(video):not(canvas):not(svg):not(svg *) {
+      background-color: #000 !important;
+      background-image: none !important;
+      color: #fff !important;
+      border-color: #fff !important;
+      text-shadow: none !important;
+    }
+    html.pake-high-contrast a,
+    html.pake-high-contrast a * {
+      color: #ff0 !important;
+      text-decoration: underline !important;
+    }
+    html.pake-high-contrast :focus {
+      outline: 3px solid #0ff !important;
+    }
+  `;
+  document.head.append(style);
+
+  const saved = window.localStorage.getItem("pakeHighContrast");
+  const enabled = saved === null ? !!pakeConfig.high_contrast : saved === "true";
+  document.documentElement.classList.toggle("pake-high-contrast", enabled);
+}
+
+function pakeToggleHighContrast() {
+  const enabled = document.documentElement.classList.toggle("pake-high-contrast");
+  window.localStorage.setItem("pakeHighContrast", String(enabled));
+}
+
+/**
+ * 收集当前页面的请求信息，交给 Rust 生成 curl 命令（仅 devtools 构建可用）
+ */
+function pakeCopyAsCurl() {
+  const headers = [
+    ["User-Agent", navigator.userAgent],
+    ["Accept-Language", navigator.languages.join(",")],
+    ["Referer", document.referrer],
+    ["Cookie", document.cookie],
+  ].filter(([, value]) => value);
+  const info = { url: window.location.href, headers };
+  window.ipc.postMessage(`curl_info:${JSON.stringify(info)}`);
+}
+
+/**
+ * 聚焦第一个可见且可编辑的输入框，页面已有焦点元素时不处理
+ */
+function focusFirstInput() {
+  if (document.activeElement && document.activeElement !== document.body) {
+    return;
+  }
+  const candidates = document.querySelectorAll(
+    'input:not([type=hidden]):not([disabled]):not([readonly]), textarea:not([disabled]):not([readonly]), [contenteditable="true"]'
+  );
+  for (const el of candidates) {
+    const rect = el.getBoundingClientRect();
+    if (rect.width > 0 && rect.height > 0 && getComputedStyle(el).visibility !== "hidden") {
+      el.focus();
+      return;
+    }
+  }
+}
+
+/**
+ * devtools 构建下将 console 输出转发给 Rust，供调试控制台窗口显示
+ */
+function installConsoleForwarding() {
+  if (!window.__PAKE_DEVTOOLS__) {
+    return;
+  }
+  const format = (arg) => {
+    if (typeof arg === "string") {
+      return arg;
+    }
+    try {
+      return JSON.stringify(arg);
+    } catch (_e) {
+      return String(arg);
+    }
+  };
+  for (const level of ["log", "info", "warn", "error", "debug"]) {
+    const original = console[level];
+    console[level] = (...args) => {
+      original.apply(console, args);
+      window.ipc.postMessage(`console:${level}:${args.map(format).join(" ")}`);
+    };
+  }
+}
+
+/**
+ * 配置了 prevent_sleep 时，有视频正在播放就通知 Rust 阻止系统休眠，全部停止后恢复
+ */
+function installKeepAwake() {
+  if (!pakeConfig.prevent_sleep || window.top !== window) {
+    return;
+  }
+  let awake = false;
+  const update = () => {
+    const playing = Array.from(document.querySelectorAll("video")).some(
+      (video) => !video.paused && !video.ended && video.readyState > 2
+    );
+    if (playing !== awake) {
+      awake = playing;
+      window.ipc.postMessage(`keep_awake:${playing}`);
+    }
+  };
+  // 媒体事件不冒泡，在捕获阶段监听
+  for (const type of ["playing", "pause", "ended", "emptied"]) {
+    document.addEventListener(type, update, true);
+  }
+  window.addEventListener("pagehide", () => {
+    if (awake) {
+      window.ipc.postMessage("keep_awake:false");
+    }
+  });
+}
+
+/**
+ * 配置了 capture_js_errors 时，把未处理的错误和 Promise 拒绝上报给 Rust
+ *
+ * 相同的错误只上报一次，每秒最多上报 5 条，避免错误循环刷屏
+ */
+function installErrorReporting() {
+  if (!pakeConfig.capture_js_errors) {
+    return;
+  }
+  const MAX_PER_SECOND = 5;
+  const reported = new Set();
+  let windowStart = 0;
+  let count = 0;
+  const report = (error) => {
+    const key = `${error.message}|${error.source}|${error.line}`;
+    const now = Date.now();
+    if (now - windowStart >= 1000) {
+      windowStart = now;
+      count = 0;
+    }
+    if (reported.has(key) || count >= MAX_PER_SECOND) {
+      return;
+    }
+    reported.add(key);
+    count += 1;
+    window.ipc.postMessage(`js_error:${JSON.stringify(error)}`);
+  };
+  window.addEventListener("error", (event) => {
+    report({
+      kind: "error",
+      message: event.message || String(event.error),
+      source: event.filename || "",
+      line: event.lineno || 0,
+      column: event.colno || 0,
+      stack: (event.error && event.error.stack) || "",
+    });
+  });
+  window.addEventListener("unhandledrejection", (event) => {
+    const reason = event.reason;
+    report({
+      kind: "unhandledrejection",
+      message: reason instanceof Error ? reason.message : String(reason),
+      source: "",
+      line: 0,
+      column: 0,
+      stack: (reason && reason.stack) || "",
+    });
+  });
+}
+
+/**
+ * 系统拖动期间页面收不到 mouseup，拖动结束后第一个鼠标事件即视为松开
+ */
+function notifyDragEnd() {
+  const events = ["mouseup", "mousemove", "pointerup"];
+  const onEnd = (e) => {
+    if (e.type === "mousemove" && e.buttons !== 0) {
+      return;
+    }
+    events.forEach((type) => window.removeEventListener(type, onEnd, true));
+    window.ipc.postMessage("drag_end");
+  };
+  events.forEach((type) => window.addEventListener(type, onEnd, true));
+}
+
+/**
+ * 页面加载完成前显示启动画面，配置了 splash_image 时显示图片，否则显示加载动画
+ */
+function showSplash() {
+  const splash = window.__PAKE_SPLASH__;
+  if (!splash || window.top !== window) {
+    return;
+  }
+  mountSplash(splash);
+}
+
+function mountSplash(splash) {
+  const overlay = document.createElement("div");
+  overlay.id = "pake-splash";
+  overlay.style.cssText =
+    "position:fixed;inset:0;z-index:2147483647;display:flex;align-items:center;justify-content:center;background:#fff;transition:opacity .3s ease-out;";
+
+  // splash_show_version 开启时在底部显示版本，随启动画面一起在页面就绪后隐藏
+  const info = splash.build ? document.createElement("div") : null;
+  if (info) {
+    const { name, version, build } = splash.build;
+    info.textContent = [name, version && `v${version}`, build && `(${build})`].filter(Boolean).join(" ");
+    info.style.cssText =
+      "position:absolute;left:0;right:0;bottom:16px;text-align:center;font:12px/1.4 -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;color:rgba(0,0,0,.5);user-select:text;";
+  }
+
+  const showSpinner = () => {
+    overlay.innerHTML = "";
+    const spinner = document.createElement("div");
+    spinner.style.cssText =
+      "width:32px;height:32px;border:3px solid rgba(0,0,0,.1);border-top-color:rgba(0,0,0,.6);border-radius:50%;animation:pake-spin 1s linear infinite;";
+    const keyframes = document.createElement("style");
+    keyframes.innerHTML = "@keyframes pake-spin { to { transform: rotate(360deg); } }";
+    overlay.append(keyframes, spinner);
+    if (info) {
+      overlay.appendChild(info);
+    }
+  };
+
+  if (splash.image) {
+    const img = document.createElement("img");
+    img.src = splash.image;
+    img.style.cssText = `width:100%;height:100%;object-fit:${splash.fit || "contain"};`;
+    img.onerror = showSpinner;
+    overlay.appendChild(img);
+    if (info) {
+      overlay.appendChild(info);
+    }
+  } else {
+    showSpinner();
+  }
+
+  // 注入脚本在文档开始时运行，此时 documentElement 可能还不存在，等解析器创建后再插入
+  if (document.documentElement) {
+    document.documentElement.appendChild(overlay);
+  } else {
+    const observer = new MutationObserver(() => {
+      if (document.documentElement) {
+        observer.disconnect();
+        document.documentElement.appendChild(overlay);
+      }
+    });
+    observer.observe(document, { childList: true });
+  }
+  // 配置了 ready_selector 时由 notifyPageReady 关闭
+  if (!pakeConfig.ready_selector) {
+    window.addEventListener("load", hideSplash, { once: true });
+  }
+  return overlay;
+}
+
+/**
+ * 加载超过 slow_load_ms 仍未完成时由 Rust 调用，在启动画面上提示并提供重试/取消
+ */
+function pakeShowSlowLoad() {
+  if (document.getElementById("pake-slow-load")) {
+    return;
+  }
+  // 启动画面已关闭或未配置时重新显示，沿用配置的图片和版本信息
+  const overlay = document.getElementById("pake-splash") || mountSplash(window.__PAKE_SPLASH__ || {});
+  const panel = document.createElement("div");
+  panel.id = "pake-slow-load";
+  panel.dir = uiRtl ? "rtl" : "ltr";
+  panel.style.cssText =
+    "position:absolute;left:0;right:0;bottom:48px;text-align:center;font:14px -apple-system,BlinkMacSystemFont,sans-serif;color:#666;";
+  const message = document.createElement("div");
+  message.textContent = "Still loading…";
+  message.style.marginBottom = "12px";
+  panel.appendChild(message);
+  // 禁用 IPC 时按钮发出的命令会被忽略，只保留提示
+  const actions = pakeConfig.disable_ipc ? [] : [
+    ["Retry", () => window.ipc.postMessage("reload")],
+    // 点击由 installSlowLoadCancel 处理
+    ["Cancel", null],
+  ];
+  for (const [label, onclick] of actions) {
+    const button = document.createElement("button");
+    button.textContent = label;
+    button.style.cssText = "margin:0 6px;padding:4px 16px;cursor:pointer;";
+    if (onclick) {
+      button.onclick = onclick;
+    } else {
+      button.dataset.pakeCancel = "";
+    }
+    panel.appendChild(button);
+  }
+  overlay.appendChild(panel);
+}
+
+/**
+ * 配置了 fade_in_ms 时，页面首帧绘制后通知 Rust 开始淡入窗口；有启动画面时启动画面即为首帧
+ */
+function notifyFirstPaint() {
+  if (!(pakeConfig.fade_in_ms > 0) || window.top !== window) {
+    return;
+  }
+  const notify = () =>
+    requestAnimationFrame(() => requestAnimationFrame(() => window.ipc.postMessage("first_paint")));
+  if (document.getElementById("pake-splash") || document.readyState !== "loading") {
+    notify();
+  } else {
+    document.addEventListener("DOMContentLoaded", notify, { once: true });
+  }
+}
+
+function hideSplash() {
+  const overlay = document.getElementById("pake-splash");
+  if (!overlay) {
+    return;
+  }
+  overlay.style.opacity = "0";
+  setTimeout(() => overlay.remove(), 300);
+}
 
 function setDefaultZoom() {
+  // 按域名缩放时由 Rust 通过 WebView 原生缩放设置
+  if (pakeConfig.per_host_zoom || pakeConfig.lock_zoom) {
+    return;
+  }
   const htmlZoom = window.localStorage.getItem("htmlZoom");
   if (htmlZoom) {
     document.getElementsByTagName("html")[0].style.zoom = htmlZoom;
   }
+  updateUiScale();
 }
 
 /**
  * @param {(htmlZoom: string) => string} [zoomRule]
  */
 function zoomCommon(zoomRule) {
+  if (pakeConfig.lock_zoom) {
+    return;
+  }
   const htmlZoom = window.localStorage.getItem("htmlZoom") || "100%";
   const html = document.getElementsByTagName("html")[0];
   const zoom = zoomRule(htmlZoom);
   html.style.zoom = zoom;
   window.localStorage.setItem("htmlZoom", zoom);
+  updateUiScale();
 }
 
+/**
+ * 注入的提示等界面通过 `zoom: var(--pake-ui-scale)` 反向缩放，不随页面缩放变大变小
+ */
+function updateUiScale() {
+  const html = document.documentElement;
+  const cssZoom = parseFloat(html.style.zoom) / 100 || 1;
+  html.style.setProperty("--pake-ui-scale", String(1 / (engineZoom * cssZoom)));
+}
+
+window.addEventListener("pake:zoom_changed", (event) => {
+  engineZoom = Number(event.detail) || 1;
+  updateUiScale();
+});
+
 function zoomIn() {
+  if (pakeConfig.per_host_zoom) {
+    window.ipc.postMessage("zoom_in");
+    return;
+  }
   zoomCommon((htmlZoom) => `${Math.min(parseInt(htmlZoom) + 10, 200)}%`);
 }
 
 function zoomOut() {
+  if (pakeConfig.per_host_zoom) {
+    window.ipc.postMessage("zoom_out");
+    return;
+  }
   zoomCommon((htmlZoom) => `${Math.max(parseInt(htmlZoom) - 10, 30)}%`);
 }
 
+function zoomReset() {
+  if (pakeConfig.per_host_zoom) {
+    window.ipc.postMessage("zoom_reset");
+    return;
+  }
+  zoomCommon(() => "100%");
+}
+
+/**
+ * 配置了 lock_zoom 时拦截 Ctrl+滚轮和捏合手势，引擎仍然缩放时通知 Rust 恢复为 100%
+ */
+function installZoomLock() {
+  if (!pakeConfig.lock_zoom) {
+    return;
+  }
+  window.addEventListener(
+    "wheel",
+    (event) => {
+      if (event.ctrlKey) {
+        event.preventDefault();
+      }
+    },
+    { passive: false, capture: true }
+  );
+  // WKWebView 的触控板捏合手势
+  for (const type of ["gesturestart", "gesturechange", "gestureend"]) {
+    document.addEventListener(type, (event) => event.preventDefault(), {
+      passive: false,
+    });
+  }
+  // 引擎缩放会改变 devicePixelRatio；移到缩放比例不同的显示器时也会触发，此时恢复 100% 没有影响
+  const watchPixelRatio = () => {
+    window
+      .matchMedia(`(resolution: ${window.devicePixelRatio}dppx)`)
+      .addEventListener(
+        "change",
+        () => {
+          window.ipc.postMessage("zoom_changed");
+          watchPixelRatio();
+        },
+        { once: true }
+      );
+  };
+  watchPixelRatio();
+}
+
+/**
+ * 配置了 auto_login 时由 Rust 在登录页调用，等待输入框出现后填写账号密码并提交；
+ * 同一会话最多尝试 3 次，避免密码错误时反复提交
+ */
+function pakeAutoLogin(login) {
+  const attemptsKey = "pake-auto-login-attempts";
+  const attempts = Number(window.sessionStorage.getItem(attemptsKey) || 0);
+  if (attempts >= 3) {
+    console.warn("Pake auto login skipped after 3 attempts");
+    return;
+  }
+  const setValue = (input, value) => {
+    // 通过原型上的 setter 赋值，React 等框架才能感知到变化
+    const prototype = input instanceof HTMLTextAreaElement ? HTMLTextAreaElement.prototype : HTMLInputElement.prototype;
+    Object.getOwnPropertyDescriptor(prototype, "value").set.call(input, value);
+    input.dispatchEvent(new Event("input", { bubbles: true }));
+    input.dispatchEvent(new Event("change", { bubbles: true }));
+  };
+  const deadline = Date.now() + 10000;
+  const fill = () => {
+    const username = document.querySelector(login.username_selector);
+    const password = document.querySelector(login.password_selector);
+    if (!username || !password) {
+      if (Date.now() < deadline) {
+        setTimeout(fill, 250);
+      }
+      return;
+    }
+    window.sessionStorage.setItem(attemptsKey, String(attempts + 1));
+    setValue(username, login.username);
+    setValue(password, login.password);
+    const submit = login.submit_selector && document.querySelector(login.submit_selector);
+    if (submit) {
+      submit.click();
+    } else if (password.form) {
+      password.form.requestSubmit();
+    }
+  };
+  fill();
+}
 
 function pakeToast(msg) {
 	const m = document.createElement('div');
 	m.innerHTML = msg;
-	m.style.cssText = "max-width:60%;min-width: 180px;padding:0 8px;height: 36px;color: rgb(255, 255, 255);line-height: 36px;text-align: center;border-radius: 4px;position: fixed;bottom:16px;right: 16px;transform: translate(-50%, -50%);z-index: 999999;background: rgba(0, 0, 0,.9);font-size: 14px;";
+	m.dir = uiRtl ? 'rtl' : 'ltr';
+	// 从右到左时镜像到左下角
+	const side = uiRtl ? 'left: 16px;transform: translate(50%, -50%);' : 'right: 16px;transform: translate(-50%, -50%);';
+	m.style.cssText = "max-width:60%;min-width: 180px;padding:0 8px;height: 36px;color: rgb(255, 255, 255);line-height: 36px;text-align: center;border-radius: 4px;position: fixed;bottom:16px;" + side + "z-index: 999999;background: rgba(0, 0, 0,.9);font-size: 14px;zoom: var(--pake-ui-scale, 1);";
 	document.body.appendChild(m);
 	setTimeout(function() {
     const d = 0.5;
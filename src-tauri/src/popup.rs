@@ -0,0 +1,105 @@
+//! 在新的 Pake 窗口中打开链接，窗口只负责浏览，不接入主窗口的 IPC
+use crate::navigation::{is_allowed, CurrentUrl};
+use crate::state::{SavedWindow, WindowState};
+use wry::application::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event_loop::EventLoopWindowTarget,
+    window::{Fullscreen, WindowBuilder},
+};
+use wry::webview::{WebContext, WebView, WebViewBuilder};
+
+pub struct Popup {
+    pub webview: WebView,
+    /// 最近一次导航的地址，退出时保存到窗口列表
+    url: CurrentUrl,
+    /// 打开时应用的缩放比例，新窗口不接入 IPC，之后不会再变化
+    zoom: f64,
+}
+
+impl Popup {
+    pub fn saved(&self) -> SavedWindow {
+        SavedWindow::capture(self.webview.window(), self.url.get(), self.zoom)
+    }
+}
+
+/// 新窗口与主窗口共用 `web_context`，登录状态和数据目录一致；导航同样受 `allowlist` 限制
+pub fn open<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    url: &str,
+    zoom: f64,
+    allowlist: &[String],
+    web_context: &mut WebContext,
+) -> wry::Result<Popup> {
+    open_with_geometry(event_loop, url, None, zoom, allowlist, web_context)
+}
+
+/// 按保存的窗口状态重新打开窗口
+pub fn restore<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    saved: &SavedWindow,
+    allowlist: &[String],
+    web_context: &mut WebContext,
+) -> wry::Result<Popup> {
+    open_with_geometry(
+        event_loop,
+        &saved.url,
+        Some(&saved.geometry),
+        saved.zoom,
+        allowlist,
+        web_context,
+    )
+}
+
+fn open_with_geometry<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    url: &str,
+    geometry: Option<&WindowState>,
+    zoom: f64,
+    allowlist: &[String],
+    web_context: &mut WebContext,
+) -> wry::Result<Popup> {
+    let mut builder = WindowBuilder::new()
+        .with_title("")
+        .with_inner_size(LogicalSize::new(1024.0, 768.0));
+    if let Some(state) = geometry {
+        if state.has_geometry() {
+            builder = builder
+                .with_inner_size(PhysicalSize::new(state.width, state.height))
+                .with_position(PhysicalPosition::new(state.x, state.y));
+        }
+        if state.maximized {
+            builder = builder.with_maximized(true);
+        }
+        if state.fullscreen {
+            builder = builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+    }
+    let window = builder.build(event_loop)?;
+    let current_url = CurrentUrl::new(url.to_string());
+    let webview = WebViewBuilder::new(window)?
+        .with_user_agent(crate::USER_AGENT)
+        .with_url(url)?
+        .with_devtools(cfg!(feature = "devtools"))
+        .with_web_context(web_context)
+        .with_navigation_handler({
+            let current_url = current_url.clone();
+            let allowlist = allowlist.to_vec();
+            move |url: String| {
+                if !is_allowed(&allowlist, &url) {
+                    eprintln!("警告: 已拦截新窗口中白名单外的导航: {}", url);
+                    return false;
+                }
+                current_url.set(&url);
+                true
+            }
+        })
+        .build()?;
+    if zoom != 1.0 {
+        webview.zoom(zoom);
+    }
+    Ok(Popup {
+        webview,
+        url: current_url,
+        zoom,
+    })
+}
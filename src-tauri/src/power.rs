@@ -0,0 +1,301 @@
+//! 电源状态查询，供页面在使用电池时暂停高耗电任务；系统睡眠唤醒的监听，以及播放媒体时阻止休眠
+use crate::UserEvent;
+use serde::Serialize;
+use std::time::Duration;
+use wry::application::{event_loop::EventLoopProxy, window::Window};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 没有电池的台式机上 `has_battery` 为 false，视为使用外接电源
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PowerState {
+    pub has_battery: bool,
+    pub charging: bool,
+    pub on_battery: bool,
+    /// 所有电池的平均电量（0–100），没有电池时为 None
+    pub percentage: Option<f32>,
+}
+
+pub fn read() -> PowerState {
+    use battery::{units::ratio::percent, State};
+
+    let batteries: Vec<_> = match battery::Manager::new().and_then(|m| m.batteries()) {
+        Ok(batteries) => batteries.flatten().collect(),
+        Err(e) => {
+            eprintln!("警告: 无法读取电池信息: {:?}", e);
+            return PowerState::default();
+        }
+    };
+    if batteries.is_empty() {
+        return PowerState::default();
+    }
+    let total: f32 = batteries
+        .iter()
+        .map(|b| b.state_of_charge().get::<percent>())
+        .sum();
+    PowerState {
+        has_battery: true,
+        charging: batteries.iter().any(|b| b.state() == State::Charging),
+        on_battery: batteries.iter().any(|b| b.state() == State::Discharging),
+        percentage: Some(total / batteries.len() as f32),
+    }
+}
+
+/// 定时检查电源状态，变化时（包括插拔电源）通知事件循环
+pub fn spawn_monitor(proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        let mut last = read();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let state = read();
+            if state == last {
+                continue;
+            }
+            if state.on_battery != last.on_battery {
+                println!(
+                    "电源切换: {}",
+                    if state.on_battery {
+                        "电池"
+                    } else {
+                        "外接电源"
+                    }
+                );
+            }
+            last = state.clone();
+            if proxy.send_event(UserEvent::PowerChanged(state)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// 监听 `NSWorkspaceDidWakeNotification`，系统唤醒后发送 `SystemResumed`
+#[cfg(target_os = "macos")]
+pub fn watch_wake(_window: &Window, proxy: EventLoopProxy<UserEvent>) {
+    use block::ConcreteBlock;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let center: id = msg_send![workspace, notificationCenter];
+        let name = NSString::alloc(nil).init_str("NSWorkspaceDidWakeNotification");
+        let on_wake = ConcreteBlock::new(move |_notification: id| {
+            println!("系统已从睡眠中唤醒");
+            let _ = proxy.send_event(UserEvent::SystemResumed);
+        })
+        .copy();
+        let _: id = msg_send![center, addObserverForName: name
+                                      object: nil
+                                      queue: nil
+                                      usingBlock: &*on_wake];
+        let _: () = msg_send![name, release];
+    }
+}
+
+/// 子类化主窗口以接收 `WM_POWERBROADCAST`，系统唤醒后发送 `SystemResumed`
+#[cfg(target_os = "windows")]
+pub fn watch_wake(window: &Window, proxy: EventLoopProxy<UserEvent>) {
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::WM_POWERBROADCAST;
+    use wry::application::platform::windows::WindowExtWindows;
+
+    const PBT_APMRESUMEAUTOMATIC: usize = 0x12;
+    const SUBCLASS_ID: usize = 0x5041_4b45;
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        data: usize,
+    ) -> LRESULT {
+        if msg == WM_POWERBROADCAST && wparam.0 == PBT_APMRESUMEAUTOMATIC {
+            let proxy = &*(data as *const EventLoopProxy<UserEvent>);
+            println!("系统已从睡眠中唤醒");
+            let _ = proxy.send_event(UserEvent::SystemResumed);
+        }
+        DefSubclassProc(hwnd, msg, wparam, lparam)
+    }
+
+    // 代理与窗口同生命周期，直接泄漏给子类过程使用
+    let data = Box::into_raw(Box::new(proxy)) as usize;
+    unsafe {
+        if !SetWindowSubclass(
+            HWND(window.hwnd() as _),
+            Some(subclass_proc),
+            SUBCLASS_ID,
+            data,
+        )
+        .as_bool()
+        {
+            eprintln!("警告: 无法监听系统唤醒事件");
+            drop(Box::from_raw(data as *mut EventLoopProxy<UserEvent>));
+        }
+    }
+}
+
+/// 订阅 logind 的 `PrepareForSleep` 信号，参数为 false 时表示系统已唤醒
+#[cfg(target_os = "linux")]
+pub fn watch_wake(_window: &Window, proxy: EventLoopProxy<UserEvent>) {
+    use gio::{BusType, Cancellable, DBusSignalFlags};
+    use std::cell::RefCell;
+
+    thread_local! {
+        // 订阅不持有连接，连接释放后信号也随之失效，需要在应用运行期间一直保留
+        static WAKE_CONNECTION: RefCell<Option<gio::DBusConnection>> = RefCell::new(None);
+    }
+
+    let connection = match gio::bus_get_sync(BusType::System, None::<&Cancellable>) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("警告: 无法连接系统 D-Bus，无法监听系统唤醒事件: {:?}", e);
+            return;
+        }
+    };
+    connection.signal_subscribe(
+        Some("org.freedesktop.login1"),
+        Some("org.freedesktop.login1.Manager"),
+        Some("PrepareForSleep"),
+        Some("/org/freedesktop/login1"),
+        None,
+        DBusSignalFlags::NONE,
+        move |_connection, _sender, _path, _interface, _signal, parameters| {
+            if let Some((false,)) = parameters.get::<(bool,)>() {
+                println!("系统已从睡眠中唤醒");
+                let _ = proxy.send_event(UserEvent::SystemResumed);
+            }
+        },
+    );
+    WAKE_CONNECTION.with(|slot| *slot.borrow_mut() = Some(connection));
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPMAssertionCreateWithName(
+        assertion_type: cocoa::base::id,
+        level: u32,
+        name: cocoa::base::id,
+        assertion_id: *mut u32,
+    ) -> i32;
+    fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+}
+
+/// 阻止系统和显示器进入休眠，释放（drop）后恢复
+#[cfg(target_os = "macos")]
+pub struct SleepInhibitor(u32);
+
+#[cfg(target_os = "macos")]
+impl SleepInhibitor {
+    pub fn acquire(reason: &str) -> Result<Self, String> {
+        use cocoa::base::nil;
+        use cocoa::foundation::NSString;
+        use objc::{msg_send, sel, sel_impl};
+
+        const K_IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+        let mut assertion_id = 0;
+        let result = unsafe {
+            // NSString 与 CFStringRef 可以直接互换，断言会自行保留名称，调用后即可释放
+            let assertion_type = NSString::alloc(nil).init_str("NoDisplaySleepAssertion");
+            let name = NSString::alloc(nil).init_str(reason);
+            let result = IOPMAssertionCreateWithName(
+                assertion_type,
+                K_IOPM_ASSERTION_LEVEL_ON,
+                name,
+                &mut assertion_id,
+            );
+            let _: () = msg_send![assertion_type, release];
+            let _: () = msg_send![name, release];
+            result
+        };
+        if result == 0 {
+            Ok(SleepInhibitor(assertion_id))
+        } else {
+            Err(format!("IOPMAssertionCreateWithName 返回 {}", result))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        unsafe {
+            IOPMAssertionRelease(self.0);
+        }
+    }
+}
+
+/// 阻止系统和显示器进入休眠，释放（drop）后恢复
+///
+/// `SetThreadExecutionState` 按线程生效，必须在事件循环线程中获取和释放
+#[cfg(target_os = "windows")]
+pub struct SleepInhibitor;
+
+#[cfg(target_os = "windows")]
+impl SleepInhibitor {
+    pub fn acquire(_reason: &str) -> Result<Self, String> {
+        use windows::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        let previous = unsafe {
+            SetThreadExecutionState(ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED)
+        };
+        if previous.0 == 0 {
+            Err("SetThreadExecutionState 调用失败".to_string())
+        } else {
+            Ok(SleepInhibitor)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        use windows::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS};
+
+        unsafe {
+            SetThreadExecutionState(ES_CONTINUOUS);
+        }
+    }
+}
+
+/// 通过 `systemd-inhibit` 持有 logind 的 idle/sleep 抑制锁，结束子进程即释放
+///
+/// 抑制期间运行的是读取标准输入的 `cat`，管道的写端只由本进程持有：即使本进程崩溃没有执行 drop，
+/// 管道关闭后 `cat` 读到 EOF 退出，抑制锁随之释放，不会留下孤儿进程
+#[cfg(target_os = "linux")]
+pub struct SleepInhibitor(std::process::Child);
+
+#[cfg(target_os = "linux")]
+impl SleepInhibitor {
+    pub fn acquire(reason: &str) -> Result<Self, String> {
+        use std::process::Stdio;
+
+        std::process::Command::new("systemd-inhibit")
+            .args([
+                "--what=idle:sleep",
+                "--who=Pake",
+                &format!("--why={}", reason),
+                "--mode=block",
+                "cat",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map(SleepInhibitor)
+            .map_err(|e| format!("无法启动 systemd-inhibit: {:?}", e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
@@ -0,0 +1,159 @@
+//! 统一处理页面的打印请求：pake.js 接管 `window.print()` 后通过 `print` IPC 转到这里
+use crate::config::PrintMode;
+use crate::{download, UserEvent};
+use std::path::PathBuf;
+use wry::application::event_loop::EventLoopProxy;
+use wry::webview::WebView;
+
+/// 打印为 PDF 时保存到下载目录的路径
+fn pdf_path() -> PathBuf {
+    let file_name = format!("print-{}.pdf", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    download::unique_path(download::download_path(&file_name))
+}
+
+fn report_pdf(proxy: &EventLoopProxy<UserEvent>, path: &std::path::Path, success: bool) {
+    let message = if success {
+        println!("已打印为 PDF: {}", path.display());
+        format!("Saved to {}", path.display())
+    } else {
+        eprintln!("警告: 打印为 PDF 失败: {}", path.display());
+        "Print failed".to_string()
+    };
+    let _ = proxy.send_event(UserEvent::Toast(message));
+}
+
+/// WebKitGTK 的打印操作使用页面的打印样式
+#[cfg(target_os = "linux")]
+pub fn print(webview: &WebView, mode: PrintMode, proxy: EventLoopProxy<UserEvent>) {
+    use gtk::prelude::PrintSettingsExt;
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+    use wry::application::platform::unix::WindowExtUnix;
+    use wry::webview::WebviewExtUnix;
+
+    let operation = PrintOperation::new(&*webview.webview());
+    match mode {
+        PrintMode::Dialog => {
+            operation.run_dialog(Some(webview.window().gtk_window()));
+        }
+        PrintMode::Pdf => {
+            let path = pdf_path();
+            let uri = url::Url::from_file_path(&path)
+                .map(|u| u.to_string())
+                .unwrap_or_default();
+            let settings = gtk::PrintSettings::new();
+            settings.set_printer(Some("Print to File"));
+            settings.set(gtk::PRINT_SETTINGS_OUTPUT_FILE_FORMAT, Some("pdf"));
+            settings.set(gtk::PRINT_SETTINGS_OUTPUT_URI, Some(&uri));
+            operation.set_print_settings(&settings);
+            operation.connect_finished(move |_| report_pdf(&proxy, &path, path.exists()));
+            operation.print();
+        }
+    }
+}
+
+/// WKWebView 中 `window.print()` 不起作用，改用 NSPrintOperation，打印为 PDF 时直接保存到文件。
+/// 两种方式都以窗口模态运行，不阻塞事件循环，结果在代理回调中上报
+#[cfg(target_os = "macos")]
+pub fn print(webview: &WebView, mode: PrintMode, proxy: EventLoopProxy<UserEvent>) {
+    use cocoa::base::{id, nil, NO};
+    use cocoa::foundation::NSString;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel, BOOL};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::c_void;
+    use wry::application::platform::macos::WindowExtMacOS;
+    use wry::webview::WebviewExtMacOS;
+
+    /// 打印为 PDF 时通过 contextInfo 传给回调，对话框打印不需要
+    type PdfJob = (PathBuf, EventLoopProxy<UserEvent>);
+
+    extern "C" fn did_run(_: &Object, _: Sel, _: id, success: BOOL, context: *mut c_void) {
+        if context.is_null() {
+            return;
+        }
+        let (path, proxy) = *unsafe { Box::from_raw(context as *mut PdfJob) };
+        report_pdf(&proxy, &path, success != NO);
+    }
+
+    unsafe {
+        let delegate_class = match ClassDecl::new("PakePrintDelegate", class!(NSObject)) {
+            Some(mut decl) => {
+                decl.add_method(
+                    sel!(printOperationDidRun:success:contextInfo:),
+                    did_run as extern "C" fn(&Object, Sel, id, BOOL, *mut c_void),
+                );
+                decl.register()
+            }
+            None => class!(PakePrintDelegate),
+        };
+        // 回调结束后由自动释放池释放
+        let delegate: id = msg_send![delegate_class, new];
+        let _: id = msg_send![delegate, autorelease];
+
+        let wk_webview: id = webview.webview();
+        let shared: id = msg_send![class!(NSPrintInfo), sharedPrintInfo];
+        let print_info: id = msg_send![shared, copy];
+        let context = if mode == PrintMode::Pdf {
+            let path = pdf_path();
+            let ns_path = NSString::alloc(nil).init_str(&path.display().to_string());
+            let url: id = msg_send![class!(NSURL), fileURLWithPath: ns_path];
+            let dictionary: id = msg_send![print_info, dictionary];
+            let key_disposition = NSString::alloc(nil).init_str("NSJobDisposition");
+            let save_job = NSString::alloc(nil).init_str("NSPrintSaveJob");
+            let key_url = NSString::alloc(nil).init_str("NSJobSavingURL");
+            let _: () = msg_send![dictionary, setObject: save_job forKey: key_disposition];
+            let _: () = msg_send![dictionary, setObject: url forKey: key_url];
+            for string in [ns_path, key_disposition, save_job, key_url] {
+                let _: () = msg_send![string, release];
+            }
+            Box::into_raw(Box::new((path, proxy))) as *mut c_void
+        } else {
+            std::ptr::null_mut()
+        };
+        let operation: id = msg_send![wk_webview, printOperationWithPrintInfo: print_info];
+        let _: () = msg_send![print_info, release];
+        if !context.is_null() {
+            let _: () = msg_send![operation, setShowsPrintPanel: NO];
+            let _: () = msg_send![operation, setShowsProgressPanel: NO];
+        }
+        let ns_window = webview.window().ns_window() as id;
+        let _: () = msg_send![operation, runOperationModalForWindow: ns_window
+                                         delegate: delegate
+                                         didRunSelector: sel!(printOperationDidRun:success:contextInfo:)
+                                         contextInfo: context];
+    }
+}
+
+/// WebView2 的 `window.print()` 本身可以弹出打印对话框，打印为 PDF 使用 `PrintToPdf`
+#[cfg(target_os = "windows")]
+pub fn print(webview: &WebView, mode: PrintMode, proxy: EventLoopProxy<UserEvent>) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_7;
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows::core::{Interface, HSTRING, PCWSTR};
+    use wry::webview::WebviewExtWindows;
+
+    match mode {
+        PrintMode::Dialog => {
+            let _ = webview.evaluate_script("(window.__pakeNativePrint || window.print)()");
+        }
+        PrintMode::Pdf => {
+            let path = pdf_path();
+            let ns_path = HSTRING::from(path.display().to_string());
+            let handler_path = path.clone();
+            let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, success| {
+                report_pdf(&proxy, &handler_path, result.is_ok() && success);
+                Ok(())
+            }));
+            let result = unsafe {
+                webview
+                    .controller()
+                    .CoreWebView2()
+                    .and_then(|core| core.cast::<ICoreWebView2_7>())
+                    .and_then(|core| core.PrintToPdf(PCWSTR(ns_path.as_ptr()), None, &handler))
+            };
+            if let Err(e) = result {
+                eprintln!("警告: 无法打印为 PDF {}: {:?}", path.display(), e);
+            }
+        }
+    }
+}
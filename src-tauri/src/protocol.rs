@@ -0,0 +1,126 @@
+//! `pake://app/*` 自定义协议，用于加载随应用打包的本地页面和资源
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+use wry::http::{header::CONTENT_TYPE, Request, Response};
+
+pub const PROTOCOL_NAME: &str = "pake";
+/// 本地资源目录名，与可执行文件放在一起
+const ASSETS_DIR_NAME: &str = "www";
+/// Pake 内置页面的路径前缀，这些页面编译进可执行文件
+const BUILTIN_PREFIX: &str = "__pake/";
+
+/// 内置页面的地址，Windows 上 WebView2 会把自定义协议映射为 `https://<协议名>.<host>`
+pub fn builtin_url(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("https://{}.app/{}{}", PROTOCOL_NAME, BUILTIN_PREFIX, name)
+    } else {
+        format!("{}://app/{}{}", PROTOCOL_NAME, BUILTIN_PREFIX, name)
+    }
+}
+
+fn builtin_page(name: &str) -> Option<&'static str> {
+    match name {
+        "debug-console.html" => Some(include_str!("pages/debug-console.html")),
+        "error.html" => Some(include_str!("pages/error.html")),
+        "waiting.html" => Some(include_str!("pages/waiting.html")),
+        _ => None,
+    }
+}
+
+/// 查找本地资源目录：先找相对路径（开发环境），再找可执行文件目录（安装后）
+pub fn assets_dir() -> PathBuf {
+    let relative = PathBuf::from(ASSETS_DIR_NAME);
+    if relative.is_dir() {
+        return relative;
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(ASSETS_DIR_NAME)))
+        .unwrap_or(relative)
+}
+
+/// `error_page` 为配置的自定义错误页文件，读取失败时使用内置错误页
+pub fn handle(
+    assets_dir: &Path,
+    error_page: Option<&Path>,
+    request: &Request<Vec<u8>>,
+) -> wry::Result<Response<Cow<'static, [u8]>>> {
+    let path = percent_decode_str(request.uri().path()).decode_utf8_lossy();
+    let path = path.trim_start_matches('/');
+    let path = if path.is_empty() { "index.html" } else { path };
+
+    if let Some(name) = path.strip_prefix(BUILTIN_PREFIX) {
+        if let Some(error_page) = error_page.filter(|_| name == "error.html") {
+            match std::fs::read(error_page) {
+                Ok(content) => return respond(200, "text/html", content),
+                Err(e) => eprintln!(
+                    "警告: 无法读取自定义错误页 {}: {:?}，使用内置错误页",
+                    error_page.display(),
+                    e
+                ),
+            }
+        }
+        return match builtin_page(name) {
+            Some(page) => respond(200, "text/html", page.as_bytes().to_vec()),
+            None => respond(404, "text/plain", b"Not Found".to_vec()),
+        };
+    }
+
+    // 只允许普通路径组成部分，拒绝 `..` 等越出资源目录的路径
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return respond(403, "text/plain", b"Forbidden".to_vec());
+    }
+
+    match std::fs::read(assets_dir.join(relative)) {
+        Ok(content) => respond(200, mime_type(relative), content),
+        Err(_) => {
+            eprintln!("警告: 本地资源不存在: {}", path);
+            respond(404, "text/plain", b"Not Found".to_vec())
+        }
+    }
+}
+
+fn respond(
+    status: u16,
+    content_type: &str,
+    body: Vec<u8>,
+) -> wry::Result<Response<Cow<'static, [u8]>>> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, content_type)
+        .body(Cow::Owned(body))
+        .map_err(Into::into)
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match ext.as_str() {
+        "html" | "htm" => "text/html",
+        "js" | "mjs" => "text/javascript",
+        "css" => "text/css",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "txt" => "text/plain",
+        "wasm" => "application/wasm",
+        "mp4" => "video/mp4",
+        "mp3" => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
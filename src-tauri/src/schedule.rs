@@ -0,0 +1,142 @@
+//! 按时间段切换显示的地址，适用于数字标牌
+use crate::config::ScheduleEntry;
+use crate::UserEvent;
+use chrono::{Local, NaiveTime};
+use std::time::Duration;
+use wry::application::event_loop::EventLoopProxy;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Slot {
+    from: NaiveTime,
+    to: NaiveTime,
+    url: String,
+}
+
+impl Slot {
+    /// 区间为 [from, to)，from 大于 to 时表示跨越午夜，如 22:00–06:00
+    fn contains(&self, now: NaiveTime) -> bool {
+        if self.from <= self.to {
+            self.from <= now && now < self.to
+        } else {
+            now >= self.from || now < self.to
+        }
+    }
+}
+
+pub struct Schedule {
+    slots: Vec<Slot>,
+}
+
+impl Schedule {
+    pub fn new(entries: &[ScheduleEntry]) -> Self {
+        let slots = entries
+            .iter()
+            .filter_map(|entry| {
+                let from = NaiveTime::parse_from_str(&entry.from, "%H:%M");
+                let to = NaiveTime::parse_from_str(&entry.to, "%H:%M");
+                match (from, to) {
+                    (Ok(from), Ok(to)) => Some(Slot {
+                        from,
+                        to,
+                        url: entry.url.clone(),
+                    }),
+                    _ => {
+                        eprintln!(
+                            "警告: 忽略无效的时间段 {}–{}，时间格式应为 HH:MM",
+                            entry.from, entry.to
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        Schedule { slots }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// 所有时间段的地址
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.slots.iter().map(|slot| slot.url.as_str())
+    }
+
+    /// 当前本地时间对应的地址，多个时间段重叠时取第一个
+    pub fn current_url(&self) -> Option<&str> {
+        self.active_slot(Local::now().time())
+            .map(|index| self.slots[index].url.as_str())
+    }
+
+    fn active_slot(&self, now: NaiveTime) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.contains(now))
+    }
+
+    /// 定时检查当前时间段，切换时导航到对应地址，无匹配时回到 `base_url`
+    pub fn spawn(self, base_url: String, proxy: EventLoopProxy<UserEvent>) {
+        std::thread::spawn(move || {
+            let mut active = self.active_slot(Local::now().time());
+            loop {
+                std::thread::sleep(CHECK_INTERVAL);
+                let next = self.active_slot(Local::now().time());
+                if next == active {
+                    continue;
+                }
+                active = next;
+                let url = match next {
+                    Some(index) => self.slots[index].url.clone(),
+                    None => base_url.clone(),
+                };
+                println!("按时间表切换页面: {}", url);
+                if proxy.send_event(UserEvent::Navigate(url)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule {
+        let entry = |from: &str, to: &str, url: &str| ScheduleEntry {
+            from: from.to_string(),
+            to: to.to_string(),
+            url: url.to_string(),
+        };
+        Schedule::new(&[
+            entry("08:00", "12:00", "https://menu.example.com/breakfast"),
+            entry("22:00", "06:00", "https://night.example.com/"),
+            entry("25:00", "26:00", "https://invalid.example.com/"),
+        ])
+    }
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn skips_invalid_entries_and_lists_the_rest() {
+        let urls: Vec<&str> = schedule().urls().collect();
+        assert_eq!(
+            urls,
+            [
+                "https://menu.example.com/breakfast",
+                "https://night.example.com/"
+            ]
+        );
+    }
+
+    #[test]
+    fn slots_include_the_start_and_wrap_past_midnight() {
+        let schedule = schedule();
+        assert_eq!(schedule.active_slot(at("08:00")), Some(0));
+        assert_eq!(schedule.active_slot(at("12:00")), None);
+        assert_eq!(schedule.active_slot(at("23:30")), Some(1));
+        assert_eq!(schedule.active_slot(at("05:59")), Some(1));
+        assert_eq!(schedule.active_slot(at("06:00")), None);
+    }
+}
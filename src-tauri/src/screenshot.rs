@@ -0,0 +1,144 @@
+//! 截取 WebView 当前显示的内容并保存为 PNG，各平台的截图接口都是异步的
+use crate::UserEvent;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wry::application::event_loop::EventLoopProxy;
+use wry::webview::WebView;
+
+/// 数据目录下只保留最近一次的截图
+pub const MONITOR_SCREENSHOT_FILE: &str = "pake-monitor.png";
+
+/// 定时通知事件循环截图
+pub fn spawn_monitor(interval: Duration, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+        if proxy.send_event(UserEvent::MonitorScreenshot).is_err() {
+            break;
+        }
+    });
+}
+
+/// 先写入临时文件再重命名，避免读取方拿到写了一半的图片
+fn write_png(path: &Path, bytes: &[u8]) {
+    let tmp = path.with_extension("png.tmp");
+    let result = std::fs::write(&tmp, bytes).and_then(|_| std::fs::rename(&tmp, path));
+    if let Err(e) = result {
+        eprintln!("警告: 无法保存截图 {}: {:?}", path.display(), e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn capture(webview: &WebView, path: PathBuf) {
+    use webkit2gtk::{SnapshotOptions, SnapshotRegion, WebViewExt};
+    use wry::webview::WebviewExtUnix;
+
+    webview.webview().snapshot(
+        SnapshotRegion::Visible,
+        SnapshotOptions::NONE,
+        None::<&gio::Cancellable>,
+        move |result| {
+            let png = result
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|surface| {
+                    cairo::ImageSurface::try_from(surface).map_err(|_| "不是图像表面".to_string())
+                })
+                .and_then(|surface| {
+                    let mut png = Vec::new();
+                    surface
+                        .write_to_png(&mut png)
+                        .map(|_| png)
+                        .map_err(|e| format!("{:?}", e))
+                });
+            match png {
+                Ok(png) => write_png(&path, &png),
+                Err(e) => eprintln!("警告: 截图失败: {}", e),
+            }
+        },
+    );
+}
+
+#[cfg(target_os = "macos")]
+pub fn capture(webview: &WebView, path: PathBuf) {
+    use block::ConcreteBlock;
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+    use wry::webview::WebviewExtMacOS;
+
+    // NSBitmapImageFileTypePNG
+    const PNG_FILE_TYPE: u64 = 4;
+
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let configuration: id = msg_send![class!(WKSnapshotConfiguration), new];
+        let handler = ConcreteBlock::new(move |image: id, _error: id| {
+            if image == nil {
+                eprintln!("警告: 截图失败");
+                return;
+            }
+            let tiff: id = msg_send![image, TIFFRepresentation];
+            let bitmap: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+            let properties: id = msg_send![class!(NSDictionary), dictionary];
+            let png: id = msg_send![bitmap, representationUsingType: PNG_FILE_TYPE
+                                            properties: properties];
+            if png == nil {
+                eprintln!("警告: 截图转换为 PNG 失败");
+                return;
+            }
+            let bytes: *const u8 = msg_send![png, bytes];
+            let length: usize = msg_send![png, length];
+            write_png(&path, std::slice::from_raw_parts(bytes, length));
+        })
+        .copy();
+        let _: () = msg_send![wk_webview, takeSnapshotWithConfiguration: configuration
+                                          completionHandler: &*handler];
+        let _: () = msg_send![configuration, release];
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn capture(webview: &WebView, path: PathBuf) {
+    use webview2_com::CapturePreviewCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG;
+    use windows::Win32::System::Com::STREAM_SEEK_SET;
+    use windows::Win32::UI::Shell::SHCreateMemStream;
+    use wry::webview::WebviewExtWindows;
+
+    let result = unsafe {
+        let stream = match SHCreateMemStream(None) {
+            Some(stream) => stream,
+            None => {
+                eprintln!("警告: 无法创建截图内存流");
+                return;
+            }
+        };
+        let output = stream.clone();
+        let handler = CapturePreviewCompletedHandler::create(Box::new(move |result| {
+            result?;
+            output.Seek(0, STREAM_SEEK_SET)?;
+            let mut png = Vec::new();
+            let mut chunk = [0u8; 64 * 1024];
+            loop {
+                let mut read = 0u32;
+                output
+                    .Read(chunk.as_mut_ptr() as _, chunk.len() as u32, &mut read)
+                    .ok()?;
+                if read == 0 {
+                    break;
+                }
+                png.extend_from_slice(&chunk[..read as usize]);
+            }
+            write_png(&path, &png);
+            Ok(())
+        }));
+        webview.controller().CoreWebView2().and_then(|core| {
+            core.CapturePreview(
+                COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG,
+                &stream,
+                &handler,
+            )
+        })
+    };
+    if let Err(e) = result {
+        eprintln!("警告: 截图失败: {:?}", e);
+    }
+}
@@ -0,0 +1,134 @@
+//! `dialog_mode` 为 `native` 时用系统对话框显示页面的 alert/confirm/prompt
+//!
+//! wry 为 WKWebView 设置的 UI 代理没有实现 JS 对话框相关的方法，macOS 上这些调用会被静默忽略，
+//! 这里给代理补上对应方法，用 NSAlert 显示；prompt 在对话框中附带输入框，把输入内容返回给页面。
+//! WebView2 和 WebKitGTK 自带的对话框已经是原生样式，保持不变
+use wry::webview::WebView;
+
+#[cfg(target_os = "macos")]
+pub fn install(webview: &WebView) {
+    use cocoa::base::{id, nil};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{class_addMethod, object_getClass, Class, Imp, Object, Sel, NO};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::os::raw::c_char;
+    use wry::webview::WebviewExtMacOS;
+
+    extern "C" fn run_alert(_: &Object, _: Sel, _: id, message: id, _: id, handler: id) {
+        unsafe {
+            show_alert(message, &["OK"], None);
+            let handler = &*(handler as *const block::Block<(), ()>);
+            handler.call(());
+        }
+    }
+
+    extern "C" fn run_confirm(_: &Object, _: Sel, _: id, message: id, _: id, handler: id) {
+        unsafe {
+            let confirmed = show_alert(message, &["OK", "Cancel"], None) == FIRST_BUTTON;
+            let handler = &*(handler as *const block::Block<(objc::runtime::BOOL,), ()>);
+            handler.call((confirmed as objc::runtime::BOOL,));
+        }
+    }
+
+    extern "C" fn run_prompt(
+        _: &Object,
+        _: Sel,
+        _: id,
+        message: id,
+        default_text: id,
+        _: id,
+        handler: id,
+    ) {
+        unsafe {
+            let field: id = msg_send![class!(NSTextField), alloc];
+            let field: id = msg_send![field, initWithFrame: cocoa::foundation::NSRect::new(
+                cocoa::foundation::NSPoint::new(0.0, 0.0),
+                cocoa::foundation::NSSize::new(260.0, 24.0),
+            )];
+            if default_text != nil {
+                let _: () = msg_send![field, setStringValue: default_text];
+            }
+            let response = show_alert(message, &["OK", "Cancel"], Some(field));
+            // 取消时返回 nil，页面得到 null
+            let result: id = if response == FIRST_BUTTON {
+                msg_send![field, stringValue]
+            } else {
+                nil
+            };
+            let handler = &*(handler as *const block::Block<(id,), ()>);
+            handler.call((result,));
+            let _: () = msg_send![field, release];
+        }
+    }
+
+    /// NSAlertFirstButtonReturn
+    const FIRST_BUTTON: isize = 1000;
+
+    unsafe fn ns_string(text: &str) -> id {
+        use cocoa::foundation::NSString;
+        let string = NSString::alloc(nil).init_str(text);
+        msg_send![string, autorelease]
+    }
+
+    unsafe fn show_alert(message: id, buttons: &[&str], accessory: Option<id>) -> isize {
+        let alert: id = msg_send![class!(NSAlert), new];
+        let _: () = msg_send![alert, setMessageText: message];
+        for button in buttons {
+            let _: id = msg_send![alert, addButtonWithTitle: ns_string(button)];
+        }
+        if let Some(accessory) = accessory {
+            let _: () = msg_send![alert, setAccessoryView: accessory];
+            let window: id = msg_send![alert, window];
+            let _: () = msg_send![window, setInitialFirstResponder: accessory];
+        }
+        let response: isize = msg_send![alert, runModal];
+        let _: () = msg_send![alert, release];
+        response
+    }
+
+    let methods: [(Sel, Imp, &[u8]); 3] = unsafe {
+        [
+            (
+                sel!(webView:runJavaScriptAlertPanelWithMessage:initiatedByFrame:completionHandler:),
+                std::mem::transmute(run_alert as extern "C" fn(&Object, Sel, id, id, id, id)),
+                b"v@:@@@@?\0",
+            ),
+            (
+                sel!(webView:runJavaScriptConfirmPanelWithMessage:initiatedByFrame:completionHandler:),
+                std::mem::transmute(run_confirm as extern "C" fn(&Object, Sel, id, id, id, id)),
+                b"v@:@@@@?\0",
+            ),
+            (
+                sel!(webView:runJavaScriptTextInputPanelWithPrompt:defaultText:initiatedByFrame:completionHandler:),
+                std::mem::transmute(run_prompt as extern "C" fn(&Object, Sel, id, id, id, id, id)),
+                b"v@:@@@@@?\0",
+            ),
+        ]
+    };
+
+    unsafe {
+        let wk_webview: id = webview.webview();
+        let mut delegate: id = msg_send![wk_webview, UIDelegate];
+        if delegate == nil {
+            // WKWebView 只弱引用 UI 代理，创建的代理随应用一直存在，不释放
+            let class = match ClassDecl::new("PakeScriptDialogDelegate", class!(NSObject)) {
+                Some(decl) => decl.register(),
+                None => class!(PakeScriptDialogDelegate),
+            };
+            delegate = msg_send![class, new];
+            let _: () = msg_send![wk_webview, setUIDelegate: delegate];
+        }
+        let class = object_getClass(delegate as *const Object) as *mut Class;
+        for (sel, imp, types) in methods {
+            if class_addMethod(class, sel, imp, types.as_ptr() as *const c_char) == NO {
+                eprintln!("警告: UI 代理已实现 {}，保留原有的对话框处理", sel.name());
+            }
+        }
+    }
+    println!("JS 对话框使用系统对话框 (NSAlert)");
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn install(_webview: &WebView) {
+    println!("JS 对话框使用 WebView 自带的系统对话框");
+}
@@ -0,0 +1,390 @@
+//! `export_settings` / `import_settings`：把当前配置和 Pake 自己保存的状态导出为一个 JSON 文件，
+//! 或从这样的文件恢复，方便迁移到其他电脑或分享调整好的配置
+//!
+//! 导入的配置只把 `IMPORTABLE_FIELDS` 中与打包的 pake.json 不同的字段保存在数据目录的 `pake-config.json` 中，
+//! 启动时叠加在打包的配置上，应用更新后未导入过的字段仍跟随新版本。
+//! 只有外观和窗口行为这类个人偏好可以导入；安全开关、引擎参数、网络和文件路径等字段始终以打包的配置为准，
+//! 分享的设置文件无法借此放宽打包应用的限制。`auto_login` 含有密码，不会导出
+use crate::config::PakeConfig;
+use crate::download;
+use crate::state::{
+    HostZoom, PinnedHome, WindowSession, WindowState, HOME_FILE, WINDOWS_FILE, WINDOW_STATE_FILE,
+    ZOOM_FILE,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const IMPORTED_CONFIG_FILE: &str = "pake-config.json";
+/// 导出文件的格式版本，格式不兼容地修改时递增
+const SETTINGS_VERSION: u32 = 1;
+/// 可以从导入文件覆盖的个人偏好字段，其余字段始终以打包的配置为准
+///
+/// `shortcuts` 不在其中：快捷键按下时会附带 `secure_ipc` 令牌发送任意命令
+const IMPORTABLE_FIELDS: &[&str] = &[
+    "smooth_scrolling",
+    "scroll_multiplier",
+    "remember_window_state",
+    "close_to_tray",
+    "minimize_to_tray",
+    "window_level",
+    "beforeunload",
+    "high_contrast",
+    "force_theme",
+    "resize_increment_width",
+    "resize_increment_height",
+    "auto_focus_input",
+    "drag_opacity",
+    "suppress_download_toast",
+    "fade_in_ms",
+    "title_template",
+    "middle_click_behavior",
+    "close_button_behavior",
+    "keyboard_scroll",
+    "scroll_amount",
+    "initial_zoom",
+    "per_host_zoom",
+    "print_mode",
+    "window_width",
+    "window_height",
+    "reload_on_wake",
+    "start_monitor",
+    "lock_zoom",
+    "sync_favicon",
+    "rounded_corners",
+    "blur_behavior",
+    "show_toolbar",
+    "toolbar_buttons",
+    "prevent_sleep",
+    "show_on_cursor_monitor",
+    "global_hotkey",
+    "rtl",
+    "confirm_quit",
+    "titlebar_color",
+    "follow_theme_color",
+];
+
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    version: u32,
+    #[serde(default)]
+    exported_at: String,
+    config: Value,
+    /// 导出时省略的敏感字段，导入后沿用打包的配置
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    omitted: Vec<String>,
+    /// 状态文件名到文件内容的映射
+    #[serde(default)]
+    state: BTreeMap<String, Value>,
+}
+
+/// 导出的状态文件及其内容的校验方式
+const STATE_FILES: [(&str, fn(&Value) -> bool); 4] = [
+    (WINDOW_STATE_FILE, is_valid::<WindowState>),
+    (HOME_FILE, is_valid::<PinnedHome>),
+    (ZOOM_FILE, is_valid::<HostZoom>),
+    (WINDOWS_FILE, is_valid::<WindowSession>),
+];
+
+fn is_valid<T: DeserializeOwned>(value: &Value) -> bool {
+    T::deserialize(value).is_ok()
+}
+
+/// 写入下载目录，返回导出文件的路径
+pub fn export(config: &PakeConfig, data_dir: &Path) -> Result<PathBuf, String> {
+    let now = chrono::Local::now();
+    let bundle = bundle(config, data_dir, now.to_rfc3339())?;
+    let json = serde_json::to_vec_pretty(&bundle).map_err(|e| format!("{:?}", e))?;
+    let file_name = format!("pake-settings-{}.json", now.format("%Y%m%d-%H%M%S"));
+    download::save_bytes(&file_name, &json)
+}
+
+fn bundle(
+    config: &PakeConfig,
+    data_dir: &Path,
+    exported_at: String,
+) -> Result<SettingsBundle, String> {
+    let state = STATE_FILES
+        .iter()
+        .filter_map(|(file, _)| {
+            let content = std::fs::read_to_string(data_dir.join(file)).ok()?;
+            let value = serde_json::from_str(&content).ok()?;
+            Some((file.to_string(), value))
+        })
+        .collect();
+    Ok(SettingsBundle {
+        version: SETTINGS_VERSION,
+        exported_at,
+        config: serde_json::to_value(config).map_err(|e| format!("{:?}", e))?,
+        omitted: config
+            .auto_login
+            .as_ref()
+            .map(|_| vec!["auto_login".to_string()])
+            .unwrap_or_default(),
+        state,
+    })
+}
+
+/// 校验并导入设置文件：配置无法解析时整个文件都不导入；未知字段和未知的状态文件忽略，
+/// 内容无效的状态文件跳过。`packaged` 为打包的配置，只保存与它不同的字段。返回导入的状态文件名
+pub fn import(path: &Path, data_dir: &Path, packaged: &PakeConfig) -> Result<Vec<String>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{:?}", e))?;
+    let bundle: SettingsBundle =
+        serde_json::from_str(&content).map_err(|e| format!("不是有效的设置文件: {}", e))?;
+    if bundle.version > SETTINGS_VERSION {
+        return Err(format!("不支持的设置文件版本: {}", bundle.version));
+    }
+    let config = PakeConfig::deserialize(&bundle.config).map_err(|e| format!("配置无效: {}", e))?;
+
+    let mut imported = Vec::new();
+    for (file, value) in &bundle.state {
+        match STATE_FILES.iter().find(|(name, _)| *name == file.as_str()) {
+            Some((_, is_valid)) if is_valid(value) => {
+                write_json(&data_dir.join(file), value)?;
+                imported.push(file.clone());
+            }
+            Some(_) => eprintln!("警告: 设置文件中的 {} 内容无效，跳过", file),
+            None => println!("忽略未知的状态文件: {}", file),
+        }
+    }
+    let overlay = config_overlay(&config, packaged)?;
+    write_json(
+        &data_dir.join(IMPORTED_CONFIG_FILE),
+        &Value::Object(overlay),
+    )?;
+    Ok(imported)
+}
+
+/// 重新序列化导入的配置（只保留能识别的字段），只保留 `IMPORTABLE_FIELDS` 中与打包配置不同的字段
+fn config_overlay(
+    config: &PakeConfig,
+    packaged: &PakeConfig,
+) -> Result<Map<String, Value>, String> {
+    let to_object = |config: &PakeConfig| match serde_json::to_value(config) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err("配置不是 JSON 对象".to_string()),
+        Err(e) => Err(format!("{:?}", e)),
+    };
+    let packaged = to_object(packaged)?;
+    Ok(to_object(config)?
+        .into_iter()
+        .filter(|(key, value)| {
+            IMPORTABLE_FIELDS.contains(&key.as_str()) && packaged.get(key) != Some(value)
+        })
+        .collect())
+}
+
+/// 把导入的配置叠加到打包的配置上，没有导入过或文件损坏时返回 None；
+/// 文件可能被手动修改，叠加时同样只接受 `IMPORTABLE_FIELDS`
+pub fn load_imported_config(data_dir: &Path, packaged: &PakeConfig) -> Option<PakeConfig> {
+    let path = data_dir.join(IMPORTED_CONFIG_FILE);
+    let content = std::fs::read_to_string(&path).ok()?;
+    let merged = serde_json::from_str::<Map<String, Value>>(&content).and_then(|overlay| {
+        let mut merged = match serde_json::to_value(packaged)? {
+            Value::Object(object) => object,
+            _ => Map::new(),
+        };
+        merged.extend(
+            overlay
+                .into_iter()
+                .filter(|(key, _)| IMPORTABLE_FIELDS.contains(&key.as_str())),
+        );
+        PakeConfig::deserialize(Value::Object(merged))
+    });
+    match merged {
+        Ok(config) => {
+            println!("使用导入的配置: {}", path.display());
+            // auto_login 不参与序列化，合并时会丢失
+            Some(PakeConfig {
+                auto_login: packaged.auto_login.clone(),
+                ..config
+            })
+        }
+        Err(e) => {
+            eprintln!("警告: 导入的配置文件已损坏，使用打包的配置: {:?}", e);
+            None
+        }
+    }
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    let json = serde_json::to_string(value).map_err(|e| format!("{:?}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("无法写入 {}: {:?}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AutoLogin;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pake-settings-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_bundle(dir: &Path, bundle: Value) -> PathBuf {
+        let path = dir.join("bundle.json");
+        std::fs::write(&path, bundle.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn rejects_invalid_config_without_writing_anything() {
+        let dir = temp_dir("invalid");
+        let path = write_bundle(
+            &dir,
+            json!({
+                "version": 1,
+                "config": { "disable_ipc": "yes" },
+                "state": { (HOME_FILE): { "url": "https://example.com" } },
+            }),
+        );
+        assert!(import(&path, &dir, &PakeConfig::default()).is_err());
+        assert!(!dir.join(IMPORTED_CONFIG_FILE).exists());
+        assert!(!dir.join(HOME_FILE).exists());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let dir = temp_dir("version");
+        let path = write_bundle(
+            &dir,
+            json!({ "version": SETTINGS_VERSION + 1, "config": {} }),
+        );
+        assert!(import(&path, &dir, &PakeConfig::default()).is_err());
+    }
+
+    #[test]
+    fn ignores_unknown_fields_and_state_files() {
+        let dir = temp_dir("unknown");
+        let path = write_bundle(
+            &dir,
+            json!({
+                "version": 1,
+                "config": { "lock_zoom": true, "no_such_option": 1 },
+                "state": {
+                    (HOME_FILE): { "url": "https://example.com/home" },
+                    (ZOOM_FILE): "not a zoom map",
+                    "unknown.json": { "a": 1 },
+                },
+            }),
+        );
+        let imported = import(&path, &dir, &PakeConfig::default()).unwrap();
+        assert_eq!(imported, vec![HOME_FILE.to_string()]);
+        assert!(!dir.join(ZOOM_FILE).exists());
+        assert!(!dir.join("unknown.json").exists());
+
+        let saved: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(IMPORTED_CONFIG_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(saved, json!({ "lock_zoom": true }));
+    }
+
+    #[test]
+    fn imported_config_overlays_the_packaged_config() {
+        let dir = temp_dir("overlay");
+        let path = write_bundle(
+            &dir,
+            json!({
+                "version": 1,
+                "config": { "lock_zoom": true, "data_dir": "/elsewhere" },
+            }),
+        );
+        import(&path, &dir, &PakeConfig::default()).unwrap();
+
+        // 应用更新后打包的配置改变，未导入过的字段跟随新版本
+        let packaged = PakeConfig {
+            disable_ipc: true,
+            data_dir: Some(PathBuf::from("/packaged")),
+            auto_login: Some(AutoLogin::default()),
+            ..PakeConfig::default()
+        };
+        let config = load_imported_config(&dir, &packaged).unwrap();
+        assert!(config.lock_zoom);
+        assert!(config.disable_ipc);
+        assert_eq!(config.data_dir, Some(PathBuf::from("/packaged")));
+        assert!(config.auto_login.is_some());
+    }
+
+    #[test]
+    fn security_and_engine_fields_are_not_imported() {
+        let dir = temp_dir("security");
+        let path = write_bundle(
+            &dir,
+            json!({
+                "version": 1,
+                "config": {
+                    "show_toolbar": true,
+                    "disable_ipc": false,
+                    "secure_ipc": false,
+                    "webview2_additional_args": "--disable-web-security",
+                    "hosts_overrides": { "bank.example": "203.0.113.7" },
+                    "navigation_allowlist": [],
+                    "external_schemes": ["file"],
+                    "dev_scripts_dir": "/",
+                    "health_port": 8080,
+                    "localhost_secure_context": true,
+                    "shortcuts": { "open_browser:https://evil.test": "CmdOrCtrl+P" },
+                },
+            }),
+        );
+        let packaged = PakeConfig {
+            secure_ipc: true,
+            disable_ipc: true,
+            navigation_allowlist: vec!["example.com".to_string()],
+            ..PakeConfig::default()
+        };
+        import(&path, &dir, &packaged).unwrap();
+        let saved: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(IMPORTED_CONFIG_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(saved, json!({ "show_toolbar": true }));
+
+        // 手动写入数据目录的字段同样被忽略
+        write_json(
+            &dir.join(IMPORTED_CONFIG_FILE),
+            &json!({ "show_toolbar": true, "secure_ipc": false, "health_port": 8080 }),
+        )
+        .unwrap();
+        let config = load_imported_config(&dir, &packaged).unwrap();
+        assert!(config.show_toolbar);
+        assert!(config.secure_ipc);
+        assert!(config.disable_ipc);
+        assert_eq!(config.navigation_allowlist, vec!["example.com".to_string()]);
+        assert_eq!(config.health_port, None);
+        assert!(config.webview2_additional_args.is_none());
+        assert!(config.hosts_overrides.is_empty());
+        assert!(config.shortcuts.is_empty());
+    }
+
+    #[test]
+    fn importable_fields_exist_in_the_config() {
+        let fields = match serde_json::to_value(PakeConfig::default()).unwrap() {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        };
+        for field in IMPORTABLE_FIELDS {
+            assert!(fields.contains_key(*field), "{}", field);
+        }
+    }
+
+    #[test]
+    fn export_marks_auto_login_as_omitted() {
+        let dir = temp_dir("export");
+        std::fs::write(dir.join(HOME_FILE), r#"{"url":"https://example.com"}"#).unwrap();
+        let config = PakeConfig {
+            auto_login: Some(AutoLogin::default()),
+            ..PakeConfig::default()
+        };
+        let value = serde_json::to_value(bundle(&config, &dir, String::new()).unwrap()).unwrap();
+        assert!(value["config"].get("auto_login").is_none());
+        assert_eq!(value["omitted"], json!(["auto_login"]));
+        assert_eq!(value["state"][HOME_FILE]["url"], "https://example.com");
+    }
+}
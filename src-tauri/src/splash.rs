@@ -0,0 +1,64 @@
+//! 页面加载完成前显示的启动画面
+use crate::config::SplashFit;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::ImageFormat;
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+
+/// 启动画面底部显示的应用名称、版本和构建信息，便于排查问题时确认版本
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    name: String,
+    version: &'static str,
+    /// 构建时通过 `PAKE_BUILD_INFO` 环境变量传入，如提交哈希
+    build: Option<&'static str>,
+}
+
+impl BuildInfo {
+    pub fn new(product_name: &str) -> Self {
+        BuildInfo {
+            name: product_name.to_string(),
+            version: env!("CARGO_PKG_VERSION"),
+            build: option_env!("PAKE_BUILD_INFO").filter(|info| !info.trim().is_empty()),
+        }
+    }
+}
+
+/// 生成启动画面的初始化脚本，由 pake.js 渲染；没有图片或图片加载失败时显示默认的加载动画
+pub fn splash_script(
+    image_path: Option<&Path>,
+    fit: SplashFit,
+    build: Option<BuildInfo>,
+) -> String {
+    let image = image_path.and_then(|image_path| match load_image_data_url(image_path) {
+        Ok(data_url) => Some(data_url),
+        Err(e) => {
+            eprintln!(
+                "警告: 无法加载启动图片 {}: {}，使用默认启动画面",
+                image_path.display(),
+                e
+            );
+            None
+        }
+    });
+    let splash = json!({ "image": image, "fit": fit, "build": build });
+    format!("window.__PAKE_SPLASH__ = {};", splash)
+}
+
+fn load_image_data_url(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{:?}", e))?;
+    let format = image::guess_format(&bytes).map_err(|e| format!("{:?}", e))?;
+    // 确认图片可以正常解码，避免页面中显示破图
+    image::load_from_memory_with_format(&bytes, format).map_err(|e| format!("{:?}", e))?;
+    let mime = match format {
+        ImageFormat::Png => "image/png",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::WebP => "image/webp",
+        ImageFormat::Bmp => "image/bmp",
+        ImageFormat::Ico => "image/x-icon",
+        other => return Err(format!("不支持的图片格式: {:?}", other)),
+    };
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
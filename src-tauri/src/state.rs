@@ -0,0 +1,261 @@
+//! Pake 自身需要持久化的状态，保存在数据目录下的 `pake-*.json` 文件中
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use wry::application::window::Window;
+
+pub const WINDOW_STATE_FILE: &str = "pake-window-state.json";
+pub const HOME_FILE: &str = "pake-home.json";
+pub const ZOOM_FILE: &str = "pake-zoom.json";
+pub const WINDOWS_FILE: &str = "pake-windows.json";
+
+/// 窗口状态，位置和大小记录的是非最大化、非全屏时的物理像素值
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+}
+
+impl WindowState {
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(data_dir.join(WINDOW_STATE_FILE)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                eprintln!("警告: 窗口状态文件已损坏，忽略: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) {
+        let result = serde_json::to_string(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(data_dir.join(WINDOW_STATE_FILE), json));
+        if let Err(e) = result {
+            eprintln!("警告: 无法保存窗口状态: {:?}", e);
+        }
+    }
+
+    /// 是否记录过有效的窗口大小
+    pub fn has_geometry(&self) -> bool {
+        self.width > 0 && self.height > 0
+    }
+
+    /// 记录窗口当前的位置和大小，最大化或全屏时保留之前的值，以便恢复后还原
+    pub fn track_geometry(&mut self, window: &Window) {
+        if window.is_maximized() || window.fullscreen().is_some() {
+            return;
+        }
+        if let Ok(position) = window.outer_position() {
+            self.x = position.x;
+            self.y = position.y;
+        }
+        let size = window.inner_size();
+        self.width = size.width;
+        self.height = size.height;
+    }
+
+    /// 记录窗口当前的最大化和全屏状态
+    pub fn track_flags(&mut self, window: &Window) {
+        self.maximized = window.is_maximized();
+        self.fullscreen = window.fullscreen().is_some();
+    }
+}
+
+/// 通过 `set_home` 固定的首页，优先于配置中的启动地址用于 `go_home`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedHome {
+    pub url: String,
+}
+
+impl PinnedHome {
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(data_dir.join(HOME_FILE)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(home) => Some(home),
+            Err(e) => {
+                eprintln!("警告: 首页文件已损坏，忽略: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) {
+        let result = serde_json::to_string(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(data_dir.join(HOME_FILE), json));
+        if let Err(e) = result {
+            eprintln!("警告: 无法保存首页: {:?}", e);
+        }
+    }
+
+    pub fn clear(data_dir: &Path) {
+        let path = data_dir.join(HOME_FILE);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("警告: 无法删除首页文件: {:?}", e);
+            }
+        }
+    }
+}
+
+/// 按域名记录的缩放比例，`per_host_zoom` 开启时使用
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostZoom(BTreeMap<String, f64>);
+
+impl HostZoom {
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read_to_string(data_dir.join(ZOOM_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) {
+        let result = serde_json::to_string(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(data_dir.join(ZOOM_FILE), json));
+        if let Err(e) = result {
+            eprintln!("警告: 无法保存缩放比例: {:?}", e);
+        }
+    }
+
+    pub fn get(&self, host: &str) -> Option<f64> {
+        self.0.get(host).copied()
+    }
+
+    pub fn set(&mut self, host: &str, zoom: f64) {
+        self.0.insert(host.to_string(), zoom);
+    }
+}
+
+/// 退出时打开的一个窗口，`restore_windows` 开启时用于下次启动重新打开
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedWindow {
+    pub url: String,
+    pub geometry: WindowState,
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+}
+
+fn default_zoom() -> f64 {
+    1.0
+}
+
+impl SavedWindow {
+    /// 记录窗口当前的地址、位置、大小和缩放，最大化或全屏时只记录状态，不记录大小
+    pub fn capture(window: &Window, url: String, zoom: f64) -> Self {
+        let mut geometry = WindowState::default();
+        geometry.track_geometry(window);
+        geometry.track_flags(window);
+        SavedWindow {
+            url,
+            geometry,
+            zoom,
+        }
+    }
+}
+
+/// 退出时打开的所有窗口，第一个为主窗口，其余为通过 `open_window` 打开的窗口
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowSession {
+    pub windows: Vec<SavedWindow>,
+}
+
+impl WindowSession {
+    pub fn load(data_dir: &Path) -> Self {
+        let content = match std::fs::read_to_string(data_dir.join(WINDOWS_FILE)) {
+            Ok(content) => content,
+            Err(_) => return WindowSession::default(),
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("警告: 窗口列表文件已损坏，忽略: {:?}", e);
+            WindowSession::default()
+        })
+    }
+
+    pub fn save(&self, data_dir: &Path) {
+        let result = serde_json::to_string(self)
+            .map_err(std::io::Error::from)
+            .and_then(|json| std::fs::write(data_dir.join(WINDOWS_FILE), json));
+        if let Err(e) = result {
+            eprintln!("警告: 无法保存窗口列表: {:?}", e);
+        }
+    }
+}
+
+/// 删除 Pake 自己保存的状态文件（窗口位置、窗口列表、首页、缩放、启动记录、导入的配置），不影响 WebView 的 Cookie 和缓存
+///
+/// 返回已删除的文件
+pub fn reset_all(data_dir: &Path) -> Vec<PathBuf> {
+    let files = [
+        WINDOW_STATE_FILE,
+        HOME_FILE,
+        ZOOM_FILE,
+        WINDOWS_FILE,
+        crate::launch_guard::LAUNCHES_FILE,
+        crate::settings::IMPORTED_CONFIG_FILE,
+    ];
+    let mut removed = Vec::new();
+    for file in files {
+        let path = data_dir.join(file);
+        if !path.exists() {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(_) => {
+                println!("已删除状态文件: {}", path.display());
+                removed.push(path);
+            }
+            Err(e) => eprintln!("警告: 无法删除状态文件 {}: {:?}", path.display(), e),
+        }
+    }
+    if removed.is_empty() {
+        println!("没有需要清除的状态文件");
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pake-state-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pinned_home_round_trips_and_clears() {
+        let dir = temp_dir("home");
+        assert!(PinnedHome::load(&dir).is_none());
+        PinnedHome {
+            url: "https://example.com/dashboard".to_string(),
+        }
+        .save(&dir);
+        assert_eq!(
+            PinnedHome::load(&dir).map(|home| home.url),
+            Some("https://example.com/dashboard".to_string())
+        );
+        PinnedHome::clear(&dir);
+        assert!(PinnedHome::load(&dir).is_none());
+        // 没有首页文件时清除也不应出错
+        PinnedHome::clear(&dir);
+    }
+
+    #[test]
+    fn corrupt_pinned_home_is_ignored() {
+        let dir = temp_dir("corrupt-home");
+        std::fs::write(dir.join(HOME_FILE), "{ not json").unwrap();
+        assert!(PinnedHome::load(&dir).is_none());
+    }
+}
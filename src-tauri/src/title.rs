@@ -0,0 +1,82 @@
+//! 按 `title_template` 生成窗口标题
+use url::Url;
+
+/// 替换模板中的 `{title}`、`{url}`、`{host}`，页面标题为空时用应用名代替
+pub fn format_title(template: &str, page_title: &str, url: &str, product_name: &str) -> String {
+    let page_title = page_title.trim();
+    let page_title = if page_title.is_empty() {
+        product_name
+    } else {
+        page_title
+    };
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+        .unwrap_or_default();
+    // 逐段替换，页面标题中出现的 `{url}` 等文字保持原样
+    let placeholders = [("{title}", page_title), ("{url}", url), ("{host}", host.as_str())];
+    let mut title = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        title.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                title.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                title.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    title.push_str(rest);
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_title_url_and_host() {
+        assert_eq!(
+            format_title(
+                "{title} — {host}",
+                " Inbox ",
+                "https://mail.example.com/u/0",
+                "Mail"
+            ),
+            "Inbox — mail.example.com"
+        );
+        assert_eq!(
+            format_title("{url}", "Inbox", "https://example.com/a", "Mail"),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn empty_page_title_uses_the_product_name() {
+        assert_eq!(
+            format_title("{title} | Acme", "  ", "about:blank", "Mail"),
+            "Mail | Acme"
+        );
+    }
+
+    #[test]
+    fn placeholders_in_the_page_title_are_not_expanded() {
+        assert_eq!(
+            format_title(
+                "{title} ({host})",
+                "{url} {x}",
+                "https://example.com/",
+                "Mail"
+            ),
+            "{url} {x} (example.com)"
+        );
+    }
+}
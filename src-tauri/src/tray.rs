@@ -0,0 +1,60 @@
+//! 系统托盘，启用 `close_to_tray` 或 `minimize_to_tray` 时创建
+use wry::application::{
+    event_loop::EventLoopWindowTarget,
+    menu::{ContextMenu, MenuId, MenuItemAttributes},
+    system_tray::{SystemTray, SystemTrayBuilder},
+    window::{Icon, Window},
+};
+
+pub struct Tray {
+    // 托盘对象被释放后图标会消失，需要一直持有
+    _tray: SystemTray,
+    pub show_id: MenuId,
+    pub quit_id: MenuId,
+}
+
+impl Tray {
+    pub fn set_icon(&mut self, icon: Icon) {
+        self._tray.set_icon(icon);
+    }
+}
+
+pub fn build_tray<T>(event_loop: &EventLoopWindowTarget<T>) -> Option<Tray> {
+    let icon = match bundled_icon() {
+        Ok(icon) => icon,
+        Err(e) => {
+            eprintln!("警告: 无法加载托盘图标: {}，不创建托盘", e);
+            return None;
+        }
+    };
+    let mut menu = ContextMenu::new();
+    let show_id = menu.add_item(MenuItemAttributes::new("Show")).id();
+    let quit_id = menu.add_item(MenuItemAttributes::new("Quit")).id();
+    match SystemTrayBuilder::new(icon, Some(menu)).build(event_loop) {
+        Ok(tray) => Some(Tray {
+            _tray: tray,
+            show_id,
+            quit_id,
+        }),
+        Err(e) => {
+            eprintln!("警告: 无法创建系统托盘: {:?}", e);
+            None
+        }
+    }
+}
+
+/// 从托盘恢复窗口
+pub fn restore_window(window: &Window) {
+    window.set_visible(true);
+    window.set_minimized(false);
+    window.set_focus();
+}
+
+/// 打包时附带的应用图标
+pub fn bundled_icon() -> Result<Icon, String> {
+    let image = image::load_from_memory(include_bytes!("../png/icon_32.ico"))
+        .map_err(|e| format!("{:?}", e))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Icon::from_rgba(image.into_raw(), width, height).map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,643 @@
+//! tao 未提供的各平台窗口能力
+use crate::UserEvent;
+use std::time::{Duration, Instant};
+use wry::application::{
+    dpi::{PhysicalPosition, PhysicalSize, Position},
+    event_loop::EventLoopProxy,
+    monitor::MonitorHandle,
+    window::Window,
+};
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use wry::application::dpi::LogicalPosition;
+
+const FADE_FRAME: Duration = Duration::from_millis(16);
+
+/// 将窗口固定在普通窗口之下（桌面层），用于天气、时钟等桌面小组件
+#[cfg(target_os = "macos")]
+pub fn set_always_below(window: &Window) {
+    use cocoa::base::id;
+    use objc::{msg_send, sel, sel_impl};
+    use wry::application::platform::macos::WindowExtMacOS;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGWindowLevelForKey(key: i32) -> i32;
+    }
+    const K_CG_DESKTOP_WINDOW_LEVEL_KEY: i32 = 2;
+    // NSWindowCollectionBehaviorCanJoinAllSpaces | NSWindowCollectionBehaviorStationary
+    const COLLECTION_BEHAVIOR: u64 = 1 << 0 | 1 << 4;
+
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let level = CGWindowLevelForKey(K_CG_DESKTOP_WINDOW_LEVEL_KEY) as i64;
+        let _: () = msg_send![ns_window, setLevel: level];
+        let _: () = msg_send![ns_window, setCollectionBehavior: COLLECTION_BEHAVIOR];
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_always_below(window: &Window) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SetWindowPos, HWND_BOTTOM, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+    };
+    use wry::application::platform::windows::WindowExtWindows;
+
+    unsafe {
+        SetWindowPos(
+            HWND(window.hwnd() as _),
+            HWND_BOTTOM,
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_always_below(window: &Window) {
+    use gtk::prelude::GtkWindowExt;
+    use wry::application::platform::unix::WindowExtUnix;
+
+    window.gtk_window().set_keep_below(true);
+}
+
+/// 设置整个窗口的不透明度，取值 0.0–1.0
+#[cfg(target_os = "macos")]
+pub fn set_opacity(window: &Window, opacity: f64) {
+    use cocoa::base::id;
+    use objc::{msg_send, sel, sel_impl};
+    use wry::application::platform::macos::WindowExtMacOS;
+
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let _: () = msg_send![ns_window, setAlphaValue: opacity.clamp(0.0, 1.0)];
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_opacity(window: &Window, opacity: f64) {
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED, WS_EX_TRANSPARENT,
+    };
+    use wry::application::platform::windows::WindowExtWindows;
+
+    let hwnd = HWND(window.hwnd() as _);
+    unsafe {
+        let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        // 恢复完全不透明时去掉分层样式，避免分层窗口的额外合成开销；点击穿透仍依赖分层样式，此时保留
+        if opacity >= 1.0 && ex_style & WS_EX_TRANSPARENT.0 as isize == 0 {
+            if ex_style & WS_EX_LAYERED.0 as isize != 0 {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style & !(WS_EX_LAYERED.0 as isize));
+            }
+            return;
+        }
+        if ex_style & WS_EX_LAYERED.0 as isize == 0 {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED.0 as isize);
+        }
+        let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_opacity(window: &Window, opacity: f64) {
+    use gtk::prelude::WidgetExt;
+    use wry::application::platform::unix::WindowExtUnix;
+
+    window.gtk_window().set_opacity(opacity.clamp(0.0, 1.0));
+}
+
+/// 解析 `#rgb` 或 `#rrggbb` 格式的颜色，`#` 可省略
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    match hex.len() {
+        3 => Some((
+            channel(0..1)? * 17,
+            channel(1..2)? * 17,
+            channel(2..3)? * 17,
+        )),
+        6 => Some((channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+        _ => None,
+    }
+}
+
+/// 设置标题栏和窗口边框颜色，标题文字按亮度自动选择黑色或白色；None 恢复系统颜色
+///
+/// 依赖 Windows 11 的 DWM 属性，旧版 Windows 上设置失败时保持系统颜色
+#[cfg(target_os = "windows")]
+pub fn set_titlebar_color(window: &Window, color: Option<(u8, u8, u8)>) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Dwm::{
+        DwmSetWindowAttribute, DWMWA_BORDER_COLOR, DWMWA_CAPTION_COLOR, DWMWA_TEXT_COLOR,
+    };
+    use wry::application::platform::windows::WindowExtWindows;
+
+    // COLORREF 为 0x00BBGGRR，DWMWA_COLOR_DEFAULT 表示使用系统颜色
+    const DWMWA_COLOR_DEFAULT: u32 = 0xFFFF_FFFF;
+    let colorref = |(r, g, b): (u8, u8, u8)| u32::from(r) | u32::from(g) << 8 | u32::from(b) << 16;
+    let (caption, text) = match color {
+        Some((r, g, b)) => {
+            let luminance = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+            let text = if luminance > 150.0 {
+                (0, 0, 0)
+            } else {
+                (255, 255, 255)
+            };
+            (colorref((r, g, b)), colorref(text))
+        }
+        None => (DWMWA_COLOR_DEFAULT, DWMWA_COLOR_DEFAULT),
+    };
+    let hwnd = HWND(window.hwnd() as _);
+    for (attribute, value) in [
+        (DWMWA_CAPTION_COLOR, caption),
+        (DWMWA_BORDER_COLOR, caption),
+        (DWMWA_TEXT_COLOR, text),
+    ] {
+        let result = unsafe {
+            DwmSetWindowAttribute(
+                hwnd,
+                attribute,
+                &value as *const u32 as *const std::ffi::c_void,
+                std::mem::size_of::<u32>() as u32,
+            )
+        };
+        if let Err(e) = result {
+            println!("当前系统不支持设置标题栏颜色: {:?}", e);
+            return;
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_titlebar_color(_window: &Window, _color: Option<(u8, u8, u8)>) {}
+
+/// 设置内容区后面的窗口背景色，None 恢复主题默认颜色
+#[cfg(target_os = "linux")]
+pub fn set_background_color(window: &Window, color: Option<(u8, u8, u8)>) {
+    use gtk::gdk::RGBA;
+    use gtk::prelude::WidgetExt;
+    use gtk::StateFlags;
+    use wry::application::platform::unix::WindowExtUnix;
+
+    let rgba = color.map(|(r, g, b)| {
+        RGBA::new(
+            f64::from(r) / 255.0,
+            f64::from(g) / 255.0,
+            f64::from(b) / 255.0,
+            1.0,
+        )
+    });
+    // gtk 3 中没有不依赖 CSS 的替代接口
+    #[allow(deprecated)]
+    window
+        .gtk_window()
+        .override_background_color(StateFlags::NORMAL, rgba.as_ref());
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_background_color(_window: &Window, _color: Option<(u8, u8, u8)>) {}
+
+/// 用圆角形状裁剪窗口，窗口大小变化时重新计算；最大化和全屏时恢复直角
+#[cfg(target_os = "linux")]
+pub fn set_rounded_corners(window: &Window, radius: f64) {
+    use gtk::gdk::WindowState;
+    use gtk::prelude::{GtkWindowExt, WidgetExt};
+    use wry::application::platform::unix::WindowExtUnix;
+
+    window
+        .gtk_window()
+        .connect_size_allocate(move |widget, allocation| {
+            let fullscreen = widget
+                .window()
+                .map_or(false, |w| w.state().contains(WindowState::FULLSCREEN));
+            if widget.is_maximized() || fullscreen {
+                widget.shape_combine_region(None);
+                return;
+            }
+            match rounded_region(allocation.width(), allocation.height(), radius) {
+                Ok(region) => widget.shape_combine_region(Some(&region)),
+                Err(e) => eprintln!("警告: 无法设置窗口圆角: {}", e),
+            }
+        });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_rounded_corners(_window: &Window, _radius: f64) {
+    println!("警告: rounded_corners 仅在 Linux 上生效，已忽略");
+}
+
+/// 在 1 位遮罩上绘制圆角矩形，再转换为 GDK 可用的形状区域
+#[cfg(target_os = "linux")]
+fn rounded_region(width: i32, height: i32, radius: f64) -> Result<cairo::Region, String> {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let surface = cairo::ImageSurface::create(cairo::Format::A1, width, height)
+        .map_err(|e| format!("{:?}", e))?;
+    {
+        let cr = cairo::Context::new(&surface).map_err(|e| format!("{:?}", e))?;
+        let (w, h) = (width as f64, height as f64);
+        let r = radius.min(w / 2.0).min(h / 2.0);
+        cr.new_sub_path();
+        cr.arc(w - r, r, r, -FRAC_PI_2, 0.0);
+        cr.arc(w - r, h - r, r, 0.0, FRAC_PI_2);
+        cr.arc(r, h - r, r, FRAC_PI_2, PI);
+        cr.arc(r, r, r, PI, 3.0 * FRAC_PI_2);
+        cr.close_path();
+        cr.fill().map_err(|e| format!("{:?}", e))?;
+    }
+    gtk::gdk::cairo_region_create_from_surface(&surface)
+        .ok_or_else(|| "无法从遮罩创建区域".to_string())
+}
+
+/// 当前光标在屏幕上的位置，坐标系与 `MonitorHandle::position()` 一致
+#[cfg(target_os = "windows")]
+pub fn cursor_position() -> Option<Position> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    // tao 启用了按显示器的 DPI 感知，这里得到的是物理像素
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }
+        .as_bool()
+        .then(|| PhysicalPosition::new(point.x, point.y).into())
+}
+
+#[cfg(target_os = "macos")]
+pub fn cursor_position() -> Option<Position> {
+    use cocoa::appkit::{NSEvent, NSScreen};
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSArray, NSPoint, NSRect};
+
+    unsafe {
+        // AppKit 坐标以主屏左下角为原点，换算为以左上角为原点的逻辑坐标
+        let screens = NSScreen::screens(nil);
+        if screens.count() == 0 {
+            return None;
+        }
+        let main_screen: id = screens.objectAtIndex(0);
+        let frame: NSRect = NSScreen::frame(main_screen);
+        let point: NSPoint = NSEvent::mouseLocation(nil);
+        Some(LogicalPosition::new(point.x, frame.size.height - point.y).into())
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn cursor_position() -> Option<Position> {
+    let pointer = gtk::gdk::Display::default()?.default_seat()?.pointer()?;
+    let (_, x, y) = pointer.position();
+    Some(LogicalPosition::new(x, y).into())
+}
+
+/// 显示器的工作区，即去掉任务栏、Dock 和菜单栏后可以放置窗口的区域，物理像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkArea {
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+impl WorkArea {
+    /// 整个显示器，取不到工作区时使用
+    fn full(monitor: &MonitorHandle) -> Self {
+        WorkArea {
+            position: monitor.position(),
+            size: monitor.size(),
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.position.x..self.position.x + self.size.width as i32).contains(&x)
+            && (self.position.y..self.position.y + self.size.height as i32).contains(&y)
+    }
+
+    /// 把给定尺寸的窗口居中放在工作区内时的左上角，放不下时贴着工作区左上角
+    pub fn centered(&self, size: PhysicalSize<u32>) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(
+            self.position.x + (self.size.width as i32 - size.width as i32).max(0) / 2,
+            self.position.y + (self.size.height as i32 - size.height as i32).max(0) / 2,
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn work_area(monitor: &MonitorHandle) -> WorkArea {
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO};
+    use wry::application::platform::windows::MonitorHandleExtWindows;
+
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(HMONITOR(monitor.hmonitor() as _), &mut info) }.as_bool() {
+        return WorkArea::full(monitor);
+    }
+    let rect = info.rcWork;
+    WorkArea {
+        position: PhysicalPosition::new(rect.left, rect.top),
+        size: PhysicalSize::new(
+            (rect.right - rect.left).max(0) as u32,
+            (rect.bottom - rect.top).max(0) as u32,
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn work_area(monitor: &MonitorHandle) -> WorkArea {
+    use cocoa::appkit::NSScreen;
+    use cocoa::base::id;
+    use cocoa::foundation::NSRect;
+    use wry::application::platform::macos::MonitorHandleExtMacOS;
+
+    let screen = match monitor.ns_screen() {
+        Some(screen) => screen as id,
+        None => return WorkArea::full(monitor),
+    };
+    // AppKit 坐标以左下角为原点，按整个屏幕与可见区域的差值换算出上下左右的边距
+    let (frame, visible): (NSRect, NSRect) =
+        unsafe { (NSScreen::frame(screen), NSScreen::visibleFrame(screen)) };
+    let scale = monitor.scale_factor();
+    let origin = monitor.position();
+    let top = (frame.origin.y + frame.size.height) - (visible.origin.y + visible.size.height);
+    WorkArea {
+        position: PhysicalPosition::new(
+            origin.x + ((visible.origin.x - frame.origin.x) * scale).round() as i32,
+            origin.y + (top * scale).round() as i32,
+        ),
+        size: PhysicalSize::new(
+            (visible.size.width * scale).round() as u32,
+            (visible.size.height * scale).round() as u32,
+        ),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn work_area(monitor: &MonitorHandle) -> WorkArea {
+    use gtk::gdk::prelude::MonitorExt;
+
+    // tao 的显示器坐标是 GDK 坐标乘以缩放比例，用显示器中心点找到对应的 GdkMonitor
+    let scale = monitor.scale_factor();
+    let origin = monitor.position();
+    let size = monitor.size();
+    let center_x = (origin.x as f64 + size.width as f64 / 2.0) / scale;
+    let center_y = (origin.y as f64 + size.height as f64 / 2.0) / scale;
+    let area = gtk::gdk::Display::default()
+        .and_then(|display| display.monitor_at_point(center_x as i32, center_y as i32))
+        .map(|gdk_monitor| gdk_monitor.workarea());
+    match area {
+        Some(area) => WorkArea {
+            position: PhysicalPosition::new(
+                (area.x() as f64 * scale).round() as i32,
+                (area.y() as f64 * scale).round() as i32,
+            ),
+            size: PhysicalSize::new(
+                (area.width() as f64 * scale).round() as u32,
+                (area.height() as f64 * scale).round() as u32,
+            ),
+        },
+        None => WorkArea::full(monitor),
+    }
+}
+
+/// 让整个窗口忽略鼠标，点击穿透到下面的窗口；Linux 使用 `set_input_region` 按区域穿透
+#[cfg(target_os = "macos")]
+pub fn set_click_through(window: &Window, ignore: bool) {
+    use cocoa::base::{id, BOOL, NO, YES};
+    use objc::{msg_send, sel, sel_impl};
+    use wry::application::platform::macos::WindowExtMacOS;
+
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let ignore: BOOL = if ignore { YES } else { NO };
+        let _: () = msg_send![ns_window, setIgnoresMouseEvents: ignore];
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_click_through(window: &Window, ignore: bool) {
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED, WS_EX_TRANSPARENT,
+    };
+    use wry::application::platform::windows::WindowExtWindows;
+
+    let hwnd = HWND(window.hwnd() as _);
+    unsafe {
+        let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        // WS_EX_TRANSPARENT 只对分层窗口穿透点击，新设为分层窗口时需要同时设置不透明度，否则窗口不可见
+        let layered = ex_style & WS_EX_LAYERED.0 as isize != 0;
+        let new_style = if ignore {
+            ex_style | WS_EX_LAYERED.0 as isize | WS_EX_TRANSPARENT.0 as isize
+        } else {
+            ex_style & !(WS_EX_TRANSPARENT.0 as isize)
+        };
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
+        if ignore && !layered {
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, LWA_ALPHA);
+        }
+    }
+}
+
+/// 只有这些区域接收鼠标，其余部分点击穿透；区域为相对内容区的物理像素
+#[cfg(target_os = "linux")]
+pub fn set_input_region(window: &Window, regions: &[crate::overlay::Region]) {
+    use gtk::prelude::WidgetExt;
+    use wry::application::platform::unix::WindowExtUnix;
+
+    // GTK 使用逻辑像素
+    let scale = window.scale_factor();
+    let rectangles: Vec<cairo::RectangleInt> = regions
+        .iter()
+        .map(|region| cairo::RectangleInt {
+            x: (region.x / scale).floor() as i32,
+            y: (region.y / scale).floor() as i32,
+            width: (region.width / scale).ceil() as i32,
+            height: (region.height / scale).ceil() as i32,
+        })
+        .collect();
+    window
+        .gtk_window()
+        .input_shape_combine_region(Some(&cairo::Region::create_rectangles(&rectangles)));
+}
+
+/// 窗口是否处于最小化状态
+#[cfg(target_os = "macos")]
+pub fn is_minimized(window: &Window) -> bool {
+    use cocoa::base::{id, BOOL, YES};
+    use objc::{msg_send, sel, sel_impl};
+    use wry::application::platform::macos::WindowExtMacOS;
+
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let minimized: BOOL = msg_send![ns_window, isMiniaturized];
+        minimized == YES
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_minimized(window: &Window) -> bool {
+    use gtk::gdk::WindowState;
+    use gtk::prelude::WidgetExt;
+    use wry::application::platform::unix::WindowExtUnix;
+
+    window.gtk_window().window().map_or(false, |gdk_window| {
+        gdk_window.state().contains(WindowState::ICONIFIED)
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_minimized(window: &Window) -> bool {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::IsIconic;
+    use wry::application::platform::windows::WindowExtWindows;
+
+    unsafe { IsIconic(HWND(window.hwnd() as _)).as_bool() }
+}
+
+/// Dock 菜单的“退出”、注销和关机会直接调用 `terminate:`，不经过事件循环。
+/// 给 tao 的应用代理加上 `applicationShouldTerminate:`，先取消这次退出并发送 `QuitRequested`，
+/// 由 exit_app 确认后再退出
+#[cfg(target_os = "macos")]
+pub fn intercept_app_terminate(proxy: EventLoopProxy<UserEvent>) {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::{id, nil};
+    use objc::runtime::{class_addMethod, object_getClass, Class, Imp, Object, Sel, NO};
+    use objc::{msg_send, sel, sel_impl};
+    use std::sync::Mutex;
+
+    static PROXY: Mutex<Option<EventLoopProxy<UserEvent>>> = Mutex::new(None);
+    // NSTerminateCancel
+    const TERMINATE_CANCEL: u64 = 0;
+
+    extern "C" fn should_terminate(_this: &Object, _sel: Sel, _sender: id) -> u64 {
+        if let Some(proxy) = PROXY.lock().ok().and_then(|proxy| proxy.clone()) {
+            let _ = proxy.send_event(UserEvent::QuitRequested);
+        }
+        TERMINATE_CANCEL
+    }
+
+    if let Ok(mut slot) = PROXY.lock() {
+        *slot = Some(proxy);
+    }
+    unsafe {
+        let delegate: id = msg_send![NSApp(), delegate];
+        if delegate == nil {
+            eprintln!("警告: 找不到应用代理，Dock 菜单退出时不会确认");
+            return;
+        }
+        let class = object_getClass(delegate as *const Object) as *mut Class;
+        let imp: Imp =
+            std::mem::transmute(should_terminate as extern "C" fn(&Object, Sel, id) -> u64);
+        let added = class_addMethod(
+            class,
+            sel!(applicationShouldTerminate:),
+            imp,
+            "Q@:@\0".as_ptr() as *const std::os::raw::c_char,
+        );
+        if added == NO {
+            eprintln!("警告: 应用代理已实现 applicationShouldTerminate:，Dock 菜单退出时不会确认");
+        }
+    }
+}
+
+/// 隐藏窗口：macOS 隐藏整个应用，点击 Dock 图标即可恢复；其他平台隐藏窗口，需要通过托盘恢复
+#[cfg(target_os = "macos")]
+pub fn hide(_window: &Window) {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::nil;
+    use objc::{msg_send, sel, sel_impl};
+
+    unsafe {
+        let _: () = msg_send![NSApp(), hide: nil];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn hide(window: &Window) {
+    window.set_visible(false);
+}
+
+/// 当前环境是否能设置窗口不透明度，Linux 下需要窗口合成器支持
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub fn supports_opacity(_window: &Window) -> bool {
+    true
+}
+
+#[cfg(target_os = "linux")]
+pub fn supports_opacity(window: &Window) -> bool {
+    use gtk::prelude::{GtkWindowExt, WidgetExt};
+    use wry::application::platform::unix::WindowExtUnix;
+
+    window
+        .gtk_window()
+        .screen()
+        .map_or(false, |screen| screen.is_composited())
+}
+
+/// 在后台线程中按约 60fps 逐步发送不透明度，由事件循环实际设置
+pub fn spawn_fade_in(duration: Duration, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        loop {
+            let progress = start.elapsed().as_secs_f64() / duration.as_secs_f64();
+            if proxy
+                .send_event(UserEvent::WindowOpacity(progress.min(1.0)))
+                .is_err()
+                || progress >= 1.0
+            {
+                break;
+            }
+            std::thread::sleep(FADE_FRAME);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 主显示器右侧 1920x1080 的副屏，顶部 25 像素为菜单栏，底部 70 像素为 Dock
+    fn secondary_work_area() -> WorkArea {
+        WorkArea {
+            position: PhysicalPosition::new(1920, 25),
+            size: PhysicalSize::new(1920, 985),
+        }
+    }
+
+    #[test]
+    fn windows_are_centered_in_the_work_area() {
+        let area = secondary_work_area();
+        assert_eq!(
+            area.centered(PhysicalSize::new(960, 485)),
+            PhysicalPosition::new(2400, 275)
+        );
+    }
+
+    #[test]
+    fn oversized_windows_stick_to_the_work_area_origin() {
+        let area = secondary_work_area();
+        assert_eq!(
+            area.centered(PhysicalSize::new(2560, 1440)),
+            PhysicalPosition::new(1920, 25)
+        );
+    }
+
+    #[test]
+    fn work_area_excludes_the_menu_bar_and_dock() {
+        let area = secondary_work_area();
+        assert!(area.contains(1920, 25));
+        assert!(area.contains(3839, 1009));
+        assert!(!area.contains(1920, 10));
+        assert!(!area.contains(2000, 1010));
+        assert!(!area.contains(100, 500));
+    }
+}
@@ -0,0 +1,789 @@
+// pake.js 中与页面交互的函数的测试，用 `node --test src-tauri/tests` 运行
+//
+// pake.js 是注入页面的普通脚本，这里按名称取出单个函数，在带有最小 DOM 替身的 vm 上下文中执行
+import { test } from "node:test";
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+import vm from "node:vm";
+
+const source = readFileSync(new URL("../src/pake.js", import.meta.url), "utf8");
+
+/** 取出 `function <name>(...) { ... }` 的完整源码 */
+function extractFunction(name) {
+  const start = source.indexOf(`function ${name}(`);
+  assert.notEqual(start, -1, `pake.js 中没有 ${name}`);
+  let depth = 0;
+  for (let i = source.indexOf("{", start); i < source.length; i++) {
+    if (source[i] === "{") depth++;
+    if (source[i] === "}" && --depth === 0) {
+      return source.slice(start, i + 1);
+    }
+  }
+  throw new Error(`${name} 的括号不匹配`);
+}
+
+/** 在 context 中定义给定的函数并返回上下文 */
+function load(names, context) {
+  vm.createContext(context);
+  vm.runInContext(names.map(extractFunction).join("\n"), context);
+  return context;
+}
+
+class FakeEvent {
+  constructor(type, init = {}) {
+    this.type = type;
+    this.bubbles = Boolean(init.bubbles);
+  }
+}
+
+class FakeInput {
+  constructor(form) {
+    this.form = form;
+    this._value = "";
+    this.events = [];
+  }
+  dispatchEvent(event) {
+    this.events.push(event.type);
+  }
+}
+Object.defineProperty(FakeInput.prototype, "value", {
+  get() {
+    return this._value;
+  },
+  set(value) {
+    this._value = value;
+  },
+});
+
+function sessionStorage() {
+  const items = new Map();
+  return {
+    getItem: (key) => (items.has(key) ? items.get(key) : null),
+    setItem: (key, value) => items.set(key, String(value)),
+  };
+}
+
+function loginPage() {
+  const form = { submitted: 0, requestSubmit() { this.submitted++; } };
+  const elements = {
+    "#user": new FakeInput(form),
+    "#pass": new FakeInput(form),
+    "#submit": { clicks: 0, click() { this.clicks++; } },
+  };
+  const storage = sessionStorage();
+  const context = load(["pakeAutoLogin"], {
+    window: { sessionStorage: storage, location: { origin: "https://example.com" } },
+    document: { querySelector: (selector) => elements[selector] || null },
+    HTMLInputElement: FakeInput,
+    HTMLTextAreaElement: class {},
+    Event: FakeEvent,
+    console: { warn() {} },
+    setTimeout,
+    Date,
+  });
+  return { context, elements, form };
+}
+
+const login = {
+  username_selector: "#user",
+  password_selector: "#pass",
+  submit_selector: "#submit",
+  username: "kiosk",
+  password: "secret",
+};
+
+test("auto login fills the form and clicks submit", () => {
+  const { context, elements } = loginPage();
+  context.pakeAutoLogin(login);
+  assert.equal(elements["#user"].value, "kiosk");
+  assert.equal(elements["#pass"].value, "secret");
+  assert.deepEqual(elements["#pass"].events, ["input", "change"]);
+  assert.equal(elements["#submit"].clicks, 1);
+});
+
+test("auto login submits the password form without a submit selector", () => {
+  const { context, form } = loginPage();
+  context.pakeAutoLogin({ ...login, submit_selector: "" });
+  assert.equal(form.submitted, 1);
+});
+
+test("auto login gives up after three attempts in a session", () => {
+  const { context, elements } = loginPage();
+  for (let i = 0; i < 5; i++) {
+    context.pakeAutoLogin(login);
+  }
+  assert.equal(elements["#submit"].clicks, 3);
+});
+
+/** 只认识十六进制、rgba()、transparent 和 color(srgb …) 的 2D canvas 替身，无法识别的颜色不改变 fillStyle */
+class FakeCanvasContext {
+  constructor() {
+    this.rgba = [0, 0, 0, 255];
+    this.pixel = [0, 0, 0, 0];
+  }
+  get fillStyle() {
+    return this.rgba.join(",");
+  }
+  set fillStyle(value) {
+    const parsed = FakeCanvasContext.parse(value.trim());
+    if (parsed) {
+      this.rgba = parsed;
+    }
+  }
+  static parse(value) {
+    let match;
+    if ((match = value.match(/^#([0-9a-f]{3})$/i))) {
+      return [...match[1]].map((c) => parseInt(c + c, 16)).concat(255);
+    }
+    if ((match = value.match(/^#([0-9a-f]{6})$/i))) {
+      return match[1].match(/../g).map((c) => parseInt(c, 16)).concat(255);
+    }
+    if ((match = value.match(/^rgba?\((\d+),\s*(\d+),\s*(\d+)(?:,\s*([\d.]+))?\)$/))) {
+      return [+match[1], +match[2], +match[3], Math.round((match[4] ?? 1) * 255)];
+    }
+    if ((match = value.match(/^color\(srgb ([\d.]+) ([\d.]+) ([\d.]+)\)$/))) {
+      return [match[1], match[2], match[3]].map((n) => Math.round(n * 255)).concat(255);
+    }
+    if (value === "transparent") {
+      return [0, 0, 0, 0];
+    }
+    return null;
+  }
+  clearRect() {
+    this.pixel = [0, 0, 0, 0];
+  }
+  fillRect() {
+    this.pixel = [...this.rgba];
+  }
+  getImageData() {
+    return { data: this.pixel };
+  }
+}
+
+function themeColorPage(metas) {
+  const posted = [];
+  const observers = [];
+  const context = load(["cssColorToHex", "installThemeColorSync"], {
+    pakeConfig: { follow_theme_color: true },
+    window: {
+      ipc: { postMessage: (message) => posted.push(message) },
+      addEventListener: (type, listener) => type === "DOMContentLoaded" && listener(),
+      matchMedia: (media) => ({ matches: media === "(prefers-color-scheme: dark)", addEventListener() {} }),
+    },
+    document: {
+      head: {},
+      createElement: () => ({ getContext: () => new FakeCanvasContext() }),
+      querySelectorAll: () => metas,
+    },
+    MutationObserver: class {
+      constructor(callback) {
+        observers.push(callback);
+      }
+      observe() {}
+    },
+  });
+  context.window.top = context.window;
+  context.installThemeColorSync();
+  return { context, posted, mutate: () => observers.forEach((callback) => callback()) };
+}
+
+test("css colors convert to hex without touching the document", () => {
+  const { context } = themeColorPage([]);
+  assert.equal(context.cssColorToHex("#0af"), "#00aaff");
+  assert.equal(context.cssColorToHex("rgb(18, 52, 86)"), "#123456");
+  assert.equal(context.cssColorToHex("color(srgb 1 0 0.5)"), "#ff0080");
+  assert.equal(context.cssColorToHex("transparent"), "");
+  assert.equal(context.cssColorToHex("not-a-color"), "");
+});
+
+test("theme color sync reports live changes once", () => {
+  const meta = { content: "#ffffff", media: "" };
+  const { posted, mutate } = themeColorPage([meta]);
+  assert.deepEqual(posted, ["theme_color:#ffffff"]);
+  mutate();
+  assert.deepEqual(posted, ["theme_color:#ffffff"]);
+  meta.content = "rgb(0, 0, 0)";
+  mutate();
+  assert.deepEqual(posted, ["theme_color:#ffffff", "theme_color:#000000"]);
+});
+
+test("theme color sync picks the meta matching the color scheme", () => {
+  const { posted } = themeColorPage([
+    { content: "#eeeeee", media: "(prefers-color-scheme: light)" },
+    { content: "#111111", media: "(prefers-color-scheme: dark)" },
+  ]);
+  assert.deepEqual(posted, ["theme_color:#111111"]);
+});
+
+/** 只支持启动画面用到的操作的元素替身 */
+class FakeElement {
+  constructor(tag) {
+    this.tagName = tag;
+    this.children = [];
+    this.style = {};
+  }
+  append(...children) {
+    this.children.push(...children);
+  }
+  appendChild(child) {
+    this.children.push(child);
+    return child;
+  }
+  set innerHTML(_) {
+    this.children = [];
+  }
+}
+
+/** 文档开始时还没有 documentElement 的页面，`parse()` 模拟解析器创建根元素 */
+function splashPage(splash) {
+  const observers = [];
+  const document = {
+    documentElement: null,
+    createElement: (tag) => new FakeElement(tag),
+    getElementById: (id) =>
+      (document.documentElement?.children || []).find((child) => child.id === id) || null,
+  };
+  const context = load(["showSplash", "mountSplash", "hideSplash", "pakeShowSlowLoad"], {
+    pakeConfig: { disable_ipc: true },
+    uiRtl: false,
+    window: { __PAKE_SPLASH__: splash, addEventListener() {} },
+    document,
+    MutationObserver: class {
+      constructor(callback) {
+        this.callback = callback;
+        this.active = true;
+        observers.push(this);
+      }
+      observe() {}
+      disconnect() {
+        this.active = false;
+      }
+    },
+  });
+  context.window.top = context.window;
+  const parse = () => {
+    document.documentElement = new FakeElement("html");
+    observers.filter((observer) => observer.active).forEach((observer) => observer.callback());
+  };
+  return { context, document, parse };
+}
+
+test("splash waits for the document element before mounting", () => {
+  const { context, document, parse } = splashPage({ image: null, build: null });
+  context.showSplash();
+  assert.equal(document.getElementById("pake-splash"), null);
+  parse();
+  assert.ok(document.getElementById("pake-splash"));
+});
+
+test("slow load mounts the configured splash when none is shown", () => {
+  const build = { name: "Pake", version: "1.0.0", build: null };
+  const { context, document, parse } = splashPage({ image: null, build });
+  parse();
+  context.pakeShowSlowLoad();
+  const overlay = document.getElementById("pake-splash");
+  const texts = overlay.children.map((child) => child.textContent);
+  assert.ok(texts.includes("Pake v1.0.0"));
+  assert.ok(overlay.children.some((child) => child.id === "pake-slow-load"));
+});
+
+function printPage(printMode, userAgent) {
+  const posted = [];
+  const listeners = {};
+  const nativePrint = () => {};
+  const window = {
+    print: nativePrint,
+    ipc: { postMessage: (message) => posted.push(message) },
+    addEventListener: (type, listener) => (listeners[type] = listener),
+  };
+  const context = load(["installPrintHandler"], {
+    pakeConfig: { print_mode: printMode },
+    navigator: { userAgent },
+    window,
+    Date,
+  });
+  context.installPrintHandler((command) => posted.push(`signed:${command}`));
+  return { window, nativePrint, posted, listeners };
+}
+
+const windowsAgent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Edg/120.0";
+const macAgent = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15";
+
+test("print dialog mode keeps the native window.print where it works", () => {
+  const { window, nativePrint } = printPage("dialog", windowsAgent);
+  assert.equal(window.print, nativePrint);
+});
+
+test("print is routed to the app for pdf mode and on macOS", () => {
+  for (const [mode, agent] of [["pdf", windowsAgent], ["dialog", macAgent]]) {
+    const { window, nativePrint, posted } = printPage(mode, agent);
+    assert.notEqual(window.print, nativePrint);
+    window.print();
+    assert.deepEqual(posted, ["print"]);
+  }
+});
+
+test("print is signed only right after a real click or key press", () => {
+  const { window, posted, listeners } = printPage("pdf", windowsAgent);
+  listeners.click({ isTrusted: false });
+  window.print();
+  listeners.keydown({ isTrusted: true });
+  window.print();
+  assert.deepEqual(posted, ["print", "signed:print"]);
+});
+
+test("slow-load cancel is signed only for real clicks on the cancel button", () => {
+  const posted = [];
+  let listener;
+  load(["installSlowLoadCancel"], {
+    document: { addEventListener: (type, callback) => (listener = callback) },
+  }).installSlowLoadCancel((command) => posted.push(command));
+  const cancel = { closest: (selector) => (selector.includes("data-pake-cancel") ? {} : null) };
+  listener({ target: cancel, isTrusted: false });
+  listener({ target: { closest: () => null }, isTrusted: true });
+  listener({ target: cancel, isTrusted: true });
+  assert.deepEqual(posted, ["close_window"]);
+});
+
+/** 记录收到的模拟拖放事件的放置区域 */
+function dropZone() {
+  return {
+    tagName: "DIV",
+    events: [],
+    dispatchEvent(event) {
+      this.events.push(event);
+    },
+  };
+}
+
+function fileDropPage(elementsAt) {
+  const listeners = {};
+  const body = dropZone();
+  const context = load(["installFileDropForwarding"], {
+    pakeConfig: { forward_file_drops: true },
+    window: { addEventListener: (type, listener) => (listeners[type] = listener) },
+    document: {
+      activeElement: body,
+      body,
+      elementFromPoint: (x, y) => elementsAt(x, y),
+      querySelector: () => null,
+    },
+    DataTransfer: class {
+      constructor() {
+        this.files = [];
+        this.items = { add: (file) => this.files.push(file) };
+      }
+    },
+    File: class {
+      constructor(parts, name) {
+        this.name = name;
+      }
+    },
+    DragEvent: class {
+      constructor(type, init) {
+        Object.assign(this, init, { type });
+      }
+    },
+    Uint8Array,
+    atob: (data) => Buffer.from(data, "base64").toString("binary"),
+  });
+  context.window.top = context.window;
+  context.installFileDropForwarding();
+  const drop = (detail) => listeners["pake:files_dropped"]({ detail });
+  return { body, drop };
+}
+
+test("dropped files go to the element under the drop point", () => {
+  const zone = dropZone();
+  const { body, drop } = fileDropPage((x, y) => (x === 300 && y === 210 ? zone : null));
+  drop({ files: [{ name: "a.txt", base64_content: "aGVsbG8=" }], point: { x: 300, y: 210 } });
+  assert.deepEqual(
+    zone.events.map((event) => event.type),
+    ["dragenter", "dragover", "drop"]
+  );
+  assert.equal(zone.events[2].clientX, 300);
+  assert.equal(zone.events[2].dataTransfer.files[0].name, "a.txt");
+  assert.equal(body.events.length, 0);
+});
+
+test("dropped files without a point fall back to the page body", () => {
+  const { body, drop } = fileDropPage(() => assert.fail("没有位置时不应查找元素"));
+  drop({ files: [{ name: "a.txt", base64_content: "aGVsbG8=" }], point: null });
+  assert.equal(body.events.length, 3);
+});
+
+function localePage(config) {
+  class Navigator {}
+  const context = load(["installLocaleOverrides"], {
+    pakeConfig: config,
+    Navigator,
+    navigator: new Navigator(),
+    console: { warn() {} },
+  });
+  context.installLocaleOverrides();
+  return (expression) => vm.runInContext(expression, context);
+}
+
+test("locale override changes navigator languages and Intl defaults", () => {
+  const evaluate = localePage({ override_locale: "de-DE" });
+  assert.equal(evaluate("navigator.language"), "de-DE");
+  assert.deepEqual([...evaluate("navigator.languages")], ["de-DE"]);
+  assert.equal(evaluate("new Intl.NumberFormat().format(1234.5)"), "1.234,5");
+  assert.equal(evaluate("new Intl.DateTimeFormat().resolvedOptions().locale"), "de-DE");
+  assert.equal(evaluate("new Intl.NumberFormat('en-US').format(1234.5)"), "1,234.5");
+});
+
+test("timezone override changes the offset seen by the page", () => {
+  const evaluate = localePage({ override_timezone: "Asia/Tokyo" });
+  assert.equal(evaluate("new Date(0).getTimezoneOffset()"), -540);
+  assert.equal(evaluate("new Intl.DateTimeFormat().resolvedOptions().timeZone"), "Asia/Tokyo");
+});
+
+/** vm 上下文中创建的对象原型不同，转成普通对象再比较 */
+const plain = (value) => JSON.parse(JSON.stringify(value));
+
+/** 可滚动元素替身，记录 scrollBy 调用 */
+function scrollBox(style, size, parentElement = null) {
+  return {
+    style,
+    parentElement,
+    scrollHeight: size.scroll,
+    clientHeight: size.client,
+    scrollWidth: 0,
+    clientWidth: 0,
+    scrolls: [],
+    scrollBy(options) {
+      this.scrolls.push(options);
+    },
+  };
+}
+
+function scrollPage(config) {
+  const listeners = {};
+  const head = { children: [], append(child) { this.children.push(child); } };
+  const body = scrollBox({ overflowX: "visible", overflowY: "visible" }, { scroll: 0, client: 0 });
+  const scrollingElement = scrollBox({}, { scroll: 2000, client: 800 });
+  const context = load(["installScrollBehavior", "findScrollable"], {
+    pakeConfig: config,
+    window: { addEventListener: (type, listener) => (listeners[type] = listener) },
+    document: {
+      head,
+      body,
+      documentElement: scrollingElement,
+      scrollingElement,
+      createElement: () => ({}),
+    },
+    getComputedStyle: (el) => el.style,
+  });
+  context.installScrollBehavior();
+  const wheel = (target, init = {}) => {
+    const event = {
+      target,
+      deltaX: 0,
+      deltaY: 100,
+      ctrlKey: false,
+      defaultPrevented: false,
+      ...init,
+      preventDefault() {
+        this.defaultPrevented = true;
+      },
+    };
+    listeners.wheel(event);
+    return event;
+  };
+  return { head, body, scrollingElement, wheel, listeners };
+}
+
+test("smooth scrolling toggle sets scroll-behavior", () => {
+  assert.match(scrollPage({ smooth_scrolling: false }).head.children[0].innerHTML, /scroll-behavior: auto/);
+  assert.match(scrollPage({ smooth_scrolling: true }).head.children[0].innerHTML, /scroll-behavior: smooth/);
+  assert.equal(scrollPage({}).head.children.length, 0);
+});
+
+test("scroll multiplier scales wheel deltas on the nearest scrollable element", () => {
+  const { body, scrollingElement, wheel } = scrollPage({ scroll_multiplier: 2 });
+  const list = scrollBox({ overflowX: "hidden", overflowY: "auto" }, { scroll: 900, client: 300 }, body);
+  const item = scrollBox({ overflowX: "visible", overflowY: "visible" }, { scroll: 20, client: 20 }, list);
+  const event = wheel(item);
+  assert.ok(event.defaultPrevented);
+  assert.deepEqual(plain(list.scrolls), [{ left: 0, top: 200, behavior: "auto" }]);
+  wheel(body, { deltaY: -50 });
+  assert.deepEqual(plain(scrollingElement.scrolls), [{ left: 0, top: -100, behavior: "auto" }]);
+});
+
+test("scroll multiplier leaves zoom gestures and the default of 1 alone", () => {
+  const { wheel } = scrollPage({ scroll_multiplier: 3 });
+  const event = wheel({ parentElement: null }, { ctrlKey: true });
+  assert.equal(event.defaultPrevented, false);
+  assert.equal(scrollPage({ scroll_multiplier: 1 }).listeners.wheel, undefined);
+});
+
+/** 带尺寸和可见性的输入框替身 */
+function field(width, height, visibility = "visible") {
+  return {
+    focused: false,
+    style: { visibility },
+    getBoundingClientRect: () => ({ width, height }),
+    focus() {
+      this.focused = true;
+    },
+  };
+}
+
+function focusPage(candidates, activeElement = null) {
+  const body = {};
+  const document = {
+    body,
+    activeElement: activeElement || body,
+    querySelectorAll: () => candidates,
+  };
+  return load(["focusFirstInput"], { document, getComputedStyle: (el) => el.style });
+}
+
+test("auto focus picks the first visible input", () => {
+  const hidden = field(0, 0);
+  const invisible = field(200, 30, "hidden");
+  const visible = field(200, 30);
+  const later = field(200, 30);
+  focusPage([hidden, invisible, visible, later]).focusFirstInput();
+  assert.deepEqual(
+    [hidden, invisible, visible, later].map((el) => el.focused),
+    [false, false, true, false]
+  );
+});
+
+test("auto focus keeps focus the page already set", () => {
+  const input = field(200, 30);
+  focusPage([input], field(100, 20)).focusFirstInput();
+  assert.equal(input.focused, false);
+});
+
+function keyboardScrollPage(config) {
+  const listeners = {};
+  const messages = [];
+  const context = load(["installKeyboardScroll"], {
+    pakeConfig: config,
+    window: {
+      addEventListener: (type, listener) => (listeners[type] = listener),
+      ipc: { postMessage: (message) => messages.push(message) },
+    },
+  });
+  context.installKeyboardScroll();
+  const press = (key, target = { tagName: "DIV" }, init = {}) => {
+    const event = {
+      key,
+      target,
+      defaultPrevented: false,
+      ...init,
+      preventDefault() {
+        this.defaultPrevented = true;
+      },
+    };
+    listeners.keydown(event);
+    return event;
+  };
+  return { listeners, messages, press };
+}
+
+test("keyboard scroll sends paging keys to Rust", () => {
+  const { messages, press } = keyboardScrollPage({ keyboard_scroll: true });
+  for (const key of ["PageDown", "PageUp", "Home", "End"]) {
+    assert.ok(press(key).defaultPrevented);
+  }
+  assert.deepEqual(messages, ["scroll_down", "scroll_up", "scroll_top", "scroll_bottom"]);
+});
+
+test("keyboard scroll ignores editable targets, modifiers and other keys", () => {
+  const { messages, press } = keyboardScrollPage({ keyboard_scroll: true });
+  assert.equal(press("PageDown", { tagName: "INPUT" }).defaultPrevented, false);
+  press("Home", { tagName: "DIV", isContentEditable: true });
+  press("End", { tagName: "DIV" }, { ctrlKey: true });
+  press("ArrowDown");
+  assert.deepEqual(messages, []);
+  assert.equal(keyboardScrollPage({}).listeners.keydown, undefined);
+});
+
+/** 记录 scrollBy/scrollTo 调用的滚动容器替身 */
+function pagedBox(scroll, client, overflowY = "auto") {
+  return {
+    style: { overflowY },
+    scrollHeight: scroll,
+    clientHeight: client,
+    clientWidth: 500,
+    calls: [],
+    scrollBy(options) {
+      this.calls.push(["by", options.top]);
+    },
+    scrollTo(options) {
+      this.calls.push(["to", options.top]);
+    },
+  };
+}
+
+function pakeScrollPage(scrollingElement, candidates = []) {
+  const body = {};
+  return load(["pakeScroll"], {
+    document: {
+      body,
+      scrollingElement,
+      documentElement: scrollingElement,
+      activeElement: body,
+      querySelectorAll: () => candidates,
+    },
+    getComputedStyle: (el) => el.style,
+  });
+}
+
+test("pakeScroll pages the document by 90% of the viewport or a fixed amount", () => {
+  const page = pagedBox(3000, 1000, "visible");
+  const context = pakeScrollPage(page);
+  context.pakeScroll("down", null);
+  context.pakeScroll("up", 250);
+  context.pakeScroll("top", null);
+  context.pakeScroll("bottom", null);
+  assert.deepEqual(plain(page.calls), [
+    ["by", 900],
+    ["by", -250],
+    ["to", 0],
+    ["to", 3000],
+  ]);
+});
+
+test("pakeScroll falls back to the largest scrollable container", () => {
+  const page = pagedBox(800, 800);
+  const small = pagedBox(600, 200);
+  small.clientWidth = 100;
+  const main = pagedBox(5000, 700);
+  const hidden = pagedBox(5000, 700, "hidden");
+  hidden.clientWidth = 1000;
+  pakeScrollPage(page, [small, hidden, main]).pakeScroll("down", null);
+  assert.deepEqual(plain(main.calls), [["by", 630]]);
+  assert.deepEqual([page.calls, small.calls, hidden.calls].map((calls) => calls.length), [0, 0, 0]);
+});
+
+function uiScalePage(engineZoom, cssZoom) {
+  const properties = {};
+  const html = {
+    style: {
+      zoom: cssZoom,
+      setProperty: (name, value) => (properties[name] = value),
+    },
+  };
+  const context = load(["updateUiScale"], { engineZoom, document: { documentElement: html } });
+  context.updateUiScale();
+  return Number(properties["--pake-ui-scale"]);
+}
+
+test("injected UI counter-scales both the engine zoom and the html zoom", () => {
+  assert.equal(uiScalePage(1, ""), 1);
+  assert.equal(uiScalePage(2, ""), 0.5);
+  assert.equal(uiScalePage(1, "125%"), 0.8);
+  assert.equal(uiScalePage(2, "50%"), 1);
+});
+
+test("injected UI is right-to-left for rtl or an RTL override_locale", () => {
+  const isRtl = (config) => load(["isRtlUi"], { pakeConfig: config }).isRtlUi();
+  assert.equal(isRtl({}), false);
+  assert.equal(isRtl({ override_locale: "ar-EG" }), true);
+  assert.equal(isRtl({ override_locale: "he_IL" }), true);
+  assert.equal(isRtl({ override_locale: "fr-CA" }), false);
+  assert.equal(isRtl({ rtl: false, override_locale: "fa-IR" }), false);
+  assert.equal(isRtl({ rtl: true, override_locale: "en-US" }), true);
+});
+
+test("toast is mirrored to the bottom left for right-to-left UI", () => {
+  const toast = (uiRtl) => {
+    const children = [];
+    load(["pakeToast"], {
+      uiRtl,
+      document: {
+        body: { appendChild: (child) => children.push(child) },
+        createElement: () => ({ style: {} }),
+      },
+      setTimeout: () => {},
+    }).pakeToast("Saved");
+    return children[0];
+  };
+  const rtl = toast(true);
+  assert.equal(rtl.dir, "rtl");
+  assert.match(rtl.style.cssText, /left: 16px;transform: translate\(50%/);
+  const ltr = toast(false);
+  assert.equal(ltr.dir, "ltr");
+  assert.match(ltr.style.cssText, /right: 16px;transform: translate\(-50%/);
+});
+
+class FakeForm {
+  constructor(rel = "") {
+    this.rel = rel;
+  }
+}
+
+function referrerPage(config, top = true) {
+  const listeners = {};
+  const window = {};
+  window.top = top ? window : {};
+  const context = load(["installReferrerStripping"], {
+    pakeConfig: config,
+    window,
+    document: { addEventListener: (type, listener) => (listeners[type] = listener) },
+    HTMLFormElement: FakeForm,
+  });
+  context.installReferrerStripping();
+  return listeners;
+}
+
+test("strip_referrer marks clicked links and submitted forms as no-referrer", () => {
+  const listeners = referrerPage({ strip_referrer: true });
+  const link = { referrerPolicy: "" };
+  listeners.click({ target: { closest: (selector) => (selector.includes("a[href]") ? link : null) } });
+  assert.equal(link.referrerPolicy, "no-referrer");
+  listeners.click({ target: {} });
+
+  const form = new FakeForm("external");
+  listeners.submit({ target: form });
+  listeners.submit({ target: form });
+  assert.equal(form.rel, "external noreferrer");
+  const bare = new FakeForm();
+  listeners.submit({ target: bare });
+  assert.equal(bare.rel, "noreferrer");
+});
+
+test("strip_referrer is off by default and inside frames", () => {
+  assert.deepEqual(referrerPage({}), {});
+  assert.deepEqual(referrerPage({ strip_referrer: true }, false), {});
+});
+
+test("signed IPC messages bypass native senders replaced by the page", () => {
+  const received = [];
+  const leaked = [];
+  const chromeWindow = { chrome: { webview: { postMessage: (message) => received.push(message) } } };
+  const webkitWindow = {
+    webkit: { messageHandlers: { ipc: { postMessage: (message) => received.push(message) } } },
+  };
+  for (const window of [chromeWindow, webkitWindow]) {
+    const send = load(["nativeIpcSender"], { window }).nativeIpcSender();
+    // 页面脚本在 pake.js 之后替换原生方法，试图截获令牌
+    if (window.chrome) {
+      window.chrome.webview.postMessage = (message) => leaked.push(message);
+      window.chrome.webview = { postMessage: (message) => leaked.push(message) };
+    } else {
+      window.webkit.messageHandlers.ipc.postMessage = (message) => leaked.push(message);
+    }
+    send("secure:token:navigate:https://example.com");
+  }
+  assert.deepEqual(received, [
+    "secure:token:navigate:https://example.com",
+    "secure:token:navigate:https://example.com",
+  ]);
+  assert.deepEqual(leaked, []);
+  assert.equal(load(["nativeIpcSender"], { window: {} }).nativeIpcSender(), null);
+});
+
+test("every config field read by pake.js is injected into the page", () => {
+  const config = readFileSync(new URL("../src/config.rs", import.meta.url), "utf8");
+  const list = config.match(/const PAGE_FIELDS: &\[&str\] = &\[([^\]]*)\];/);
+  assert.ok(list, "config.rs 中没有 PAGE_FIELDS");
+  const injected = new Set([...list[1].matchAll(/"([a-z0-9_]+)"/g)].map((match) => match[1]));
+  const read = new Set([...source.matchAll(/pakeConfig\.([a-z0-9_]+)/g)].map((match) => match[1]));
+  assert.deepEqual([...read].filter((field) => !injected.has(field)), []);
+});